@@ -0,0 +1,214 @@
+//! Parses the arguments of the subcommands.
+
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use miratope_core::geometry::Point;
+
+/// Any error encountered while running a subcommand.
+#[derive(Debug)]
+pub enum CliError {
+    /// The arguments were invalid.
+    Usage(String),
+
+    /// A polytope couldn't be read from a file.
+    Read {
+        /// The path to the file.
+        path: String,
+
+        /// The error message.
+        message: String,
+    },
+
+    /// A polytope couldn't be written to a file.
+    Write {
+        /// The path to the file.
+        path: String,
+
+        /// The error message.
+        message: String,
+    },
+
+    /// An operation failed on the polytope.
+    Operation(String),
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{}", message),
+            Self::Read { path, message } => write!(f, "could not read {}: {}", path, message),
+            Self::Write { path, message } => write!(f, "could not write {}: {}", path, message),
+            Self::Operation(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// The result of running a subcommand.
+pub type CliResult<T> = Result<T, CliError>;
+
+/// The arguments of a subcommand, split into positional arguments, options
+/// with values like `--height 2`, and flags like `--flatten`.
+#[derive(Debug, Default)]
+pub struct Args {
+    /// The positional arguments, in order.
+    positional: Vec<String>,
+
+    /// The values of the options, indexed by their names without the dashes.
+    options: HashMap<String, String>,
+
+    /// The flags that were set, without the dashes.
+    flags: Vec<String>,
+}
+
+impl Args {
+    /// Parses the arguments of a subcommand. The names in `flags` are taken to
+    /// be flags, any other argument starting with `--` is an option followed
+    /// by its value. `-o` is short for `--output`, and a lone `-` is a
+    /// positional argument standing for the standard input.
+    pub fn parse<T: IntoIterator<Item = String>>(args: T, flags: &[&str]) -> CliResult<Self> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let name = if arg == "-o" {
+                "output"
+            } else if let Some(name) = arg.strip_prefix("--") {
+                name
+            } else {
+                parsed.positional.push(arg);
+                continue;
+            };
+
+            if let Some((name, value)) = name.split_once('=') {
+                parsed.options.insert(name.to_string(), value.to_string());
+            } else if flags.contains(&name) {
+                parsed.flags.push(name.to_string());
+            } else {
+                match args.next() {
+                    Some(value) => {
+                        parsed.options.insert(name.to_string(), value);
+                    }
+                    None => return Err(CliError::Usage(format!("missing value for --{}", name))),
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns the positional arguments.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    /// Returns the single positional argument, which is usually the input.
+    pub fn single(&self) -> CliResult<&str> {
+        match self.positional.as_slice() {
+            [arg] => Ok(arg),
+            [] => Err(CliError::Usage("missing input file".to_string())),
+            _ => Err(CliError::Usage(format!(
+                "expected a single input file, found {}",
+                self.positional.len()
+            ))),
+        }
+    }
+
+    /// Returns whether a flag was set.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// Returns the raw value of an option, if it was set.
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /// Parses the value of an option, if it was set.
+    pub fn parse_option<T: FromStr>(&self, name: &str) -> CliResult<Option<T>>
+    where
+        T::Err: Display,
+    {
+        self.option(name)
+            .map(|value| {
+                value.parse().map_err(|err| {
+                    CliError::Usage(format!("invalid value \"{}\" for --{}: {}", value, name, err))
+                })
+            })
+            .transpose()
+    }
+
+    /// Parses a comma-separated list of values of an option, if it was set.
+    pub fn parse_list<T: FromStr>(&self, name: &str) -> CliResult<Option<Vec<T>>>
+    where
+        T::Err: Display,
+    {
+        self.option(name)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|entry| {
+                        entry.trim().parse().map_err(|err| {
+                            CliError::Usage(format!(
+                                "invalid entry \"{}\" for --{}: {}",
+                                entry, name, err
+                            ))
+                        })
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Parses a point given as a comma-separated list of coordinates, if it
+    /// was set.
+    pub fn parse_point(&self, name: &str) -> CliResult<Option<Point<f64>>> {
+        Ok(self.parse_list(name)?.map(Point::from_vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses a list of arguments.
+    fn parse(args: &[&str], flags: &[&str]) -> Args {
+        Args::parse(args.iter().map(|arg| arg.to_string()), flags).unwrap()
+    }
+
+    /// Positional arguments, options and flags are told apart.
+    #[test]
+    fn parse_args() {
+        let args = parse(
+            &["a.off", "--height", "2", "-", "--flatten", "--radius=0.5", "-o", "b.off"],
+            &["flatten"],
+        );
+
+        assert_eq!(args.positional(), ["a.off", "-"]);
+        assert_eq!(args.parse_option::<f64>("height").unwrap(), Some(2.0));
+        assert_eq!(args.parse_option::<f64>("radius").unwrap(), Some(0.5));
+        assert_eq!(args.option("output"), Some("b.off"));
+        assert!(args.flag("flatten"));
+        assert!(!args.flag("retroprism"));
+    }
+
+    /// Lists and points are comma-separated.
+    #[test]
+    fn parse_lists() {
+        let args = parse(&["--rings", "0,2", "--center", "1, 0.5,-1"], &[]);
+
+        assert_eq!(args.parse_list::<usize>("rings").unwrap(), Some(vec![0, 2]));
+        assert_eq!(
+            args.parse_point("center").unwrap(),
+            Some(Point::from_vec(vec![1.0, 0.5, -1.0]))
+        );
+        assert!(args.parse_list::<usize>("center").is_err());
+    }
+
+    /// Options need values.
+    #[test]
+    fn missing_value() {
+        assert!(Args::parse(vec!["--height".to_string()], &[]).is_err());
+    }
+}
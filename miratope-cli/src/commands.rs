@@ -0,0 +1,612 @@
+//! The subcommands of the command-line interface. Each of them mirrors one of
+//! the operations in the top panel of Miratope.
+
+use std::{
+    ffi::OsStr,
+    fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use crate::args::{Args, CliError, CliResult};
+
+use miratope_core::{
+    abs::Ranked,
+    conc::{
+        faceting::{FacetingOptions, GroupEnum, PrintProgress},
+        Concrete, ConcretePolytope,
+    },
+    file::{
+        off::{OffOptions, OffReader},
+        FromFile,
+    },
+    geometry::{Hyperplane, Hypersphere, Point},
+    name::Name,
+    Polytope,
+};
+
+/// Reads a polytope and its name from a path. OFF files are read from the
+/// standard input if the path is `-`. If the file doesn't store a name, the
+/// file name is used instead.
+pub fn read(path: &str) -> CliResult<(Concrete, Name)> {
+    let read_error = |message: String| CliError::Read {
+        path: path.to_string(),
+        message,
+    };
+
+    if path == "-" {
+        read_off(io::stdin().lock(), path)
+    } else if Path::new(path).extension() == Some(OsStr::new("off")) {
+        let file = fs::File::open(path).map_err(|err| read_error(err.to_string()))?;
+        read_off(BufReader::new(file), path)
+    } else {
+        let poly = Concrete::from_path(&path).map_err(|err| read_error(err.to_string()))?;
+        Ok((poly, file_name(path)))
+    }
+}
+
+/// Reads a polytope and its name from an OFF file at a given path.
+fn read_off<R: BufRead>(reader: R, path: &str) -> CliResult<(Concrete, Name)> {
+    let mut reader = OffReader::new(reader);
+    let name = reader.name().unwrap_or_else(|| file_name(path));
+    let poly = reader.build().map_err(|err| CliError::Read {
+        path: path.to_string(),
+        message: err.to_string(),
+    })?;
+
+    Ok((poly, name))
+}
+
+/// Returns the name of a file without its extension.
+fn file_name(path: &str) -> Name {
+    match Path::new(path).file_stem() {
+        Some(stem) if path != "-" => stem.to_string_lossy().into_owned().into(),
+        _ => Name::default(),
+    }
+}
+
+/// Writes a polytope and its name to a path, or as an OFF file to the
+/// standard output if there's no path or it's `-`. GGB files don't store the
+/// name.
+pub fn write(poly: &Concrete, name: Name, path: Option<&str>) -> CliResult<()> {
+    write_with(
+        poly,
+        OffOptions {
+            name: Some(name),
+            ..Default::default()
+        },
+        path,
+    )
+}
+
+/// Writes a polytope to a path like [`write`], with the given options for the
+/// OFF file.
+fn write_with(poly: &Concrete, options: OffOptions, path: Option<&str>) -> CliResult<()> {
+    let path = path.unwrap_or("-");
+    let write_error = |message: String| CliError::Write {
+        path: path.to_string(),
+        message,
+    };
+
+    if path == "-" {
+        let off = poly.to_off(options).map_err(|err| write_error(err.to_string()))?;
+        print!("{}", off);
+        return Ok(());
+    }
+
+    match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+        Some("off") => poly
+            .to_path(path, options)
+            .map_err(|err| write_error(err.to_string())),
+        Some("ggb") => poly
+            .to_ggb_path(path)
+            .map_err(|err| write_error(err.to_string())),
+        Some(ext) => Err(write_error(format!("can't write files with extension \"{}\"", ext))),
+        None => Err(write_error("the file has no extension".to_string())),
+    }
+}
+
+/// Runs a subcommand with the given arguments.
+pub fn run(command: &str, args: Vec<String>) -> CliResult<()> {
+    match command {
+        "dual" => dual(&Args::parse(args, &[])?),
+        "prism" => prism(&Args::parse(args, &[])?),
+        "pyramid" => pyramid(&Args::parse(args, &[])?),
+        "antiprism" => antiprism(&Args::parse(args, &["retroprism"])?),
+        "duoprism" => duoprism(&Args::parse(args, &[])?),
+        "compound" => compound(&Args::parse(args, &[])?),
+        "truncate" => truncate(&Args::parse(args, &[])?),
+        "petrial" => petrial(&Args::parse(args, &[])?),
+        "facet" => facet(&Args::parse(
+            args,
+            &["any-edge-length", "chiral", "compounds", "uniform", "exclude-hemis"],
+        )?),
+        "cross-section" => cross_section(&Args::parse(args, &["flatten"])?),
+        "props" => props(&Args::parse(args, &[])?),
+        "convert" => convert(&Args::parse(args, &["stella"])?),
+        "eval" => eval(&Args::parse(args, &[])?),
+        _ => Err(CliError::Usage(format!("unknown command \"{}\"", command))),
+    }
+}
+
+/// Returns the dimension of the space a polytope lives in.
+fn dim(poly: &Concrete) -> CliResult<usize> {
+    poly.dim()
+        .ok_or_else(|| CliError::Operation("the polytope has no vertices".to_string()))
+}
+
+/// Checks that a point has a given dimension.
+fn check_dim(name: &str, point: &Point<f64>, dim: usize) -> CliResult<()> {
+    if point.len() == dim {
+        Ok(())
+    } else {
+        Err(CliError::Usage(format!(
+            "--{} has {} coordinates, but the polytope is {}-dimensional",
+            name,
+            point.len(),
+            dim
+        )))
+    }
+}
+
+/// Builds the dual of a polytope, with respect to the unit sphere at the
+/// origin unless `--radius` or `--center` are given.
+fn dual(args: &Args) -> CliResult<()> {
+    let (mut poly, name) = read(args.single()?)?;
+    let dim = dim(&poly)?;
+
+    let radius = args.parse_option("radius")?.unwrap_or(1.0);
+    let center = args.parse_point("center")?.unwrap_or_else(|| Point::zeros(dim));
+    check_dim("center", &center, dim)?;
+
+    poly.try_dual_mut_with(&Hypersphere::with_radius(center, radius))
+        .map_err(|err| CliError::Operation(format!("dual failed: {}", err)))?;
+    write(&poly, name.dual(), args.option("output"))
+}
+
+/// Builds a prism of a given `--height`.
+fn prism(args: &Args) -> CliResult<()> {
+    let (poly, name) = read(args.single()?)?;
+    let height = args.parse_option("height")?.unwrap_or(1.0);
+
+    write(&poly.prism_with(height), name.prism(), args.option("output"))
+}
+
+/// Builds a pyramid with an apex at a given `--height` over an `--offset` of
+/// the base.
+fn pyramid(args: &Args) -> CliResult<()> {
+    let (poly, name) = read(args.single()?)?;
+    let dim = dim(&poly)?;
+
+    let height = args.parse_option("height")?.unwrap_or(1.0);
+    let offset = args.parse_point("offset")?.unwrap_or_else(|| Point::zeros(dim));
+    check_dim("offset", &offset, dim)?;
+
+    write(
+        &poly.pyramid_with(offset.push(height)),
+        name.pyramid(),
+        args.option("output"),
+    )
+}
+
+/// Builds an antiprism of a given `--height`, whose bases are dual with
+/// respect to a sphere with a given `--radius` and `--center`. The
+/// `--retroprism` flag builds a retroprism instead.
+fn antiprism(args: &Args) -> CliResult<()> {
+    let (poly, name) = read(args.single()?)?;
+    let dim = dim(&poly)?;
+
+    let height = args.parse_option("height")?.unwrap_or(1.0);
+    let radius: f64 = args.parse_option("radius")?.unwrap_or(1.0);
+    let center = args.parse_point("center")?.unwrap_or_else(|| Point::zeros(dim));
+    check_dim("center", &center, dim)?;
+
+    let mut squared_radius = radius * radius;
+    if args.flag("retroprism") {
+        squared_radius *= -1.0;
+    }
+    let sphere = Hypersphere::with_squared_radius(center, squared_radius);
+
+    let antiprism = poly
+        .try_antiprism_with(&sphere, height)
+        .map_err(|err| CliError::Operation(format!("antiprism failed: {}", err)))?;
+    write(&antiprism, name.antiprism(), args.option("output"))
+}
+
+/// Builds the duoprism of two polytopes.
+fn duoprism(args: &Args) -> CliResult<()> {
+    match args.positional() {
+        [p, q] => {
+            let (p, p_name) = read(p)?;
+            let (q, q_name) = read(q)?;
+
+            write(
+                &p.duoprism(&q),
+                Name::Duoprism(vec![p_name, q_name]),
+                args.option("output"),
+            )
+        }
+        _ => Err(CliError::Usage("duoprism takes two input files".to_string())),
+    }
+}
+
+/// Builds the compound of some polytopes in the same space.
+fn compound(args: &Args) -> CliResult<()> {
+    if args.positional().is_empty() {
+        return Err(CliError::Usage("missing input files".to_string()));
+    }
+
+    let mut components = Vec::new();
+    let mut names = Vec::new();
+    for path in args.positional() {
+        let (poly, name) = read(path)?;
+        if let Some(first) = components.first() {
+            if dim(first)? != dim(&poly)? {
+                return Err(CliError::Operation(format!(
+                    "{} doesn't have the same dimension as the other components",
+                    path
+                )));
+            }
+        }
+
+        components.push(poly);
+        names.push((1, name));
+    }
+
+    let compound = Concrete::compound(components.into_iter());
+    write(&compound, Name::Compound(names), args.option("output"))
+}
+
+/// Truncates a polytope. `--rings` lists the ringed nodes, and `--depths`
+/// the weights of each of them, which default to 1.
+fn truncate(args: &Args) -> CliResult<()> {
+    let (mut poly, name) = read(args.single()?)?;
+    let dim = dim(&poly)?;
+
+    let rings: Vec<usize> = args
+        .parse_list("rings")?
+        .ok_or_else(|| CliError::Usage("missing --rings".to_string()))?;
+    if let Some(ring) = rings.iter().find(|&&ring| ring >= dim) {
+        return Err(CliError::Usage(format!(
+            "ring {} is out of range for a {}-dimensional polytope",
+            ring, dim
+        )));
+    }
+
+    let depths = args.parse_list("depths")?.unwrap_or_else(|| vec![1.0; dim]);
+    if depths.len() != dim {
+        return Err(CliError::Usage(format!(
+            "--depths has {} entries, but the polytope is {}-dimensional",
+            depths.len(),
+            dim
+        )));
+    }
+
+    poly.element_sort();
+    write(
+        &poly.truncate_with(rings, depths),
+        format!("Truncated {}", name).into(),
+        args.option("output"),
+    )
+}
+
+/// Builds the Petrial of a polytope.
+fn petrial(args: &Args) -> CliResult<()> {
+    let (mut poly, name) = read(args.single()?)?;
+
+    if poly.petrial_mut() {
+        write(&poly, name.petrial(), args.option("output"))
+    } else {
+        Err(CliError::Operation("the polytope has no Petrial".to_string()))
+    }
+}
+
+/// Enumerates the facetings of a polytope, and saves them to the `--output`
+/// directory. The options can be loaded from a `--preset` saved from
+/// Miratope, and then changed individually.
+fn facet(args: &Args) -> CliResult<()> {
+    let (mut poly, _) = read(args.single()?)?;
+    let output = args
+        .option("output")
+        .ok_or_else(|| CliError::Usage("facet needs an --output directory".to_string()))?;
+
+    if poly.rank() < 4 {
+        return Err(CliError::Operation(
+            "faceting polytopes of rank less than 3 is not supported".to_string(),
+        ));
+    }
+
+    let mut options = match args.option("preset") {
+        Some(path) => {
+            let read_error = |message: String| CliError::Read {
+                path: path.to_string(),
+                message,
+            };
+            let src = fs::read_to_string(path).map_err(|err| read_error(err.to_string()))?;
+            ron::from_str(&src).map_err(|err| read_error(err.to_string()))?
+        }
+        None => FacetingOptions::new().with_edge_length(1.0),
+    };
+
+    if let Some(edge_length) = args.parse_option("edge-length")? {
+        options = options.with_edge_length(edge_length);
+    }
+    if let Some(min) = args.parse_option("min-edge-length")? {
+        options.min_edge_length = Some(min);
+    }
+    if let Some(max) = args.parse_option("max-edge-length")? {
+        options.max_edge_length = Some(max);
+    }
+    if args.flag("any-edge-length") {
+        options.any_single_edge_length = true;
+    }
+    if let Some(noble) = args.parse_option("noble")? {
+        options.noble = Some(noble);
+    }
+    if let Some(max_per_hyperplane) = args.parse_option("max-per-hyperplane")? {
+        options.max_per_hyperplane = Some(max_per_hyperplane);
+    }
+    if let Some(max_results) = args.parse_option("max-results")? {
+        options.max_results = Some(max_results);
+    }
+    if let Some(checkpoint) = args.option("checkpoint") {
+        options.checkpoint = Some(PathBuf::from(checkpoint));
+    }
+    options.uniform |= args.flag("uniform");
+    options.include_compounds |= args.flag("compounds");
+    options.exclude_hemis |= args.flag("exclude-hemis");
+
+    fs::create_dir_all(output).map_err(|err| CliError::Write {
+        path: output.to_string(),
+        message: err.to_string(),
+    })?;
+    let options = options.with_save_to_file(Some(PathBuf::from(output)));
+
+    let vertices = poly.vertices.clone();
+    let symmetry = GroupEnum::Chiral(args.flag("chiral"));
+    poly.faceting_with_progress(vertices, symmetry, &options, &mut PrintProgress::default());
+    Ok(())
+}
+
+/// Takes a cross-section of a polytope by the hyperplane with a given
+/// `--normal` and `--offset` from the origin. The `--flatten` flag projects it
+/// into a space of one dimension less.
+fn cross_section(args: &Args) -> CliResult<()> {
+    let (poly, name) = read(args.single()?)?;
+    let dim = dim(&poly)?;
+
+    if poly.rank() < 4 {
+        return Err(CliError::Operation(
+            "can't take cross-sections of polygons or lower".to_string(),
+        ));
+    }
+
+    let normal = args
+        .parse_point("normal")?
+        .ok_or_else(|| CliError::Usage("missing --normal".to_string()))?;
+    check_dim("normal", &normal, dim)?;
+    let normal = normal
+        .try_normalize(0.0)
+        .ok_or_else(|| CliError::Usage("--normal can't be zero".to_string()))?;
+    let offset = args.parse_option("offset")?.unwrap_or(0.0);
+
+    let hyperplane = Hyperplane::new(normal, offset);
+    let mut slice = poly.cross_section(&hyperplane);
+    if args.flag("flatten") {
+        slice.flatten_into(&hyperplane.subspace);
+        slice.recenter_with(&hyperplane.flatten(&hyperplane.project(&Point::zeros(dim))));
+    }
+
+    write(
+        &slice,
+        format!("Cross-section of {}", name).into(),
+        args.option("output"),
+    )
+}
+
+/// Prints the properties of some polytopes: their element counts, volume,
+/// circumradius, flag count, symmetry order and orientability.
+fn props(args: &Args) -> CliResult<()> {
+    if args.positional().is_empty() {
+        return Err(CliError::Usage("missing input files".to_string()));
+    }
+
+    for path in args.positional() {
+        let (mut poly, name) = read(path)?;
+        poly.element_sort();
+
+        println!("{}", path);
+        println!("  name: {}", name);
+        println!("  rank: {}", poly.rank() as isize - 1);
+        println!(
+            "  element counts: {}",
+            poly.abs
+                .el_count_iter()
+                .map(|count| count.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        match poly.volume() {
+            Some(volume) => println!("  volume: {}", volume),
+            None => println!("  volume: none"),
+        }
+        match poly.circumsphere() {
+            Some(sphere) => println!("  circumradius: {}", sphere.radius()),
+            None => println!("  circumradius: none"),
+        }
+        println!("  flags: {}", poly.flags().count());
+        match poly.get_symmetry_group() {
+            Some((group, _)) => println!("  symmetry order: {}", group.count()),
+            None => println!("  symmetry order: unknown"),
+        }
+        println!("  orientable: {}", if poly.orientable() { "yes" } else { "no" });
+    }
+
+    Ok(())
+}
+
+/// Converts a polytope from one file format into another. With `--stella`,
+/// OFF files are written in the dialect Stella4D reads, with colored faces.
+fn convert(args: &Args) -> CliResult<()> {
+    let (poly, name) = read(args.single()?)?;
+
+    if args.flag("stella") {
+        write_with(&poly, OffOptions::stella(Some(name)), args.option("output"))
+    } else {
+        write(&poly, name, args.option("output"))
+    }
+}
+
+/// Builds a polytope from an expression like `dual(prism(star(5, 2))) x cube`.
+/// The expression may be split over several arguments, which are joined by
+/// spaces.
+fn eval(args: &Args) -> CliResult<()> {
+    if args.positional().is_empty() {
+        return Err(CliError::Usage("missing expression".to_string()));
+    }
+
+    let expr = args.positional().join(" ");
+    let poly = Concrete::from_expr(&expr).map_err(|err| CliError::Operation(err.to_string()))?;
+    write(&poly, expr.into(), args.option("output"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a path in the temporary directory.
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("miratope-cli-{}", name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Writes a polytope to a temporary OFF file.
+    fn temp_off(poly: &Concrete, name: &str) -> String {
+        let path = temp_path(&format!("{}.off", name));
+        write(poly, name.into(), Some(&path)).unwrap();
+        path
+    }
+
+    /// Runs a subcommand with some arguments.
+    fn run_with(command: &str, args: &[&str]) -> CliResult<()> {
+        run(command, args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    /// Operations read and write OFF files, keeping track of the names.
+    #[test]
+    fn operations() {
+        let square = temp_off(&Concrete::polygon(4), "square");
+        let output = temp_path("square-prism.off");
+
+        run_with("prism", &[&square, "-o", &output]).unwrap();
+        let (prism, name) = read(&output).unwrap();
+        assert!(prism.abs.el_count_iter().eq([1, 8, 12, 6, 1]));
+        assert_eq!(name, Name::from("square").prism());
+
+        run_with("dual", &[&output, "--radius", "2", "-o", &output]).unwrap();
+        let (dual, _) = read(&output).unwrap();
+        assert!(dual.abs.el_count_iter().eq([1, 6, 12, 8, 1]));
+    }
+
+    /// Polytopes can be converted into GGB files and read back.
+    #[test]
+    fn convert_ggb() {
+        let cube = temp_off(&Concrete::cube(), "cube");
+        let output = temp_path("cube.ggb");
+
+        run_with("convert", &[&cube, "-o", &output]).unwrap();
+        let (read_cube, _) = read(&output).unwrap();
+        assert!(read_cube.abs.el_count_iter().eq([1, 8, 12, 6, 1]));
+
+        let tesseract = temp_off(&Concrete::hypercube(5), "tesseract-ggb");
+        assert!(matches!(
+            run_with("convert", &[&tesseract, "-o", &output]),
+            Err(CliError::Write { .. })
+        ));
+    }
+
+    /// Polytopes can be converted into colored OFF files for Stella4D, which
+    /// we can read back.
+    #[test]
+    fn convert_stella() {
+        let tesseract = temp_off(&Concrete::hypercube(5), "tesseract-stella-in");
+        let output = temp_path("tesseract-stella.off");
+
+        run_with("convert", &[&tesseract, "--stella", "-o", &output]).unwrap();
+        let src = std::fs::read_to_string(&output).unwrap();
+        assert!(src.lines().any(|line| line == "4OFF"));
+
+        // Faces are followed by their colors.
+        assert!(src
+            .lines()
+            .any(|line| line.starts_with("4 ") && line.split_whitespace().count() == 8));
+
+        let (read_tesseract, _) = read(&output).unwrap();
+        assert!(read_tesseract.abs.el_count_iter().eq([1, 16, 32, 24, 8, 1]));
+    }
+
+    /// Products and compounds take several inputs.
+    #[test]
+    fn products() {
+        let triangle = temp_off(&Concrete::polygon(3), "triangle");
+        let output = temp_path("triangle-duoprism.off");
+
+        run_with("duoprism", &[&triangle, &triangle, "-o", &output]).unwrap();
+        let (duoprism, _) = read(&output).unwrap();
+        assert!(duoprism.abs.el_count_iter().eq([1, 9, 18, 15, 6, 1]));
+
+        assert!(matches!(
+            run_with("duoprism", &[&triangle]),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    /// Expressions are evaluated and named after themselves.
+    #[test]
+    fn eval() {
+        let output = temp_path("eval.off");
+
+        run_with("eval", &["prism(pentagon)", "x", "dyad", "-o", &output]).unwrap();
+        let (poly, name) = read(&output).unwrap();
+        assert!(poly.abs.el_count_iter().eq([1, 20, 40, 29, 9, 1]));
+        assert_eq!(name, Name::from("prism(pentagon) x dyad"));
+
+        assert!(matches!(run_with("eval", &[]), Err(CliError::Usage(_))));
+        assert!(matches!(
+            run_with("eval", &["prism("]),
+            Err(CliError::Operation(_))
+        ));
+    }
+
+    /// Invalid arguments are reported.
+    #[test]
+    fn errors() {
+        let square = temp_off(&Concrete::polygon(4), "square-errors");
+
+        assert!(matches!(run_with("frobnicate", &[]), Err(CliError::Usage(_))));
+        assert!(matches!(
+            run_with("dual", &[&square, "--center", "0,0,0"]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            run_with("truncate", &[&square, "--rings", "5"]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            run_with("convert", &[&temp_path("missing.off")]),
+            Err(CliError::Read { .. })
+        ));
+
+        // The output directory isn't created for polytopes that can't be
+        // faceted.
+        let output = temp_path("square-facetings");
+        assert!(matches!(
+            run_with("facet", &[&square, "-o", &output]),
+            Err(CliError::Operation(_))
+        ));
+        assert!(!std::path::Path::new(&output).exists());
+    }
+}
@@ -0,0 +1,62 @@
+//! A command-line interface for Miratope, which runs its operations on OFF
+//! and GGB files without opening the GUI, so that they can be scripted.
+
+mod args;
+mod commands;
+
+use args::CliError;
+
+/// The help text for the command-line interface.
+const USAGE: &str = "\
+Usage: miratope-cli <command> [options] <input>... [-o <output>]
+
+Inputs are OFF or GGB files, or - for an OFF file read from the standard input.
+Outputs are OFF or GGB files, written as OFF files to the standard output if -o
+is omitted. GGB files only hold polytopes in up to three dimensions. With
+--stella, OFF files are written with colored faces, as Stella4D reads them.
+
+Commands:
+  dual <input> [--radius R] [--center x,y,...]
+  prism <input> [--height H]
+  pyramid <input> [--height H] [--offset x,y,...]
+  antiprism <input> [--height H] [--radius R] [--center x,y,...] [--retroprism]
+  duoprism <input> <input>
+  compound <input>...
+  truncate <input> --rings i,j,... [--depths d,d,...]
+  petrial <input>
+  facet <input> -o <directory> [--preset options.ron] [--edge-length L]
+        [--min-edge-length L] [--max-edge-length L] [--any-edge-length]
+        [--noble N] [--max-per-hyperplane N] [--max-results N]
+        [--checkpoint file.ron] [--chiral] [--uniform] [--compounds]
+        [--exclude-hemis]
+  cross-section <input> --normal x,y,... [--offset D] [--flatten]
+  props <input>...
+  convert <input> -o <output> [--stella]
+  eval <expression> [-o <output>]
+  help";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+
+    let command = match args.next() {
+        Some(command) if command != "help" && command != "--help" && command != "-h" => command,
+        Some(_) => {
+            println!("{}", USAGE);
+            return;
+        }
+        None => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(err) = commands::run(&command, args.collect()) {
+        eprintln!("error: {}", err);
+
+        if let CliError::Usage(_) = err {
+            eprintln!("\n{}", USAGE);
+            std::process::exit(2);
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,138 @@
+//! Measures how long it takes to load large OFF files, both with a single
+//! thread and with the element lists parsed in parallel.
+//!
+//! Run it with `cargo bench -p miratope-core --bench off`. By default, it loads
+//! the largest files in the library, together with a few generated polytopes
+//! with many elements. Other OFF files can be loaded by passing their paths,
+//! as in `cargo bench -p miratope-core --bench off -- path/to/file.off`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use miratope_core::{abs::Ranked, conc::Concrete, file::FromFile, Polytope};
+
+/// The number of times each file is loaded.
+const RUNS: u32 = 5;
+
+/// The number of files from the library that are loaded by default.
+const LIBRARY_FILES: usize = 5;
+
+/// Returns every OFF file in a folder and its subfolders.
+fn off_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.is_dir() {
+                off_files(&path, files);
+            } else if path.extension().map_or(false, |ext| ext == "off") {
+                files.push(path);
+            }
+        }
+    }
+}
+
+/// Returns the largest OFF files in the library.
+fn library_files() -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    off_files(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("../lib"),
+        &mut files,
+    );
+
+    files.sort_by_key(|path| std::cmp::Reverse(file_size(path)));
+    files.truncate(LIBRARY_FILES);
+    files
+}
+
+/// Writes a few large polytopes into temporary OFF files.
+fn generated_files() -> Vec<PathBuf> {
+    let polytopes = [
+        (
+            "400-400 duoprism",
+            Concrete::polygon(400).duoprism(&Concrete::polygon(400)),
+        ),
+        (
+            "40-40-40 trioprism",
+            Concrete::polygon(40)
+                .duoprism(&Concrete::polygon(40))
+                .duoprism(&Concrete::polygon(40)),
+        ),
+    ];
+
+    polytopes
+        .iter()
+        .map(|(name, poly)| {
+            let path = std::env::temp_dir().join(format!("miratope-bench-{}.off", name));
+            poly.to_path(&path, Default::default())
+                .expect("could not write OFF file");
+            path
+        })
+        .collect()
+}
+
+/// Returns the size of a file in bytes.
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+/// Returns the average time it takes to load an OFF file, together with the
+/// loaded polytope.
+fn load(path: &Path) -> (Duration, Concrete) {
+    let mut total = Duration::default();
+    let mut poly = None;
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        poly = Some(Concrete::from_path(&path).expect("could not load OFF file"));
+        total += start.elapsed();
+    }
+
+    (total / RUNS, poly.unwrap())
+}
+
+fn main() {
+    // Cargo passes a --bench flag, which we ignore.
+    let mut paths: Vec<_> = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .map(PathBuf::from)
+        .collect();
+
+    if paths.is_empty() {
+        paths = library_files();
+        paths.extend(generated_files());
+    }
+
+    let single = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    println!(
+        "{:>40} {:>10} {:>10} {:>12} {:>12}",
+        "file",
+        "size (kB)",
+        "elements",
+        "1 thread",
+        format!("{} threads", rayon::current_num_threads())
+    );
+
+    for path in paths {
+        let (single_time, _) = single.install(|| load(&path));
+        let (parallel_time, poly) = load(&path);
+        let elements: usize = poly.el_count_iter().sum();
+
+        println!(
+            "{:>40} {:>10} {:>10} {:>10.1}ms {:>10.1}ms",
+            path.file_stem().unwrap().to_string_lossy(),
+            file_size(&path) / 1000,
+            elements,
+            single_time.as_secs_f64() * 1000.0,
+            parallel_time.as_secs_f64() * 1000.0,
+        );
+    }
+}
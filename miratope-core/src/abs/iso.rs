@@ -0,0 +1,809 @@
+//! Contains the code to test whether two abstract polytopes are isomorphic, to
+//! compute canonical forms for them, and to compute their automorphism groups.
+//!
+//! Both are based on the flag graph of a polytope, whose vertices are its
+//! flags, and whose edges join flags that differ by a single flag change. Once
+//! we fix where a flag is sent, an isomorphism is completely determined on the
+//! flag-connected component containing it.
+
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+};
+
+use super::{flag::Flag, Abstract, ElementMap, Ranked};
+use crate::{
+    group::{permutation::DPermutation, Group},
+    Polytope,
+};
+
+use nalgebra::Dynamic;
+
+use vec_like::VecLike;
+
+/// The flag graph of a polytope.
+struct FlagGraph {
+    /// The rank of the polytope.
+    rank: usize,
+
+    /// The flags of the polytope.
+    flags: Vec<Flag>,
+
+    /// The index of the flag resulting from each flag change. The flag change
+    /// of rank `r` is stored at index `r - 1`.
+    adjacent: Vec<Vec<usize>>,
+
+    /// The flags in each flag-connected component of the polytope.
+    components: Vec<Vec<usize>>,
+}
+
+/// The result of traversing a component of the flag graph from a given flag.
+struct Traversal {
+    /// Describes the component of the flag graph up to relabeling. Two
+    /// traversals have the same code if and only if there's an isomorphism
+    /// between their components sending the flags in one traversal to the
+    /// flags in the other, in order.
+    code: Vec<usize>,
+
+    /// The indices of the flags in the order they were visited.
+    order: Vec<usize>,
+}
+
+impl FlagGraph {
+    /// Builds the flag graph of a polytope. The polytope must be sorted.
+    fn new(polytope: &Abstract) -> Self {
+        let rank = polytope.rank();
+        let flags: Vec<_> = polytope.flags().collect();
+        let indices: HashMap<_, _> = flags
+            .iter()
+            .enumerate()
+            .map(|(idx, flag)| (flag.clone(), idx))
+            .collect();
+
+        let adjacent: Vec<Vec<_>> = flags
+            .iter()
+            .map(|flag| {
+                (1..rank)
+                    .map(|r| indices[&flag.change(polytope, r)])
+                    .collect()
+            })
+            .collect();
+
+        // Finds the flag-connected components via a depth-first search.
+        let mut components = Vec::new();
+        let mut visited = vec![false; flags.len()];
+        for start in 0..flags.len() {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            while let Some(idx) = stack.pop() {
+                component.push(idx);
+
+                for &adj in &adjacent[idx] {
+                    if !visited[adj] {
+                        visited[adj] = true;
+                        stack.push(adj);
+                    }
+                }
+            }
+
+            components.push(component);
+        }
+
+        Self {
+            rank,
+            flags,
+            adjacent,
+            components,
+        }
+    }
+
+    /// Traverses the component of the flag graph containing a given flag in a
+    /// breadth-first manner, applying flag changes in increasing rank order.
+    ///
+    /// For each visited flag, we record the labels of its adjacent flags and of
+    /// its proper elements, where flags and elements are labeled in the order
+    /// they're first found. Recording the elements allows us to tell apart
+    /// polytopes that aren't strongly connected, whose flag graphs alone don't
+    /// determine them.
+    ///
+    /// If the code becomes lexicographically greater than `bound`, the
+    /// traversal is abandoned and `None` is returned.
+    fn traverse(&self, start: usize, bound: Option<&[usize]>) -> Option<Traversal> {
+        let mut flag_labels = HashMap::new();
+        let mut element_labels = vec![HashMap::new(); self.rank + 1];
+        let mut code = Vec::new();
+        let mut order = Vec::new();
+
+        // Whether the code is already known to be less than the bound.
+        let mut less = bound.is_none();
+
+        let mut queue = VecDeque::new();
+        flag_labels.insert(start, 0);
+        queue.push_back(start);
+
+        while let Some(idx) = queue.pop_front() {
+            order.push(idx);
+            let code_len = code.len();
+
+            for &adj in &self.adjacent[idx] {
+                let len = flag_labels.len();
+                code.push(*flag_labels.entry(adj).or_insert_with(|| {
+                    queue.push_back(adj);
+                    len
+                }));
+            }
+
+            let flag = &self.flags[idx];
+            for (r, labels) in element_labels.iter_mut().enumerate().take(self.rank).skip(1) {
+                let len = labels.len();
+                code.push(*labels.entry(flag[r]).or_insert(len));
+            }
+
+            // Compares the new entries against the bound.
+            if !less {
+                let bound = bound.unwrap();
+                for (i, &value) in code.iter().enumerate().skip(code_len) {
+                    match value.cmp(&bound[i]) {
+                        Ordering::Less => {
+                            less = true;
+                            break;
+                        }
+                        Ordering::Greater => return None,
+                        Ordering::Equal => {}
+                    }
+                }
+            }
+        }
+
+        Some(Traversal { code, order })
+    }
+
+    /// Returns the lexicographically least code of any traversal of a
+    /// component, together with the indices of the flags that start a
+    /// traversal with this code.
+    fn canonical_code(&self, component: &[usize]) -> (Vec<usize>, Vec<usize>) {
+        let mut best: Option<Vec<usize>> = None;
+        let mut starts = Vec::new();
+
+        for &start in component {
+            if let Some(traversal) = self.traverse(start, best.as_deref()) {
+                if best.as_ref() != Some(&traversal.code) {
+                    best = Some(traversal.code);
+                    starts.clear();
+                }
+
+                starts.push(start);
+            }
+        }
+
+        (best.unwrap_or_default(), starts)
+    }
+}
+
+/// A canonical form for an abstract polytope, as returned by
+/// [`Abstract::canonical_form`].
+///
+/// Isomorphic polytopes always have the same canonical form. Conversely,
+/// polytopes with the same canonical form are isomorphic, as long as their
+/// flag-connected components don't share any elements other than the minimal
+/// and maximal ones. This is always the case for flag-connected polytopes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CanonicalForm {
+    /// The number of elements of each rank.
+    el_counts: Vec<usize>,
+
+    /// The canonical codes of the flag-connected components of the polytope,
+    /// in sorted order.
+    components: Vec<Vec<usize>>,
+}
+
+/// A partial isomorphism between two polytopes that keeps track of its changes,
+/// so that they can be undone.
+struct PartialMap {
+    /// The image of each element, if it's been set.
+    map: ElementMap<Option<usize>>,
+
+    /// The preimage of each element, if it's been set.
+    inverse: ElementMap<Option<usize>>,
+
+    /// The elements whose image has been set, in order.
+    history: Vec<(usize, usize)>,
+}
+
+impl PartialMap {
+    /// Initializes an empty map between two polytopes with the given element
+    /// counts.
+    fn new(el_counts: &[usize]) -> Self {
+        let map: ElementMap<_> = el_counts.iter().map(|&count| vec![None; count]).collect();
+
+        Self {
+            inverse: map.clone(),
+            map,
+            history: Vec::new(),
+        }
+    }
+
+    /// Maps each element of a flag into the corresponding element of another.
+    /// Returns `false` if this isn't consistent with the map so far.
+    fn insert(&mut self, flag: &Flag, image: &Flag) -> bool {
+        for (r, (&idx, &img)) in flag.iter().zip(image.iter()).enumerate() {
+            match (self.map[(r, idx)], self.inverse[(r, img)]) {
+                (None, None) => {
+                    self.map[(r, idx)] = Some(img);
+                    self.inverse[(r, img)] = Some(idx);
+                    self.history.push((r, idx));
+                }
+                (Some(i), Some(j)) if i == img && j == idx => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Undoes all changes made after the history had a given length.
+    fn undo(&mut self, len: usize) {
+        for (r, idx) in self.history.drain(len..) {
+            let img = self.map[(r, idx)].take().unwrap();
+            self.inverse[(r, img)] = None;
+        }
+    }
+}
+
+/// The data needed to search for an isomorphism between two polytopes.
+struct IsomorphismSearch<'a> {
+    /// The flag graph of the first polytope.
+    graph: &'a FlagGraph,
+
+    /// The flag graph of the second polytope.
+    other: &'a FlagGraph,
+
+    /// The canonical code of each component of the first polytope, together
+    /// with a flag starting a traversal with that code.
+    codes: Vec<(Vec<usize>, usize)>,
+
+    /// The canonical code of each component of the second polytope, together
+    /// with all flags starting a traversal with that code.
+    other_codes: Vec<(Vec<usize>, Vec<usize>)>,
+
+    /// Whether each component of the second polytope has been used.
+    used: Vec<bool>,
+
+    /// The flag each component of the first polytope is mapped to in the
+    /// current partial isomorphism.
+    images: Vec<usize>,
+
+    /// Whether to find all isomorphisms, or to stop after the first one.
+    all: bool,
+
+    /// The isomorphisms found so far, stored as the images of the starting
+    /// flags of each component.
+    found: Vec<Vec<usize>>,
+}
+
+impl<'a> IsomorphismSearch<'a> {
+    /// Initializes a search for isomorphisms between two polytopes.
+    fn new(graph: &'a FlagGraph, other: &'a FlagGraph, all: bool) -> Self {
+        let codes: Vec<_> = graph
+            .components
+            .iter()
+            .map(|component| {
+                let (code, starts) = graph.canonical_code(component);
+                (code, starts[0])
+            })
+            .collect();
+        let other_codes: Vec<_> = other
+            .components
+            .iter()
+            .map(|component| other.canonical_code(component))
+            .collect();
+
+        Self {
+            graph,
+            other,
+            used: vec![false; other_codes.len()],
+            images: Vec::with_capacity(codes.len()),
+            codes,
+            other_codes,
+            all,
+            found: Vec::new(),
+        }
+    }
+
+    /// Attempts to extend a partial isomorphism to the components of the first
+    /// polytope starting from a given one, by backtracking. Returns `true` if
+    /// the search should stop.
+    fn search(&mut self, map: &mut PartialMap, component: usize) -> bool {
+        if component == self.codes.len() {
+            self.found.push(self.images.clone());
+            return !self.all;
+        }
+
+        let start = self.codes[component].1;
+        let order = self.graph.traverse(start, None).unwrap().order;
+
+        for other_component in 0..self.other_codes.len() {
+            if self.used[other_component]
+                || self.other_codes[other_component].0 != self.codes[component].0
+            {
+                continue;
+            }
+
+            self.used[other_component] = true;
+
+            for other_start in self.other_codes[other_component].1.clone() {
+                let other_order = self.other.traverse(other_start, None).unwrap().order;
+                let len = map.history.len();
+
+                let consistent = order.iter().zip(other_order.iter()).all(|(&f, &g)| {
+                    map.insert(&self.graph.flags[f], &self.other.flags[g])
+                });
+
+                self.images.push(other_start);
+                if consistent && self.search(map, component + 1) {
+                    return true;
+                }
+
+                self.images.pop();
+                map.undo(len);
+            }
+
+            self.used[other_component] = false;
+        }
+
+        false
+    }
+}
+
+/// The group of automorphisms of an abstract polytope, as returned by
+/// [`Abstract::automorphism_group`]. An automorphism is a permutation of the
+/// elements of a polytope that preserves incidences.
+///
+/// Since an automorphism is determined by where it sends a single flag in each
+/// flag-connected component, we only store these images, and build the
+/// automorphisms themselves on demand.
+pub struct AutomorphismGroup {
+    /// The flag graph of the polytope.
+    graph: FlagGraph,
+
+    /// The number of elements of each rank of the polytope.
+    el_counts: Vec<usize>,
+
+    /// The flags of each component in the order they're visited, starting from
+    /// a fixed flag.
+    start_orders: Vec<Vec<usize>>,
+
+    /// The image of the starting flag of each component under each
+    /// automorphism.
+    images: Vec<Vec<usize>>,
+}
+
+impl AutomorphismGroup {
+    /// Returns the order of the group, i.e. the number of automorphisms of the
+    /// polytope.
+    pub fn order(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Returns the flags of the polytope, in the order used by the
+    /// permutations of [`Self::flag_permutation`].
+    pub fn flags(&self) -> &[Flag] {
+        &self.graph.flags
+    }
+
+    /// Returns the automorphism with a given index as a permutation of the
+    /// flags of the polytope.
+    pub fn flag_permutation(&self, idx: usize) -> DPermutation {
+        let mut perm = vec![0; self.graph.flags.len()];
+
+        for (order, &image) in self.start_orders.iter().zip(&self.images[idx]) {
+            let image_order = self.graph.traverse(image, None).unwrap().order;
+
+            for (&f, &g) in order.iter().zip(&image_order) {
+                perm[f] = g;
+            }
+        }
+
+        // Safety: an automorphism maps the flags of the polytope bijectively.
+        unsafe { DPermutation::from_iterator(perm, self.graph.flags.len()) }
+    }
+
+    /// Returns the automorphism with a given index as an [`ElementMap`]
+    /// storing the index of the image of each element.
+    pub fn element_map(&self, idx: usize) -> ElementMap<usize> {
+        let perm = self.flag_permutation(idx);
+        let flags = &self.graph.flags;
+        let mut map: ElementMap<_> = self.el_counts.iter().map(|&count| vec![0; count]).collect();
+
+        // Every element belongs to some flag.
+        for (f, flag) in flags.iter().enumerate() {
+            let image = &flags[perm[f]];
+
+            for (r, (&idx, &img)) in flag.iter().zip(image.iter()).enumerate() {
+                map[(r, idx)] = img;
+            }
+        }
+
+        map
+    }
+
+    /// Returns the automorphisms as a [`Group`] of permutations of the flags of
+    /// the polytope.
+    pub fn group(&self) -> Group<impl Iterator<Item = DPermutation> + '_> {
+        let len = self.graph.flags.len();
+
+        // Safety: the automorphisms of a polytope form a group.
+        unsafe {
+            Group::new(
+                Dynamic::new(len),
+                (0..self.order()).map(move |idx| self.flag_permutation(idx)),
+            )
+        }
+    }
+
+    /// Returns the index of the flag orbit of each flag of the polytope.
+    pub fn flag_orbits(&self) -> Vec<usize> {
+        let len = self.graph.flags.len();
+        let mut orbits = vec![None; len];
+        let mut orbit_count = 0;
+
+        let perms: Vec<_> = (0..self.order()).map(|idx| self.flag_permutation(idx)).collect();
+        for f in 0..len {
+            if orbits[f].is_none() {
+                for perm in &perms {
+                    orbits[perm[f]] = Some(orbit_count);
+                }
+
+                orbit_count += 1;
+            }
+        }
+
+        orbits.into_iter().map(Option::unwrap).collect()
+    }
+
+    /// Returns the number of flag orbits of the polytope.
+    pub fn flag_orbit_count(&self) -> usize {
+        // The group acts freely on the flags of a flag-connected polytope.
+        if self.graph.components.len() == 1 {
+            self.graph.flags.len() / self.order()
+        } else {
+            self.flag_orbits().into_iter().max().map_or(0, |max| max + 1)
+        }
+    }
+
+    /// Returns whether the polytope is regular, i.e. whether its automorphism
+    /// group acts transitively on its flags.
+    pub fn is_regular(&self) -> bool {
+        self.flag_orbit_count() == 1
+    }
+
+    /// Returns whether the polytope is a two-orbit polytope, i.e. whether its
+    /// flags fall into exactly two orbits.
+    pub fn is_two_orbit(&self) -> bool {
+        self.flag_orbit_count() == 2
+    }
+
+    /// Returns the ranks of the flag changes that preserve each flag orbit, if
+    /// the polytope is a two-orbit polytope. Otherwise, returns `None`. Unlike
+    /// elsewhere, these ranks start from 0 for vertex changes.
+    ///
+    /// This is the set `I` in the class `2_I` of the polytope, in the notation
+    /// of Hubard.
+    pub fn two_orbit_class(&self) -> Option<Vec<usize>> {
+        if !self.is_two_orbit() {
+            return None;
+        }
+
+        let orbits = self.flag_orbits();
+        Some(
+            (0..self.graph.rank - 1)
+                .filter(|&r| orbits[0] == orbits[self.graph.adjacent[0][r]])
+                .collect(),
+        )
+    }
+
+    /// Returns whether the polytope is chiral, i.e. whether it has two flag
+    /// orbits, with adjacent flags always lying in different orbits.
+    pub fn is_chiral(&self) -> bool {
+        matches!(self.two_orbit_class(), Some(class) if class.is_empty())
+    }
+}
+
+/// Returns the polytope if it's sorted, or a sorted copy of it otherwise.
+fn sorted(polytope: &Abstract) -> Cow<'_, Abstract> {
+    if polytope.sorted() {
+        Cow::Borrowed(polytope)
+    } else {
+        let mut clone = polytope.clone();
+        clone.element_sort();
+        Cow::Owned(clone)
+    }
+}
+
+impl Abstract {
+    /// Computes a [`CanonicalForm`] for the polytope. Isomorphic polytopes
+    /// always have the same canonical form.
+    ///
+    /// This takes time roughly quadratic in the number of flags of the
+    /// polytope.
+    pub fn canonical_form(&self) -> CanonicalForm {
+        let el_counts = self.el_count_iter().collect();
+        let polytope = sorted(self);
+
+        let components = if polytope.rank() < 2 {
+            Vec::new()
+        } else {
+            let graph = FlagGraph::new(&polytope);
+            let mut components: Vec<_> = graph
+                .components
+                .iter()
+                .map(|component| graph.canonical_code(component).0)
+                .collect();
+            components.sort_unstable();
+            components
+        };
+
+        CanonicalForm {
+            el_counts,
+            components,
+        }
+    }
+
+    /// Returns a hash of the [`CanonicalForm`] of the polytope. Isomorphic
+    /// polytopes always have the same hash, which makes this useful to
+    /// deduplicate polytopes.
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.canonical_form().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Finds an isomorphism between two polytopes, if it exists. The
+    /// isomorphism is returned as an [`ElementMap`] storing the index of the
+    /// image of each element.
+    pub fn isomorphism(&self, other: &Self) -> Option<ElementMap<usize>> {
+        if self.rank() != other.rank() || !self.el_count_iter().eq(other.el_count_iter()) {
+            return None;
+        }
+
+        // The nullitope and the point have a single element of each rank.
+        if self.rank() < 2 {
+            return Some(self.el_count_iter().map(|count| vec![0; count]).collect());
+        }
+
+        let polytope = sorted(self);
+        let other = sorted(other);
+        let graph = FlagGraph::new(&polytope);
+        let other_graph = FlagGraph::new(&other);
+
+        if graph.flags.len() != other_graph.flags.len()
+            || graph.components.len() != other_graph.components.len()
+        {
+            return None;
+        }
+
+        let el_counts: Vec<_> = polytope.el_count_iter().collect();
+        let mut map = PartialMap::new(&el_counts);
+        let mut search = IsomorphismSearch::new(&graph, &other_graph, false);
+
+        if search.search(&mut map, 0) {
+            Some(
+                map.map
+                    .into_iter()
+                    .map(|list| list.into_iter().collect::<Option<Vec<_>>>())
+                    .collect::<Option<_>>()?,
+            )
+        } else {
+            None
+        }
+    }
+
+    /// Returns whether two polytopes are isomorphic, i.e. whether they're the
+    /// same up to relabeling of their elements.
+    pub fn is_isomorphic(&self, other: &Self) -> bool {
+        self.isomorphism(other).is_some()
+    }
+
+    /// Computes the [`AutomorphismGroup`] of the polytope, i.e. the group of
+    /// its combinatorial symmetries.
+    pub fn automorphism_group(&self) -> AutomorphismGroup {
+        let polytope = sorted(self);
+        let graph = FlagGraph::new(&polytope);
+        let el_counts: Vec<_> = polytope.el_count_iter().collect();
+
+        let mut search = IsomorphismSearch::new(&graph, &graph, true);
+        search.search(&mut PartialMap::new(&el_counts), 0);
+
+        let starts: Vec<_> = search.codes.iter().map(|(_, start)| *start).collect();
+        let images = search.found;
+        let start_orders = starts
+            .iter()
+            .map(|&start| graph.traverse(start, None).unwrap().order)
+            .collect();
+
+        AutomorphismGroup {
+            graph,
+            el_counts,
+            start_orders,
+            images,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::Concrete, cox::cd::Cd};
+
+    /// Checks that an element map is an isomorphism between two polytopes.
+    fn test_isomorphism(p: &Abstract, q: &Abstract) {
+        let map = p.isomorphism(q).expect("no isomorphism found");
+
+        for r in 1..=p.rank() {
+            for (idx, el) in p[r].iter().enumerate() {
+                let mut subs: Vec<_> = el.subs.iter().map(|&sub| map[(r - 1, sub)]).collect();
+                subs.sort_unstable();
+                let mut image_subs: Vec<_> = q[(r, map[(r, idx)])].subs.iter().copied().collect();
+                image_subs.sort_unstable();
+                assert_eq!(subs, image_subs);
+            }
+        }
+
+        assert_eq!(p.canonical_form(), q.canonical_form());
+    }
+
+    /// Checks that a polytope is isomorphic to itself and to its relabelings.
+    #[test]
+    fn relabel() {
+        for p in [
+            Abstract::nullitope(),
+            Abstract::point(),
+            Abstract::dyad(),
+            Abstract::polygon(5),
+            Abstract::cube(),
+            Abstract::simplex(5),
+        ] {
+            test_isomorphism(&p, &p);
+
+            // Reversing the order of the elements of each rank.
+            let mut q = p.clone();
+            let rank = q.rank();
+            for r in 1..rank {
+                let len = q.el_count(r);
+                let reverse = |idx: usize| len - 1 - idx;
+
+                q[r].reverse();
+                for el in q[r + 1].iter_mut() {
+                    el.subs.iter_mut().for_each(|sub| *sub = reverse(*sub));
+                }
+                for el in q[r - 1].iter_mut() {
+                    el.sups.iter_mut().for_each(|sup| *sup = reverse(*sup));
+                }
+            }
+
+            // Safety: relabeling elements doesn't change the polytope.
+            unsafe {
+                q.set_sorted(false);
+            }
+            test_isomorphism(&p, &q);
+        }
+    }
+
+    /// Checks that different constructions of the same polytopes give
+    /// isomorphic results.
+    #[test]
+    fn constructions() {
+        test_isomorphism(&Abstract::cube(), &Abstract::polygon(4).prism());
+        test_isomorphism(&Abstract::octahedron(), &Abstract::polygon(4).tegum());
+        test_isomorphism(&Abstract::tetrahedron(), &Abstract::polygon(3).pyramid());
+        test_isomorphism(&Abstract::octahedron(), &Abstract::polygon(3).antiprism());
+        test_isomorphism(&Abstract::cube().dual(), &Abstract::octahedron());
+    }
+
+    /// Checks that non-isomorphic polytopes are told apart.
+    #[test]
+    fn non_isomorphic() {
+        let polytopes = [
+            Abstract::cube(),
+            Abstract::polygon(4).pyramid().prism(),
+            Abstract::polygon(6).prism(),
+            Abstract::polygon(4).antiprism(),
+            Abstract::polygon(8).pyramid(),
+            Abstract::cube().petrial().unwrap(),
+        ];
+
+        for (i, p) in polytopes.iter().enumerate() {
+            for (j, q) in polytopes.iter().enumerate() {
+                assert_eq!(p.is_isomorphic(q), i == j);
+                assert_eq!(p.canonical_hash() == q.canonical_hash(), i == j);
+            }
+        }
+    }
+
+    /// Checks compounds, whose flag graphs are disconnected.
+    #[test]
+    fn compound() {
+        let triangle = Abstract::polygon(3);
+        let square = Abstract::polygon(4);
+        let mut p = triangle.clone();
+        p.comp_append(square.clone());
+        let mut q = square;
+        q.comp_append(triangle);
+
+        test_isomorphism(&p, &q);
+        assert!(!p.is_isomorphic(&Abstract::polygon(7)));
+    }
+
+    /// Checks that every automorphism of a polytope preserves incidences.
+    fn test_automorphisms(p: &Abstract, order: usize, flag_orbits: usize) {
+        let group = p.automorphism_group();
+        assert_eq!(group.order(), order);
+        assert_eq!(group.flag_orbit_count(), flag_orbits);
+        assert_eq!(group.group().count(), order);
+
+        for idx in 0..group.order() {
+            let map = group.element_map(idx);
+
+            for r in 1..=p.rank() {
+                for (i, el) in p[r].iter().enumerate() {
+                    let mut subs: Vec<_> = el.subs.iter().map(|&sub| map[(r - 1, sub)]).collect();
+                    subs.sort_unstable();
+                    let mut image_subs: Vec<_> = p[(r, map[(r, i)])].subs.iter().copied().collect();
+                    image_subs.sort_unstable();
+                    assert_eq!(subs, image_subs);
+                }
+            }
+        }
+    }
+
+    /// Checks the automorphism groups of some regular polytopes.
+    #[test]
+    fn regular() {
+        test_automorphisms(&Abstract::point(), 1, 1);
+        test_automorphisms(&Abstract::dyad(), 2, 1);
+        test_automorphisms(&Abstract::polygon(7), 14, 1);
+        test_automorphisms(&Abstract::cube(), 48, 1);
+        test_automorphisms(&Abstract::simplex(5), 120, 1);
+        test_automorphisms(&Abstract::cube().petrial().unwrap(), 48, 1);
+
+        assert!(Abstract::octahedron().automorphism_group().is_regular());
+    }
+
+    /// Checks the automorphism groups of some non-regular polytopes.
+    #[test]
+    fn orbits() {
+        test_automorphisms(&Abstract::polygon(5).prism(), 20, 3);
+        test_automorphisms(&Abstract::polygon(5).pyramid(), 10, 4);
+
+        // The cuboctahedron is a two-orbit polytope, whose vertex and edge
+        // changes preserve the flag orbits.
+        let group = Concrete::from_cd(&Cd::parse("o4x3o").unwrap())
+            .unwrap()
+            .abs
+            .automorphism_group();
+        assert_eq!(group.order(), 48);
+        assert!(group.is_two_orbit());
+        assert_eq!(group.two_orbit_class(), Some(vec![0, 1]));
+        assert!(!group.is_chiral());
+    }
+
+    /// Checks the automorphism groups of compounds, which may swap their
+    /// components.
+    #[test]
+    fn compound_automorphisms() {
+        let mut p = Abstract::polygon(3);
+        p.comp_append(Abstract::polygon(3));
+        test_automorphisms(&p, 72, 1);
+
+        let mut q = Abstract::polygon(3);
+        q.comp_append(Abstract::polygon(4));
+        test_automorphisms(&q, 48, 2);
+    }
+}
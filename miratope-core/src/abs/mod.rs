@@ -2,6 +2,7 @@
 
 pub mod antiprism;
 pub mod flag;
+pub mod iso;
 pub mod product;
 pub mod ranked;
 pub mod valid;
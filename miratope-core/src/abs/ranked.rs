@@ -11,6 +11,7 @@ use std::{
 use super::Abstract;
 
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 use vec_like::*;
 
 /// Represents a map from ranks and indices into elements of a given type.
@@ -50,7 +51,7 @@ impl<T> IndexMut<(usize, usize)> for ElementMap<T> {
 /// refers to any element that's incident and of lesser rank than another. We
 /// instead use the term **recursive subelement** for the standard mathematical
 /// notion.
-#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct Subelements(Vec<usize>);
 impl_veclike!(Subelements, Item = usize);
@@ -68,7 +69,7 @@ impl_veclike!(Subelements, Item = usize);
 /// refers to any element that's incident and of greater rank than another. We
 /// instead use the term **recursive superelement** for the standard
 /// mathematical notion.
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct Superelements(Vec<usize>);
 impl_veclike!(Superelements, Item = usize);
@@ -79,7 +80,7 @@ impl_veclike!(Superelements, Item = usize);
 /// Even though one of these fields would suffice to precisely define an
 /// element in an abstract polytope, we're often are in need of both of them. To
 /// avoid recalculating them every single time, we just store them both.
-#[derive(Default, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Default, Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Element {
     /// The indices of the subelements of the previous rank.
     pub subs: Subelements,
@@ -137,7 +138,7 @@ impl Element {
 /// A list of [`Elements`](Element) of the same rank.
 ///
 /// Internally, this is just a wrapper around a `Vec<Element>`.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct ElementList(pub Vec<Element>);
 impl_veclike!(ElementList, Item = Element);
@@ -286,7 +287,7 @@ pub type ElementIntoIter = iter::Flatten<iter::Map<vec::IntoIter<ElementList>, I
 ///
 /// Contrary to [`Abstract`], there's no requirement that the elements in
 /// `Ranks` form a valid polytope.
-#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Ranks(Vec<ElementList>);
 impl_veclike!(Ranks, Item = ElementList);
 
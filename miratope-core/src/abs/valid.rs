@@ -1,14 +1,13 @@
 //! Contains the code that verifies whether a set of [`Ranks`] correspond to a
 //! valid [`Abstract`](crate::Abstract) polytope.
 
-// TODO: finish these!
-
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use partitions::partition_vec;
 use strum_macros::Display;
 use vec_like::VecLike;
 
-use super::{Ranked, Ranks, Section};
+use super::{Abstract, Ranked, Ranks, Section};
 
 /// Represents the way in which two elements with one rank of difference are
 /// incident to one another. Used as a field in some [`AbstractError`] variants.
@@ -152,16 +151,47 @@ impl std::error::Error for AbstractError {}
 /// The return value for [`Ranks::is_valid`].
 pub type AbstractResult<T> = Result<T, AbstractError>;
 
+/// The optional checks to run when determining whether a polytope is valid.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ValidityOptions {
+    /// Whether to check that the polytope is
+    /// [strongly connected](Ranks::is_strongly_connected). This is
+    /// considerably more expensive than the other checks, and compounds
+    /// always fail it.
+    pub strongly_connected: bool,
+}
+
+impl ValidityOptions {
+    /// The options that check for strong connectivity.
+    pub fn strongly_connected() -> Self {
+        Self {
+            strongly_connected: true,
+        }
+    }
+}
+
 impl Ranks {
     /// Checks whether the ranks form a valid polytope, i.e. whether the poset
     /// is bounded, dyadic, and all of its indices refer to valid elements.
+    ///
+    /// This doesn't check for strong connectivity, since that's considerably
+    /// more expensive. Use [`Self::is_valid_with`] for that.
     pub fn is_valid(&self) -> AbstractResult<()> {
+        self.is_valid_with(ValidityOptions::default())
+    }
+
+    /// Checks whether the ranks form a valid polytope, together with any of
+    /// the optional checks that are set.
+    pub fn is_valid_with(&self, options: ValidityOptions) -> AbstractResult<()> {
         self.bounded()?;
         self.check_incidences()?;
         self.is_dyadic()?;
 
+        if options.strongly_connected {
+            self.is_strongly_connected()?;
+        }
+
         Ok(())
-        // && self.is_strongly_connected()
     }
 
     /// Determines whether the polytope is bounded, i.e. whether it has a single
@@ -274,7 +304,7 @@ impl Ranks {
 
         // For every element, by looking through the subelements of its
         // subelements, we need to find each exactly twice.
-        for r in 2..self.rank() {
+        for r in 2..=self.rank() {
             for (idx, el) in self[r].iter().enumerate() {
                 let mut hash_sub_subs = HashMap::new();
 
@@ -316,18 +346,223 @@ impl Ranks {
         Ok(())
     }
 
-    /// Determines whether the polytope is connected. A valid non-compound
-    /// polytope should always return `true`.
-    pub fn is_connected(&self, _section: Section) -> bool {
-        todo!()
-        /*
-        let section = self.get_section(section).unwrap();
-        section.flags().count() == section.oriented_flags().count() */
+    /// Returns the elements in the section above a given element, indexed by
+    /// rank and ordered by index. The element itself is included.
+    fn elements_above(&self, rank: usize, idx: usize) -> Vec<Vec<usize>> {
+        let mut above = vec![Vec::new(); self.len()];
+        above[rank].push(idx);
+
+        for r in rank..self.rank() {
+            let mut next = HashSet::new();
+            for &i in &above[r] {
+                next.extend(self[(r, i)].sups.iter().copied());
+            }
+
+            above[r + 1] = next.into_iter().collect();
+            above[r + 1].sort_unstable();
+        }
+
+        above
+    }
+
+    /// Determines whether a section of the polytope is connected, i.e.
+    /// whether any two of its proper elements can be joined by a path of
+    /// incident proper elements. Sections of height 2 or less are always
+    /// connected.
+    ///
+    /// # Panics
+    /// This method will panic if the section isn't valid.
+    pub fn is_connected(&self, section: Section) -> AbstractResult<()> {
+        if section.hi_rank < section.lo_rank + 3 {
+            return Ok(());
+        }
+
+        // Finds the proper elements of the section, by intersecting the
+        // elements below the upper element with those above the lower one.
+        let above = self.elements_above(section.lo_rank, section.lo_idx);
+        let mut proper: HashSet<(usize, usize)> = HashSet::new();
+        let mut below = vec![section.hi_idx];
+
+        for r in (section.lo_rank + 1..section.hi_rank).rev() {
+            let mut next = HashSet::new();
+            for &i in &below {
+                next.extend(self[(r + 1, i)].subs.iter().copied());
+            }
+
+            below = next.into_iter().collect();
+            proper.extend(
+                below
+                    .iter()
+                    .filter(|i| above[r].binary_search(i).is_ok())
+                    .map(|&i| (r, i)),
+            );
+        }
+
+        // Traverses the proper elements via incidences.
+        let start = match proper.iter().next() {
+            Some(&el) => el,
+            None => return Ok(()),
+        };
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut stack = vec![start];
+
+        while let Some((r, i)) = stack.pop() {
+            let el = &self[(r, i)];
+            let subs = el.subs.iter().map(|&sub| (r - 1, sub));
+            let sups = el.sups.iter().map(|&sup| (r + 1, sup));
+
+            for next in subs.chain(sups) {
+                if proper.contains(&next) && visited.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        if visited.len() == proper.len() {
+            Ok(())
+        } else {
+            Err(AbstractError::Connected(section))
+        }
+    }
+
+    /// Returns whether the facets of a section are connected through its
+    /// ridges. The section is given by its upper element, and by the elements
+    /// above its lower element, as returned by [`Self::elements_above`].
+    fn facets_connected(&self, above: &[Vec<usize>], rank: usize, idx: usize) -> bool {
+        let in_section = |r: usize, i: &usize| above[r].binary_search(i).is_ok();
+        let facets: Vec<_> = self[(rank, idx)]
+            .subs
+            .iter()
+            .filter(|i| in_section(rank - 1, i))
+            .collect();
+
+        // The facets through each ridge are connected to one another.
+        let mut ridges: HashMap<usize, usize> = HashMap::new();
+        let mut partition = partition_vec![(); facets.len()];
+        for (f, &&facet) in facets.iter().enumerate() {
+            for ridge in &self[(rank - 1, facet)].subs {
+                if in_section(rank - 2, ridge) {
+                    let first = *ridges.entry(*ridge).or_insert(f);
+                    partition.union(first, f);
+                }
+            }
+        }
+
+        (1..facets.len()).all(|f| partition.same_set(0, f))
+    }
+
+    /// Determines whether the polytope is strongly connected, i.e. whether
+    /// all of its sections are [connected](Self::is_connected). A valid
+    /// non-compound polytope should always return `Ok`.
+    pub fn is_strongly_connected(&self) -> AbstractResult<()> {
+        let rank = self.rank();
+
+        // We go through the lower elements from the top down, so that by the
+        // time we check a section, the sections between its upper element
+        // and any of its proper elements are known to be connected. Every
+        // proper element is then connected to all facets of the section above
+        // it, so the section is connected if and only if its facets are
+        // connected through its ridges.
+        for lo_rank in (0..rank.saturating_sub(2)).rev() {
+            for lo_idx in 0..self[lo_rank].len() {
+                let above = self.elements_above(lo_rank, lo_idx);
+
+                for (hi_rank, elements) in above.iter().enumerate().skip(lo_rank + 3) {
+                    for &hi_idx in elements {
+                        if !self.facets_connected(&above, hi_rank, hi_idx) {
+                            return Err(AbstractError::Connected(Section::new(
+                                lo_rank, lo_idx, hi_rank, hi_idx,
+                            )));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Abstract {
+    /// Checks whether the polytope is valid. See [`Ranks::is_valid`].
+    pub fn is_valid(&self) -> AbstractResult<()> {
+        self.ranks().is_valid()
+    }
+
+    /// Checks whether the polytope is valid, together with any of the
+    /// optional checks that are set. See [`Ranks::is_valid_with`].
+    pub fn is_valid_with(&self, options: ValidityOptions) -> AbstractResult<()> {
+        self.ranks().is_valid_with(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        abs::{Abstract, AbstractBuilder, AbstractError, Ranked, Section, ValidityOptions},
+        Polytope,
+    };
+
+    /// Regular polytopes and their products are strongly connected.
+    #[test]
+    fn strongly_connected() {
+        for poly in [
+            Abstract::polygon(5),
+            Abstract::hypercube(4),
+            Abstract::simplex(6),
+            Abstract::orthoplex(5),
+            Abstract::duoprism(&Abstract::polygon(3), &Abstract::polygon(4)),
+        ] {
+            poly.is_valid_with(ValidityOptions::strongly_connected())
+                .unwrap();
+        }
+    }
+
+    /// A compound is neither connected nor strongly connected.
+    #[test]
+    fn compound() {
+        let mut poly = Abstract::polygon(3);
+        poly.comp_append(Abstract::polygon(3));
+        let section = Section::new(0, 0, poly.rank(), 0);
+
+        assert!(poly.ranks().is_connected(section).is_err());
+        assert!(poly.ranks().is_strongly_connected().is_err());
+        poly.is_valid().unwrap();
+        assert!(matches!(
+            poly.is_valid_with(ValidityOptions::strongly_connected()),
+            Err(AbstractError::Connected(_))
+        ));
+    }
+
+    /// The prism of a compound is connected, but one of its facets isn't.
+    #[test]
+    fn compound_prism() {
+        let mut poly = Abstract::polygon(4);
+        poly.comp_append(Abstract::polygon(4));
+        let poly = poly.prism();
+        let section = Section::new(0, 0, poly.rank(), 0);
+
+        assert!(poly.ranks().is_connected(section).is_ok());
+
+        // The section we're pointed to is actually disconnected.
+        match poly.ranks().is_strongly_connected() {
+            Err(AbstractError::Connected(section)) => {
+                assert!(poly.ranks().is_connected(section).is_err())
+            }
+            other => panic!("expected a disconnected section, got {:?}", other),
+        }
     }
 
-    /// Determines whether the polytope is strongly connected. A valid
-    /// non-compound polytope should always return `true`.
-    pub fn is_strongly_connected(&self) -> bool {
-        todo!()
+    /// A dyad with three vertices isn't dyadic, even though the only section
+    /// that fails the diamond property is the whole polytope.
+    #[test]
+    fn non_dyadic_max() {
+        let mut builder = AbstractBuilder::with_rank_capacity(2);
+        builder.push_min();
+        builder.push_vertices(3);
+        builder.push_max();
+
+        assert!(builder.ranks().is_dyadic().is_err());
     }
 }
@@ -0,0 +1,278 @@
+//! Contains the code to [alternate](https://polytope.miraheze.org/wiki/Alternation)
+//! a polytope.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::Concrete;
+use crate::{
+    abs::{AbstractBuilder, Ranked, SubelementList, Subelements},
+    Polytope,
+};
+
+use vec_like::VecLike;
+
+/// The vertices of the alternation of an element, together with the
+/// neighbours of each of the element's removed vertices.
+#[derive(Clone, Default)]
+struct AlternatedElement {
+    /// The kept vertices of the element.
+    vertices: BTreeSet<usize>,
+
+    /// The kept vertices joined by an edge to each removed vertex, within the
+    /// element.
+    neighbors: BTreeMap<usize, BTreeSet<usize>>,
+}
+
+impl Concrete {
+    /// Alternates a polytope, keeping only a given subset of its vertices.
+    ///
+    /// Every element of the polytope is replaced by the element spanned by
+    /// its kept vertices, and every removed vertex is replaced by its vertex
+    /// figure, spanned by its neighbours. Elements whose kept vertices coincide
+    /// with those of a lower-ranked element, like the digons left over from
+    /// alternating a square, collapse into it. Incidences are then determined
+    /// by vertex set inclusion.
+    ///
+    /// For the result to make sense, no two kept vertices should be joined by
+    /// an edge, and neither should two removed vertices. The kept vertices
+    /// are returned in their original order.
+    ///
+    /// Returns `None` if any of the vertex indices is out of range, or if the
+    /// result isn't a valid polytope.
+    pub fn alternate(&self, vertices: &[usize]) -> Option<Self> {
+        let vertex_count = self.vertices.len();
+        if vertices.iter().any(|&v| v >= vertex_count) {
+            return None;
+        }
+
+        let rank = self.rank();
+        if rank == 0 || vertices.is_empty() {
+            return Some(Self::nullitope());
+        }
+
+        // The new index of each kept vertex.
+        let mut new_idx = vec![None; vertex_count];
+        let mut kept: Vec<_> = vertices.to_vec();
+        kept.sort_unstable();
+        kept.dedup();
+        for (idx, &v) in kept.iter().enumerate() {
+            new_idx[v] = Some(idx);
+        }
+
+        // Alternates every element, from the bottom up.
+        let mut alternated: Vec<Vec<AlternatedElement>> = vec![Vec::new(); rank + 1];
+        alternated[1] = new_idx
+            .iter()
+            .map(|idx| AlternatedElement {
+                vertices: idx.iter().copied().collect(),
+                neighbors: BTreeMap::new(),
+            })
+            .collect();
+
+        for r in 2..=rank {
+            for el in &self[r] {
+                let mut alt = AlternatedElement::default();
+
+                for &sub in &el.subs {
+                    let sub_alt = &alternated[r - 1][sub];
+                    alt.vertices.extend(sub_alt.vertices.iter().copied());
+
+                    for (&v, neighbors) in &sub_alt.neighbors {
+                        alt.neighbors
+                            .entry(v)
+                            .or_default()
+                            .extend(neighbors.iter().copied());
+                    }
+                }
+
+                // Each removed vertex of an edge neighbours the other one.
+                if r == 2 {
+                    for &v in &el.subs {
+                        if new_idx[v].is_none() {
+                            let neighbors = alt.vertices.clone();
+                            alt.neighbors.insert(v, neighbors);
+                        }
+                    }
+                }
+
+                alternated[r].push(alt);
+            }
+        }
+
+        // Assigns each vertex set the lowest rank it appears with.
+        let mut set_ranks: HashMap<Vec<usize>, usize> = HashMap::new();
+        let mut insert = |set: &BTreeSet<usize>, r: usize| {
+            if !set.is_empty() {
+                let entry = set_ranks.entry(set.iter().copied().collect()).or_insert(r);
+                *entry = (*entry).min(r);
+            }
+        };
+
+        for (r, elements) in alternated.iter().enumerate().skip(1) {
+            for alt in elements {
+                insert(&alt.vertices, r);
+
+                for neighbors in alt.neighbors.values() {
+                    insert(neighbors, r - 1);
+                }
+            }
+        }
+
+        let mut sets: Vec<Vec<Vec<usize>>> = vec![Vec::new(); rank + 1];
+        for (set, r) in set_ranks {
+            sets[r].push(set);
+        }
+        for list in &mut sets {
+            list.sort_unstable();
+        }
+        while matches!(sets.last(), Some(list) if list.is_empty()) {
+            sets.pop();
+        }
+        let new_rank = sets.len() - 1;
+
+        // Builds the polytope, using vertex set inclusion as incidence.
+        let mut builder = AbstractBuilder::with_rank_capacity(new_rank);
+        builder.push_min();
+        builder.push_vertices(kept.len());
+
+        for r in 2..=new_rank {
+            let mut by_vertex = vec![Vec::new(); kept.len()];
+            for (idx, set) in sets[r - 1].iter().enumerate() {
+                for &v in set {
+                    by_vertex[v].push(idx);
+                }
+            }
+
+            let mut subelements = SubelementList::new();
+            for set in &sets[r] {
+                let mut subs: Vec<_> = set
+                    .iter()
+                    .flat_map(|&v| by_vertex[v].iter().copied())
+                    .filter(|&idx| {
+                        sets[r - 1][idx]
+                            .iter()
+                            .all(|v| set.binary_search(v).is_ok())
+                    })
+                    .collect();
+                subs.sort_unstable();
+                subs.dedup();
+
+                subelements.push(Subelements::from(subs));
+            }
+
+            builder.push(subelements);
+        }
+
+        builder.ranks().is_valid().ok()?;
+        let vertices = kept.iter().map(|&v| self.vertices[v].clone()).collect();
+
+        // Safety: we've just checked that the polytope is valid.
+        Some(Self::new(vertices, unsafe { builder.build() }))
+    }
+
+    /// Splits the vertices of the polytope into two classes, so that no edge
+    /// joins two vertices in the same class. Returns the class containing the
+    /// first vertex of each connected component, or `None` if no such
+    /// splitting exists.
+    pub fn alternation_vertices(&self) -> Option<Vec<usize>> {
+        let vertex_count = self.vertices.len();
+        if self.rank() < 2 {
+            return Some((0..vertex_count).collect());
+        }
+
+        let mut neighbors = vec![Vec::new(); vertex_count];
+        for edge in &self[2] {
+            if let [a, b] = edge.subs.as_slice() {
+                neighbors[*a].push(*b);
+                neighbors[*b].push(*a);
+            }
+        }
+
+        // Colors every connected component of the vertex graph.
+        let mut colors = vec![None; vertex_count];
+        for start in 0..vertex_count {
+            if colors[start].is_some() {
+                continue;
+            }
+
+            colors[start] = Some(true);
+            let mut stack = vec![start];
+            while let Some(v) = stack.pop() {
+                let color = colors[v];
+
+                for &w in &neighbors[v] {
+                    match colors[w] {
+                        None => {
+                            colors[w] = color.map(|c| !c);
+                            stack.push(w);
+                        }
+                        c if c == color => return None,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Some(
+            (0..vertex_count)
+                .filter(|&v| colors[v] == Some(true))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    /// Alternates a polytope, keeping the vertices in one of the classes of a
+    /// 2-coloring of its vertices.
+    fn alternate_bipartite(poly: &Concrete) -> Concrete {
+        poly.alternate(&poly.alternation_vertices().unwrap())
+            .unwrap()
+    }
+
+    /// The alternated polygons are polygons of half as many sides.
+    #[test]
+    fn polygon() {
+        test(&alternate_bipartite(&Concrete::polygon(4)), [1, 2, 1]);
+        test(&alternate_bipartite(&Concrete::polygon(6)), [1, 3, 3, 1]);
+        test(&alternate_bipartite(&Concrete::polygon(10)), [1, 5, 5, 1]);
+    }
+
+    /// The alternated hypercubes are the demicubes.
+    #[test]
+    fn demicube() {
+        test(&alternate_bipartite(&Concrete::cube()), [1, 4, 6, 4, 1]);
+        test(
+            &alternate_bipartite(&Concrete::hypercube(5)),
+            [1, 8, 24, 32, 16, 1],
+        );
+        test(
+            &alternate_bipartite(&Concrete::hypercube(6)),
+            [1, 16, 80, 160, 120, 26, 1],
+        );
+    }
+
+    /// The alternated hexagonal prism is a triangular antiprism.
+    #[test]
+    fn antiprism() {
+        let prism = Concrete::polygon(6).prism();
+        test(&alternate_bipartite(&prism), [1, 6, 12, 8, 1]);
+    }
+
+    /// Polytopes with odd cycles can't be alternated.
+    #[test]
+    fn odd() {
+        assert!(Concrete::polygon(5).alternation_vertices().is_none());
+        assert!(Concrete::octahedron().alternation_vertices().is_none());
+    }
+
+    /// Vertex sets that don't give a polytope are rejected.
+    #[test]
+    fn invalid() {
+        assert!(Concrete::polygon(4).alternate(&[0, 4]).is_none());
+        assert!(Concrete::cube().alternate(&[0, 7]).is_none());
+    }
+}
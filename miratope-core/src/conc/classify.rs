@@ -0,0 +1,235 @@
+//! Contains the code to classify polytopes by their symmetry, i.e. to determine
+//! whether they're [isogonal](https://polytope.miraheze.org/wiki/Isogonal),
+//! [noble](https://polytope.miraheze.org/wiki/Noble_polytope),
+//! [uniform](https://polytope.miraheze.org/wiki/Uniform_polytope),
+//! [regular](https://polytope.miraheze.org/wiki/Regular_polytope), etc.
+
+use std::fmt::Display;
+
+use super::{Concrete, ConcretePolytope};
+use crate::{abs::Ranked, Polytope};
+
+/// The symmetry properties of a polytope, from which we can tell whether it's
+/// isogonal, uniform, regular, etc.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Classification {
+    /// The order of the symmetry group of the polytope.
+    pub symmetry_order: usize,
+
+    /// The number of flags of the polytope.
+    pub flag_count: usize,
+
+    /// Whether the symmetry group acts transitively on the elements of each
+    /// rank, indexed by rank.
+    pub transitive: Vec<bool>,
+
+    /// Whether all edges of the polytope have the same length.
+    pub equilateral: bool,
+
+    /// Whether all facets of the polytope are uniform.
+    pub uniform_facets: bool,
+}
+
+impl Classification {
+    /// Returns the rank of the classified polytope.
+    pub fn rank(&self) -> usize {
+        self.transitive.len() - 1
+    }
+
+    /// Returns whether the symmetry group acts transitively on the elements
+    /// of a given rank.
+    pub fn is_transitive(&self, rank: usize) -> bool {
+        self.transitive.get(rank).copied().unwrap_or_default()
+    }
+
+    /// Returns whether the polytope is flag-transitive. Since the symmetry
+    /// group acts freely on the flags, this happens precisely when there's as
+    /// many symmetries as flags.
+    pub fn is_flag_transitive(&self) -> bool {
+        self.symmetry_order == self.flag_count
+    }
+
+    /// Returns whether the polytope is isogonal, i.e. vertex-transitive.
+    pub fn is_isogonal(&self) -> bool {
+        self.is_transitive(1)
+    }
+
+    /// Returns whether the polytope is isotoxal, i.e. edge-transitive.
+    pub fn is_isotoxal(&self) -> bool {
+        self.is_transitive(2)
+    }
+
+    /// Returns whether the polytope is isotopic, i.e. facet-transitive.
+    pub fn is_isotopic(&self) -> bool {
+        self.rank() == 0 || self.is_transitive(self.rank() - 1)
+    }
+
+    /// Returns whether the polytope is noble, i.e. isogonal and isotopic.
+    pub fn is_noble(&self) -> bool {
+        self.is_isogonal() && self.is_isotopic()
+    }
+
+    /// Returns whether the polytope is uniform, i.e. equilateral, isogonal,
+    /// and with uniform facets.
+    pub fn is_uniform(&self) -> bool {
+        self.is_isogonal() && self.equilateral && self.uniform_facets
+    }
+
+    /// Returns whether the polytope is regular, i.e. flag-transitive.
+    pub fn is_regular(&self) -> bool {
+        self.is_flag_transitive()
+    }
+}
+
+impl Display for Classification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let properties: Vec<_> = [
+            (self.is_regular(), "regular"),
+            (self.is_uniform(), "uniform"),
+            (self.is_noble(), "noble"),
+            (self.is_isogonal(), "isogonal"),
+            (self.is_isotoxal(), "isotoxal"),
+            (self.is_isotopic(), "isotopic"),
+        ]
+        .iter()
+        .filter(|(holds, _)| *holds)
+        .map(|(_, name)| *name)
+        .collect();
+
+        if properties.is_empty() {
+            write!(f, "not isogonal, isotoxal or isotopic")
+        } else {
+            write!(f, "{}", properties.join(", "))
+        }
+    }
+}
+
+impl Concrete {
+    /// Classifies a polytope by its symmetry. The facets of the polytope are
+    /// classified recursively, in order to determine whether they're uniform.
+    ///
+    /// The polytope is first recentered at its gravicenter, which is fixed by
+    /// all of its symmetries. Returns `None` if the symmetry group of the
+    /// polytope or that of any of its facets can't be computed.
+    pub fn classify(&self) -> Option<Classification> {
+        let rank = self.rank();
+
+        // The nullitope and the point only have a single flag.
+        if rank <= 1 {
+            return Some(Classification {
+                symmetry_order: 1,
+                flag_count: 1,
+                transitive: vec![true; rank + 1],
+                equilateral: true,
+                uniform_facets: true,
+            });
+        }
+
+        let mut poly = self.clone();
+        poly.flatten();
+        poly.recenter();
+        poly.element_sort();
+
+        let (group, vertex_map) = poly.get_symmetry_group()?;
+        let (orbits, _) = poly.element_orbits_with(&vertex_map)?;
+
+        let mut transitive: Vec<_> = orbits.iter().map(|orbits| orbits.len() == 1).collect();
+        transitive[0] = true;
+        transitive[rank] = true;
+
+        // Every facet is equivalent to one of the representatives.
+        let mut uniform_facets = true;
+        for orbit in &orbits[rank - 1] {
+            if !poly.element(rank - 1, orbit.example)?.classify()?.is_uniform() {
+                uniform_facets = false;
+                break;
+            }
+        }
+
+        Some(Classification {
+            symmetry_order: group.count(),
+            flag_count: poly.flags().count(),
+            transitive,
+            equilateral: poly.is_equilateral(),
+            uniform_facets,
+        })
+    }
+
+    /// Returns whether a polytope is uniform. If its symmetry group can't be
+    /// computed, returns `false`.
+    pub fn is_uniform(&self) -> bool {
+        matches!(self.classify(), Some(classification) if classification.is_uniform())
+    }
+
+    /// Returns whether a polytope is regular. If its symmetry group can't be
+    /// computed, returns `false`.
+    pub fn is_regular(&self) -> bool {
+        matches!(self.classify(), Some(classification) if classification.is_regular())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cox::cd::Cd;
+
+    /// Classifies the polytope with a given Coxeter diagram.
+    fn classify_cd(cd: &str) -> Classification {
+        Concrete::from_cd(&Cd::parse(cd).unwrap())
+            .unwrap()
+            .classify()
+            .unwrap()
+    }
+
+    /// Regular polytopes are regular, uniform and noble.
+    #[test]
+    fn regular() {
+        for poly in [
+            Concrete::polygon(5),
+            Concrete::cube(),
+            Concrete::octahedron(),
+            Concrete::hypercube(5),
+            Concrete::simplex(5),
+        ] {
+            let classification = poly.classify().unwrap();
+            assert!(classification.is_regular());
+            assert!(classification.is_uniform());
+            assert!(classification.is_noble());
+        }
+    }
+
+    /// Uniform polytopes are isogonal but not necessarily isotoxal.
+    #[test]
+    fn uniform() {
+        let co = classify_cd("o4x3o");
+        assert!(co.is_uniform() && co.is_isotoxal());
+        assert!(!co.is_regular() && !co.is_isotopic());
+
+        let tic = classify_cd("x4x3o");
+        assert!(tic.is_uniform() && !tic.is_isotoxal());
+
+        let prism = classify_cd("x x3o3o");
+        assert!(prism.is_uniform() && !prism.is_noble());
+    }
+
+    /// Isogonal polytopes that aren't uniform.
+    #[test]
+    fn isogonal() {
+        // A rectangle.
+        let mut rectangle = Concrete::hypercube(3);
+        rectangle.vertices.iter_mut().for_each(|v| v[0] *= 2.0);
+        let classification = rectangle.classify().unwrap();
+        assert!(classification.is_isogonal() && !classification.is_uniform());
+        assert!(!classification.is_isotoxal());
+
+        // A rhombus.
+        let mut rhombus = Concrete::polygon(4);
+        rhombus.vertices.iter_mut().for_each(|v| v[0] *= 2.0);
+        let classification = rhombus.classify().unwrap();
+        assert!(classification.is_isotoxal() && !classification.is_isogonal());
+
+        // A cuboid.
+        let cuboid = rectangle.prism().classify().unwrap();
+        assert!(cuboid.is_isogonal() && !cuboid.uniform_facets);
+    }
+}
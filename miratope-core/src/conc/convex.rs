@@ -1,192 +1,346 @@
-use std::collections::BTreeSet;
-use std::f64::NEG_INFINITY;
+//! Contains the code to build the convex hull of an arbitrary set of points.
+//!
+//! The hull is built in two stages. First, we find the facet hyperplanes of a
+//! full-rank point set by the beneath-beyond method, which maintains a
+//! triangulated hull and adds one point at a time. Then, we recursively take
+//! the hulls of the points on each facet inside of their own affine hull,
+//! which gives us the full face lattice. Every face is identified by the set of
+//! input points it contains, which does not depend on the facet it was found
+//! from.
+
+use std::collections::{BTreeSet, HashMap};
 
 use super::Concrete;
-use petgraph::{graph::NodeIndex, Directed, Direction, Graph};
-
-/// An entry in the priority queue used in Shell.
-enum QueueEntry<'a> {
-    /// Represents the event where at a certain time, the first facet that
-    /// contains a certain point becomes visible. This facet will have the
-    /// specified vertices and normal vector.
-    Point {
-        time: f64,
-        normal: Vector,
-        point: Point,
-        vertices: Vec<Point>,
-    },
-
-    /// Represents the event where at a certain time, a facet containing a
-    /// horizon peak and the horizon ridges specified by an element's neighbors
-    /// becomes visible. This facet will have the specified normal vector.
-    Peak {
-        time: f64,
-        normal: Vector,
-        element: ShellElement<'a>,
-    },
+use crate::{
+    abs::{AbstractBuilder, SubelementList, Subelements},
+    float::Float,
+    geometry::{Point, PointOrd, Subspace, Vector},
+    Polytope,
+};
+
+use vec_like::VecLike;
+
+/// A facet of the triangulated hull built throughout the beneath-beyond
+/// algorithm. Its vertices are indices into the point set that we're hulling.
+struct SimplexFacet {
+    /// The indices of the vertices of the facet, sorted by index.
+    vertices: Vec<usize>,
+
+    /// The outer unit normal of the facet's hyperplane.
+    normal: Vector<f64>,
+
+    /// The distance from the origin to the facet's hyperplane, measured
+    /// along the normal.
+    offset: f64,
 }
 
-impl<'a> QueueEntry<'a> {
-    /// Returns the time associated with an event.
-    pub fn time(&self) -> f64 {
-        match self {
-            QueueEntry::Point { time: t, .. } => *t,
-            QueueEntry::Peak { time: t, .. } => *t,
+impl SimplexFacet {
+    /// Builds a facet from a set of vertices, orienting it so that a given
+    /// point is left on its inner side. Returns `None` if the vertices don't
+    /// span a hyperplane.
+    fn new(mut vertices: Vec<usize>, points: &[Point<f64>], inner: &Point<f64>) -> Option<Self> {
+        let subspace = Subspace::from_points(vertices.iter().map(|&v| &points[v]));
+        if !subspace.is_hyperplane() {
+            return None;
         }
-    }
-}
 
-impl<'a> PartialEq for QueueEntry<'a> {
-    fn eq(&self, other: &Self) -> bool {
-        self.time() == other.time()
+        let normal = -subspace.normal(inner)?;
+        let offset = normal.dot(&points[vertices[0]]);
+        vertices.sort_unstable();
+
+        Some(Self {
+            vertices,
+            normal,
+            offset,
+        })
     }
-}
 
-impl<'a> Eq for QueueEntry<'a> {}
+    /// Returns the signed distance from a point to the facet's hyperplane.
+    /// Points outside of the hull have positive distance.
+    fn distance(&self, p: &Point<f64>) -> f64 {
+        self.normal.dot(p) - self.offset
+    }
 
-impl<'a> PartialOrd for QueueEntry<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.time().partial_cmp(&other.time())
+    /// Returns an iterator over the ridges of the facet, i.e. the sets of
+    /// vertices obtained by removing a single one.
+    fn ridges(&self) -> impl Iterator<Item = Vec<usize>> + '_ {
+        (0..self.vertices.len()).map(move |i| {
+            let mut ridge = self.vertices.clone();
+            ridge.remove(i);
+            ridge
+        })
     }
 }
 
-impl<'a> Ord for QueueEntry<'a> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+/// Returns the outer unit normals and offsets of the facet hyperplanes of the
+/// convex hull of a set of points. The points must have full rank in their
+/// ambient space, which must be at least 1-dimensional.
+fn facet_hyperplanes(points: &[Point<f64>]) -> Vec<(Vector<f64>, f64)> {
+    let dim = points[0].len();
+
+    // Finds d + 1 affinely independent points to form an initial simplex.
+    let mut simplex = vec![0];
+    let mut subspace = Subspace::new(points[0].clone());
+    for (idx, p) in points.iter().enumerate().skip(1) {
+        if subspace.add(p).is_some() {
+            simplex.push(idx);
+
+            if subspace.is_full_rank() {
+                break;
+            }
+        }
     }
-}
+    debug_assert_eq!(simplex.len(), dim + 1, "point set is not of full rank");
 
-/// The metadata returned after deleting an element from a [`ShellQueue`]. This
-/// data specifies the minimum time in the queue, the union of all
-/// [`Point`](ShellQueueEntry::Point) and [`Peak`](ShellQueueEntry::Peak)
-/// entries at this time, and their common normal vector.
-struct QueueData<'a> {
-    time: f64,
-    normal: Vector,
-    vertices: Vec<Point>,
-    elements: Vec<ShellElement<'a>>,
-    points: Vec<Point>,
-}
+    // A point in the interior of the simplex, and hence of the hull.
+    let inner = simplex.iter().map(|&v| &points[v]).sum::<Point<f64>>() / f64::usize(dim + 1);
+
+    let mut facets: Vec<SimplexFacet> = (0..=dim)
+        .filter_map(|i| {
+            let mut vertices = simplex.clone();
+            vertices.remove(i);
+            SimplexFacet::new(vertices, points, &inner)
+        })
+        .collect();
+
+    for (idx, p) in points.iter().enumerate() {
+        // Finds the facets that can be seen from the point. If none can, the
+        // point is already contained in the hull.
+        let (visible, hidden): (Vec<_>, Vec<_>) = facets
+            .into_iter()
+            .partition(|facet| facet.distance(p) > f64::EPS);
+        facets = hidden;
+
+        if visible.is_empty() {
+            continue;
+        }
 
-#[derive(Deref, DerefMut)]
-struct Queue<'a>(BTreeSet<QueueEntry<'a>>);
+        // The horizon consists of the ridges shared by a visible facet and a
+        // hidden one. These are precisely the ridges that appear only once
+        // among the visible facets.
+        let mut ridge_counts = HashMap::new();
+        for facet in &visible {
+            for ridge in facet.ridges() {
+                *ridge_counts.entry(ridge).or_insert(0) += 1;
+            }
+        }
 
-impl<'a> Queue<'a> {
-    pub fn new() -> Self {
-        Self(BTreeSet::new())
+        // Cones the horizon from the new point.
+        for (mut ridge, count) in ridge_counts {
+            if count == 1 {
+                ridge.push(idx);
+                facets.extend(SimplexFacet::new(ridge, points, &inner));
+            }
+        }
     }
 
-    pub fn delete_min() -> QueueData<'a> {
-        todo!()
+    // Coplanar simplices are merged into a single facet.
+    let mut hyperplanes: Vec<(Vector<f64>, f64)> = Vec::new();
+    for facet in facets {
+        if !hyperplanes.iter().any(|(normal, offset)| {
+            (normal - &facet.normal).norm() < f64::EPS && (offset - facet.offset).fabs() < f64::EPS
+        }) {
+            hyperplanes.push((facet.normal, facet.offset));
+        }
     }
-}
 
-/// An element produced by the Shell algorithm. Note that the data it contains
-/// is quite different from that of a [`Element`](super::Element).
-struct ShellElement<'a> {
-    normal: Vector,
-    neighbors: Vec<NodeIndex>,
-    queue: &'a Queue<'a>,
+    hyperplanes
 }
 
-struct ShellEdge<'a>(&'a Point);
+/// Returns the facets of the convex hull of a set of points as subsets of
+/// these points. Every facet contains all of the points that lie on its
+/// hyperplane, not only its vertices.
+///
+/// The set must contain at least two distinct points.
+fn hull_facets(points: &[Point<f64>], face: &[usize]) -> Vec<Vec<usize>> {
+    let face_points: Vec<_> = face.iter().map(|&i| points[i].clone()).collect();
+    let subspace = Subspace::from_points(face_points.iter());
+    let flat_points = subspace.flatten_vec(&face_points);
+
+    // The facets of a dyad are its two endpoints. We special-case this so that
+    // facets are guaranteed to be single points.
+    if subspace.rank() == 1 {
+        let (mut min, mut max) = (0, 0);
+        for (idx, p) in flat_points.iter().enumerate() {
+            if p[0] < flat_points[min][0] {
+                min = idx;
+            }
+            if p[0] > flat_points[max][0] {
+                max = idx;
+            }
+        }
 
-struct Line(Vector, Vector);
+        return vec![vec![face[min]], vec![face[max]]];
+    }
 
-struct ShellPolytope<'a> {
-    dim: usize,
-    graph: Graph<ShellElement<'a>, ShellEdge<'a>, Directed>,
+    facet_hyperplanes(&flat_points)
+        .into_iter()
+        .map(|(normal, offset)| {
+            face.iter()
+                .zip(flat_points.iter())
+                .filter(|(_, p)| (normal.dot(p) - offset).fabs() < f64::EPS)
+                .map(|(&idx, _)| idx)
+                .collect()
+        })
+        .collect()
 }
 
-impl<'a> ShellPolytope<'a> {
-    fn new(dim: usize) -> Self {
-        todo!()
-    }
+impl Concrete {
+    /// Builds the [convex hull](https://polytope.miraheze.org/wiki/Convex_hull)
+    /// of a set of points, with its full face lattice. Repeated points and
+    /// points in the interior of the hull are ignored.
+    ///
+    /// If the points are contained in a lower-dimensional subspace, the hull
+    /// is built within it, so that the resulting polytope has the same rank as
+    /// the subspace. The vertices are nevertheless returned in their original
+    /// coordinates.
+    pub fn convex_hull(vertices: Vec<Point<f64>>) -> Self {
+        // Removes duplicate points.
+        let points: Vec<_> = vertices
+            .into_iter()
+            .map(PointOrd::new)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|p| p.0)
+            .collect();
 
-    fn convex_hull(vertices: Vec<Point>) -> Concrete {
-        let s = Subspace::from_points(&vertices);
+        if points.is_empty() {
+            return Self::nullitope();
+        }
 
-        // A vector that is contained in s, but is in "general position."
-        let y = 0.57 * &s.basis[0] + 0.43 * &s.basis[1];
-        let a = s.orthogonal_comp();
-        let x: Point = vertices.iter().sum::<Point>() / vertices.len() as f64;
+        let rank = Subspace::from_points(points.iter()).rank() + 1;
 
-        let vertices = vertices.iter().collect::<Vec<_>>();
+        // The faces of each rank, as sorted lists of the points they contain,
+        // together with maps from these lists to their indices. We start from
+        // the polytope itself and go down.
+        let mut faces: Vec<Vec<Vec<usize>>> = vec![Vec::new(); rank + 1];
+        let mut face_idx: Vec<HashMap<Vec<usize>, usize>> = vec![HashMap::new(); rank + 1];
+        let mut subelements: Vec<SubelementList> = vec![SubelementList::new(); rank + 1];
+        faces[rank].push((0..points.len()).collect());
 
-        let mut poly = Self::new(vertices[0].nrows());
+        for r in (2..=rank).rev() {
+            let (lower, upper) = faces.split_at_mut(r);
+            let lower_faces = &mut lower[r - 1];
+            let lower_idx = &mut face_idx[r - 1];
 
-        poly.shell(
-            Line(y, x),
-            NEG_INFINITY,
-            Vec::new(),
-            Vec::new(),
-            vertices.clone(),
-            vertices,
-            a,
-        );
+            for face in &upper[0] {
+                let mut subs = Subelements::new();
 
-        poly.into()
-    }
-
-    fn shell(
-        &mut self,
-        line: Line,
-        time: f64,
-        ff: Vec<NodeIndex>,
-        hr: Vec<NodeIndex>,
-        u: Vec<&'a Point>,
-        t: Vec<&'a Point>,
-        n: Vec<Vector>,
-    ) -> ShellElement<'a> {
-        // Step 1
-        let q = self.graph.add_node(ShellElement {
-            normal: vec![].into(),
-            neighbors: Vec::new(),
-            queue: &Queue::new(),
-        });
-
-        for f in ff {
-            self.graph.add_edge(f, q, weight);
-        }
+                for facet in hull_facets(&points, face) {
+                    let len = lower_faces.len();
+                    let idx = *lower_idx.entry(facet.clone()).or_insert_with(|| {
+                        lower_faces.push(facet);
+                        len
+                    });
 
-        // Step 2
-        if t.len() == 1 {
-            let p = t[0];
-
-            self.graph.add_edge(
-                q,
-                if let Some(&e) = ff.get(0) {
-                    e
-                } else {
-                    self.graph.add_node(ShellElement {
-                        normal: Vector::zeros(self.dim),
-                        neighbors: Vec::new(),
-                    })
-                },
-                ShellEdge(&p),
-            );
-        }
+                    subs.push(idx);
+                }
 
-        // Step 3
-        let mut hp = Vec::new();
-        for f in hr {
-            for g in self.graph.neighbors_directed(f, Direction::Outgoing) {
-                hp.push(g);
-                self.graph[g].neighbors.push(f);
+                subelements[r].push(subs);
             }
         }
-        todo!()
+
+        // Every vertex contains exactly one point.
+        let vertices = faces[1].iter().map(|face| points[face[0]].clone()).collect();
+
+        let mut builder = AbstractBuilder::with_rank_capacity(rank);
+        builder.push_min();
+        builder.push_vertices(faces[1].len());
+        for subs in subelements.into_iter().take(rank).skip(2) {
+            builder.push(subs);
+        }
+        if rank >= 2 {
+            builder.push_max();
+        }
+
+        // Safety: the face lattice of a convex polytope is a valid abstract
+        // polytope, and we've built precisely this.
+        Self::new(vertices, unsafe { builder.build() })
     }
-}
 
-impl<'a> Into<Concrete> for ShellPolytope<'a> {
-    fn into(self) -> Concrete {
-        todo!()
+    /// Returns the convex hull of the vertices of a polytope.
+    pub fn hull(&self) -> Self {
+        Self::convex_hull(self.vertices.clone())
     }
 }
 
-impl Concrete {
-    pub fn convex_hull_plus(&self) -> Concrete {
-        convex_hull(self.vertices.clone())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::ConcretePolytope, test};
+
+    /// Checks that the hull of a polytope's vertices has the expected element
+    /// counts.
+    fn test_hull<I: IntoIterator<Item = usize>>(vertices: Vec<Point<f64>>, element_counts: I) {
+        test(&Concrete::convex_hull(vertices), element_counts)
+    }
+
+    /// Hulls of trivial point sets.
+    #[test]
+    fn trivial() {
+        test_hull(Vec::new(), [1]);
+        test_hull(vec![Point::zeros(3)], [1, 1]);
+        test_hull(vec![Point::zeros(3), Point::zeros(3)], [1, 1]);
+    }
+
+    /// Hulls of regular polygons.
+    #[test]
+    fn polygon() {
+        for n in 3..=10 {
+            test_hull(Concrete::polygon(n).vertices, [1, n, n, 1]);
+        }
+    }
+
+    /// Hulls of the vertices of hypercubes, simplices and orthoplices.
+    #[test]
+    fn regular() {
+        test_hull(Concrete::cube().vertices, [1, 8, 12, 6, 1]);
+        test_hull(Concrete::octahedron().vertices, [1, 6, 12, 8, 1]);
+        test_hull(Concrete::hypercube(5).vertices, [1, 16, 32, 24, 8, 1]);
+        test_hull(Concrete::simplex(6).vertices, [1, 6, 15, 20, 15, 6, 1]);
+        test_hull(Concrete::orthoplex(5).vertices, [1, 8, 24, 32, 16, 1]);
+    }
+
+    /// Hulls of point sets with interior and non-extreme boundary points.
+    #[test]
+    fn redundant_points() {
+        let mut vertices = Concrete::cube().vertices;
+        vertices.push(Point::zeros(3));
+        vertices.push(vec![0.5, 0.0, 0.0].into());
+        vertices.push(vec![0.5, 0.5, 0.0].into());
+        vertices.push(vec![0.1, 0.2, -0.3].into());
+        test_hull(vertices, [1, 8, 12, 6, 1]);
+    }
+
+    /// Hulls of point sets in lower-dimensional subspaces.
+    #[test]
+    fn degenerate() {
+        test_hull(
+            Concrete::polygon(5)
+                .vertices
+                .iter()
+                .map(|v| vec![v[0], v[1], 1.0].into())
+                .collect(),
+            [1, 5, 5, 1],
+        );
+        test_hull(
+            (0..5).map(|i| vec![i as f64, 2.0 * i as f64].into()).collect(),
+            [1, 2, 1],
+        );
+        test_hull(
+            Concrete::cube()
+                .vertices
+                .iter()
+                .map(|v| vec![v[0], v[1], v[2], 0.0, v[0] - v[1]].into())
+                .collect(),
+            [1, 8, 12, 6, 1],
+        );
+    }
+
+    /// The hull of a convex polytope's vertices preserves its volume.
+    #[test]
+    fn volume() {
+        let mut hull = Concrete::hypercube(4).prism().hull();
+        assert!((hull.volume_mut().unwrap() - 1.0).abs() < f64::EPS);
     }
 }
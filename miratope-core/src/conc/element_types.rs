@@ -1,6 +1,9 @@
 //! The code used to tally up the "element types" in a polytope.
 
-use std::{collections::BTreeMap, cmp::Ordering};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+};
 
 use crate::{
     abs::{ElementMap, Ranked},
@@ -10,6 +13,7 @@ use crate::{
 };
 
 use ordered_float::OrderedFloat;
+use partitions::{partition_vec, PartitionVec};
 use vec_like::*;
 
 /// Every element in a polytope can be assigned a "type" depending on its
@@ -24,6 +28,37 @@ pub struct ElementType {
     pub count: usize,
 }
 
+/// An orbit of elements in a polytope under some symmetry group. Unlike an
+/// [`ElementType`], this is determined exactly.
+#[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct ElementOrbit {
+    /// The index of the representative for this orbit.
+    pub example: usize,
+
+    /// The number of elements in this orbit.
+    pub count: usize,
+
+    /// The order of the stabilizer of any element in this orbit, i.e. the
+    /// number of symmetries that leave it in place.
+    ///
+    /// Elements with the same subelements, like the facets of a ditope, can't
+    /// be told apart by the symmetries, which only act on the vertices. This
+    /// is really the stabilizer of the class of elements with the same
+    /// subelements as any element in the orbit, so the orbit-stabilizer
+    /// theorem only holds for the number of such classes, which may be less
+    /// than [`Self::count`].
+    pub stabilizer: usize,
+}
+
+impl From<ElementOrbit> for ElementType {
+    fn from(orbit: ElementOrbit) -> Self {
+        Self {
+            example: orbit.example,
+            count: orbit.count,
+        }
+    }
+}
+
 /// Stores the metadata associated with an element type.
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 struct TypeData {
@@ -224,6 +259,157 @@ impl Concrete {
         self.element_types_common().1
     }
 
+    /// Splits the elements of each rank into their orbits under a group of
+    /// symmetries, given as a list of vertex mappings like the ones returned by
+    /// [`Self::get_symmetry_group`].
+    ///
+    /// The vertex mappings can't tell apart elements with the same
+    /// subelements, like the facets of a ditope, so these are always put in
+    /// the same orbit. The images of all other elements are found rank by
+    /// rank, from the images of their subelements.
+    ///
+    /// As with [`Self::element_types_common`], the minimal and maximal elements
+    /// are given no orbits. Returns `None` if the vertex mappings don't map the
+    /// polytope's elements to one another.
+    pub fn element_orbits_with(
+        &self,
+        vertex_map: &[Vec<usize>],
+    ) -> Option<(Vec<Vec<ElementOrbit>>, ElementMap<usize>)> {
+        let rank = self.rank();
+
+        // A nullitope has no proper elements.
+        if rank < 1 {
+            return Some((Vec::new(), ElementMap::new()));
+        }
+
+        // Splits the elements of every proper rank into classes of elements
+        // with the same subelements, up to their classes. Every vertex is in
+        // its own class. We store the class of every element, the
+        // subelements of every class, and the class of every list of
+        // subelements.
+        let mut class_of: Vec<Vec<usize>> = vec![Vec::new(); rank];
+        let mut class_subs: Vec<Vec<Vec<usize>>> = vec![Vec::new(); rank];
+        let mut class_by_subs: Vec<HashMap<Vec<usize>, usize>> = vec![HashMap::new(); rank];
+        class_of[1] = (0..self.vertex_count()).collect();
+
+        for r in 2..rank {
+            for el in self[r].iter() {
+                let mut subs: Vec<_> = el.subs.iter().map(|&sub| class_of[r - 1][sub]).collect();
+                subs.sort_unstable();
+
+                let next_class = class_subs[r].len();
+                let class = *class_by_subs[r].entry(subs.clone()).or_insert(next_class);
+                if class == next_class {
+                    class_subs[r].push(subs);
+                }
+                class_of[r].push(class);
+            }
+        }
+
+        // An element in each class.
+        let examples: Vec<Vec<usize>> = class_of
+            .iter()
+            .map(|classes| {
+                let mut examples = vec![0; classes.len()];
+                for (idx, &class) in classes.iter().enumerate().rev() {
+                    examples[class] = idx;
+                }
+                examples
+            })
+            .collect();
+
+        // Each symmetry joins every element with the elements in the image of
+        // its class. In particular, the identity joins the elements in every
+        // class.
+        let mut partitions: Vec<PartitionVec<()>> = self
+            .el_count_iter()
+            .map(|c| partition_vec![(); c])
+            .collect();
+        for row in vertex_map {
+            if row.len() != self.vertex_count() {
+                return None;
+            }
+
+            let mut images = row.clone();
+            for r in 1..rank {
+                if r >= 2 {
+                    images = class_subs[r]
+                        .iter()
+                        .map(|subs| {
+                            let mut image: Vec<_> = subs.iter().map(|&sub| images[sub]).collect();
+                            image.sort_unstable();
+                            class_by_subs[r].get(&image).copied()
+                        })
+                        .collect::<Option<_>>()?;
+                }
+
+                for (idx, &class) in class_of[r].iter().enumerate() {
+                    partitions[r].union(idx, examples[r][images[class]]);
+                }
+            }
+        }
+
+        // Reads off the orbits from the partitions, in the order in which we
+        // first find their elements.
+        let order = vertex_map.len();
+        let mut orbits = Vec::with_capacity(rank + 1);
+        let mut orbit_of_element = ElementMap::with_capacity(rank + 1);
+
+        for (r, partition) in partitions.iter().enumerate() {
+            let mut orbits_rank: Vec<ElementOrbit> = Vec::new();
+            let mut orbit_of_element_rank = vec![0; partition.len()];
+
+            if r != 0 && r != rank {
+                let mut found = vec![false; partition.len()];
+
+                for idx in 0..partition.len() {
+                    if found[idx] {
+                        continue;
+                    }
+
+                    let orbit = orbits_rank.len();
+                    let mut count = 0;
+                    let mut classes = HashSet::new();
+                    for (el, _) in partition.set(idx) {
+                        found[el] = true;
+                        orbit_of_element_rank[el] = orbit;
+                        count += 1;
+                        classes.insert(class_of[r][el]);
+                    }
+
+                    // By the orbit-stabilizer theorem, applied to the classes
+                    // that the symmetries actually move around.
+                    orbits_rank.push(ElementOrbit {
+                        example: idx,
+                        count,
+                        stabilizer: order / classes.len(),
+                    });
+                }
+            }
+
+            orbits.push(orbits_rank);
+            orbit_of_element.push(orbit_of_element_rank);
+        }
+
+        Some((orbits, orbit_of_element))
+    }
+
+    /// Splits the elements of each rank into their orbits under the symmetry
+    /// group of the polytope. Returns `None` if the symmetry group can't be
+    /// computed.
+    ///
+    /// This is an exact alternative to [`Self::element_types_common`], which
+    /// can't tell apart non-equivalent elements with the same measurements.
+    pub fn element_orbits_common(&mut self) -> Option<(Vec<Vec<ElementOrbit>>, ElementMap<usize>)> {
+        let (_, vertex_map) = self.get_symmetry_group()?;
+        self.element_orbits_with(&vertex_map)
+    }
+
+    /// Returns a list of orbits of elements under the symmetry group.
+    pub fn element_orbits(&mut self) -> Option<Vec<Vec<ElementOrbit>>> {
+        Some(self.element_orbits_common()?.0)
+    }
+
     /// Prints all element types of a polytope into the console.
     pub fn print_element_types(&self) {
         for (r, types) in self.element_types().into_iter().enumerate().skip(1) {
@@ -248,3 +434,92 @@ impl Concrete {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Polytope;
+
+    /// Returns the number of elements, and the stabilizer order, of each orbit
+    /// of each proper rank.
+    fn orbits(poly: &mut Concrete) -> Vec<Vec<(usize, usize)>> {
+        let rank = poly.rank();
+        poly.element_orbits()
+            .unwrap()
+            .into_iter()
+            .take(rank)
+            .skip(1)
+            .map(|orbits| {
+                orbits
+                    .into_iter()
+                    .map(|o| (o.count, o.stabilizer))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The elements of a regular polytope form a single orbit each.
+    #[test]
+    fn regular() {
+        assert_eq!(
+            orbits(&mut Concrete::cube()),
+            [[(8, 6)], [(12, 4)], [(6, 8)]]
+        );
+        assert_eq!(
+            orbits(&mut Concrete::simplex(4)),
+            [[(4, 6)], [(6, 4)], [(4, 6)]]
+        );
+    }
+
+    /// The faces of a hexagonal prism form two orbits.
+    #[test]
+    fn prism() {
+        let mut prism = Concrete::polygon(6).prism();
+        let mut counts = orbits(&mut prism)[2]
+            .iter()
+            .map(|o| o.0)
+            .collect::<Vec<_>>();
+        counts.sort_unstable();
+        assert_eq!(counts, [2, 6]);
+    }
+
+    /// Orbits under the rotation group have smaller stabilizers.
+    #[test]
+    fn rotation() {
+        let mut cube = Concrete::cube();
+        let (_, vertex_map) = cube.get_rotation_group().unwrap();
+        let (orbits, _) = cube.element_orbits_with(&vertex_map).unwrap();
+        assert_eq!(
+            orbits[1],
+            [ElementOrbit {
+                example: 0,
+                count: 8,
+                stabilizer: 3
+            }]
+        );
+        assert_eq!(orbits[3][0].stabilizer, 4);
+    }
+
+    /// Elements with the same subelements, like the facets of a ditope or the
+    /// edges of a hosotope, are put in the same orbit.
+    #[test]
+    fn duplicates() {
+        let orbit = |count, stabilizer| ElementOrbit {
+            example: 0,
+            count,
+            stabilizer,
+        };
+
+        let ditope = Concrete::cube().ditope();
+        let (_, vertex_map) = Concrete::cube().get_symmetry_group().unwrap();
+        let (orbits, _) = ditope.element_orbits_with(&vertex_map).unwrap();
+        // The two facets form a single class, fixed by every symmetry.
+        assert_eq!(orbits[4], [orbit(2, 48)]);
+
+        let hosotope = Concrete::polygon(5).hosotope();
+        let swap = [vec![0, 1], vec![1, 0]];
+        let (orbits, _) = hosotope.element_orbits_with(&swap).unwrap();
+        assert_eq!(orbits[2], [orbit(5, 2)]);
+        assert_eq!(orbits[3], [orbit(5, 2)]);
+    }
+}
@@ -1,6 +1,8 @@
 //! The faceting algorithm.
 
-use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque}, vec, iter::FromIterator, io::Write, time::Instant, path::PathBuf};
+use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque}, fmt::Display, fs, vec, iter::FromIterator, io::Write, time::Instant, path::{Path, PathBuf}};
+use std::sync::{atomic::{AtomicBool, Ordering}, mpsc::{self, Receiver, SyncSender}, Arc};
+use std::thread;
 
 use crate::{
     abs::{Abstract, Element, ElementList, Ranked, Ranks, Subelements, Superelements, AbstractBuilder},
@@ -10,6 +12,7 @@ use crate::{
 };
 
 use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 
 use vec_like::*;
 
@@ -24,10 +27,515 @@ pub enum GroupEnum {
     Chiral(bool),
 }
 
+/// The stages the faceting algorithm goes through, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FacetingStage {
+    /// Computing the symmetry group of the polytope.
+    Symmetry,
+    /// Sorting the vertices into orbits.
+    Vertices,
+    /// Computing the possible edge lengths.
+    EdgeLengths,
+    /// Enumerating the hyperplanes through the vertices.
+    Hyperplanes,
+    /// Faceting each orbit of hyperplanes.
+    FacetingHyperplanes,
+    /// Sorting the ridges of the possible facets into orbits.
+    Ridges,
+    /// Combining the possible facets into facetings.
+    Combining,
+    /// Filtering out mixed compounds.
+    Filtering,
+    /// Building the facetings.
+    Building,
+    /// The faceting is complete.
+    Done,
+}
+
+impl Display for FacetingStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Symmetry => "Computing symmetry group",
+            Self::Vertices => "Matching vertices",
+            Self::EdgeLengths => "Computing edge lengths",
+            Self::Hyperplanes => "Enumerating hyperplanes",
+            Self::FacetingHyperplanes => "Faceting hyperplanes",
+            Self::Ridges => "Computing ridges",
+            Self::Combining => "Combining",
+            Self::Filtering => "Filtering mixed compounds",
+            Self::Building => "Building",
+            Self::Done => "Faceting complete",
+        })
+    }
+}
+
+/// Receives the progress reports and results of
+/// [`Concrete::faceting_with_progress`], and tells it whether to stop.
+pub trait FacetingProgress {
+    /// Called whenever the algorithm enters a new stage.
+    fn stage(&mut self, stage: FacetingStage);
+
+    /// Called with a message that should be logged, like the number of orbits
+    /// found in some stage.
+    fn message(&mut self, message: String);
+
+    /// Called periodically during long computations with a description of
+    /// their current state. Each status supersedes the previous one.
+    fn status(&mut self, status: String);
+
+    /// Called with each faceting or facet as soon as it's built, unless it's
+    /// saved to a file instead.
+    fn output(&mut self, poly: Concrete, label: Option<String>);
+
+    /// Returns whether the algorithm should stop as soon as possible. Any
+    /// results already output are kept.
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Prints the progress of the faceting algorithm to standard output, and
+/// collects its results.
+#[derive(Default)]
+pub struct PrintProgress {
+    /// The facetings and facets found so far, together with their labels.
+    pub output: Vec<(Concrete, Option<String>)>,
+}
+
+impl FacetingProgress for PrintProgress {
+    fn stage(&mut self, stage: FacetingStage) {
+        if stage == FacetingStage::Done {
+            println!("\n{}\n", stage);
+        } else {
+            println!("\n{}...", stage);
+        }
+    }
+
+    fn message(&mut self, message: String) {
+        println!("{}{}", CL, message);
+    }
+
+    fn status(&mut self, status: String) {
+        print!("{}{:.115}", CL, status);
+        std::io::stdout().flush().unwrap();
+    }
+
+    fn output(&mut self, poly: Concrete, label: Option<String>) {
+        self.output.push((poly, label));
+    }
+}
+
+/// The options for the faceting algorithm. They can be built up from the
+/// defaults with the `with_*` methods, and saved and loaded as presets.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FacetingOptions {
+    /// Whether to run the faceting once for every edge length between the
+    /// vertices, instead of using the edge length range.
+    pub any_single_edge_length: bool,
+
+    /// The minimum edge length of the facetings.
+    pub min_edge_length: Option<f64>,
+
+    /// The maximum edge length of the facetings.
+    pub max_edge_length: Option<f64>,
+
+    /// The minimum inradius of the hyperplanes of the facets.
+    pub min_inradius: Option<f64>,
+
+    /// The maximum inradius of the hyperplanes of the facets.
+    pub max_inradius: Option<f64>,
+
+    /// Whether to skip hyperplanes through the center.
+    pub exclude_hemis: bool,
+
+    /// Whether to only consider hyperplanes perpendicular to a vertex.
+    pub only_below_vertex: bool,
+
+    /// The maximum number of facet orbits of the facetings.
+    pub noble: Option<usize>,
+
+    /// The maximum number of facetings to consider per hyperplane.
+    pub max_per_hyperplane: Option<usize>,
+
+    /// Whether to only use uniform or semiuniform facets.
+    pub uniform: bool,
+
+    /// Whether to include trivial compounds of other facetings.
+    pub include_compounds: bool,
+
+    /// Whether to mark compounds and fissaries in the labels.
+    pub mark_fissary: bool,
+
+    /// Whether to list the facet orbits in the labels.
+    pub label_facets: bool,
+
+    /// Whether to output the facetings.
+    pub save: bool,
+
+    /// Whether to output the facets used by the facetings.
+    pub save_facets: bool,
+
+    /// If set, the results are written as OFF files to this directory instead
+    /// of being output.
+    pub save_to_file: Option<PathBuf>,
+
+    /// The number of facetings after which to stop.
+    pub max_results: Option<usize>,
+
+    /// If set, the state of the faceting is periodically saved to this file,
+    /// and a later faceting with the same polytope and options resumes from it.
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl Default for FacetingOptions {
+    fn default() -> Self {
+        Self {
+            any_single_edge_length: false,
+            min_edge_length: None,
+            max_edge_length: None,
+            min_inradius: None,
+            max_inradius: None,
+            exclude_hemis: false,
+            only_below_vertex: false,
+            noble: None,
+            max_per_hyperplane: None,
+            uniform: false,
+            include_compounds: false,
+            mark_fissary: true,
+            label_facets: true,
+            save: true,
+            save_facets: false,
+            save_to_file: None,
+            max_results: None,
+            checkpoint: None,
+        }
+    }
+}
+
+impl FacetingOptions {
+    /// Returns the default options.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets whether to try every single edge length.
+    pub fn with_any_single_edge_length(mut self, any_single_edge_length: bool) -> Self {
+        self.any_single_edge_length = any_single_edge_length;
+        self
+    }
+
+    /// Sets the range of edge lengths.
+    pub fn with_edge_lengths(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min_edge_length = min;
+        self.max_edge_length = max;
+        self
+    }
+
+    /// Only allows edges of the given length.
+    pub fn with_edge_length(self, edge_length: f64) -> Self {
+        self.with_edge_lengths(Some(edge_length), Some(edge_length))
+    }
+
+    /// Sets the range of inradii of the hyperplanes.
+    pub fn with_inradii(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.min_inradius = min;
+        self.max_inradius = max;
+        self
+    }
+
+    /// Sets whether to skip hyperplanes through the center.
+    pub fn with_exclude_hemis(mut self, exclude_hemis: bool) -> Self {
+        self.exclude_hemis = exclude_hemis;
+        self
+    }
+
+    /// Sets whether to only consider hyperplanes perpendicular to a vertex.
+    pub fn with_only_below_vertex(mut self, only_below_vertex: bool) -> Self {
+        self.only_below_vertex = only_below_vertex;
+        self
+    }
+
+    /// Sets the maximum number of facet orbits.
+    pub fn with_noble(mut self, noble: Option<usize>) -> Self {
+        self.noble = noble;
+        self
+    }
+
+    /// Sets the maximum number of facetings per hyperplane.
+    pub fn with_max_per_hyperplane(mut self, max_per_hyperplane: Option<usize>) -> Self {
+        self.max_per_hyperplane = max_per_hyperplane;
+        self
+    }
+
+    /// Sets whether to only use uniform or semiuniform facets.
+    pub fn with_uniform(mut self, uniform: bool) -> Self {
+        self.uniform = uniform;
+        self
+    }
+
+    /// Sets whether to include trivial compounds.
+    pub fn with_compounds(mut self, include_compounds: bool) -> Self {
+        self.include_compounds = include_compounds;
+        self
+    }
+
+    /// Sets whether to mark compounds and fissaries.
+    pub fn with_mark_fissary(mut self, mark_fissary: bool) -> Self {
+        self.mark_fissary = mark_fissary;
+        self
+    }
+
+    /// Sets whether to list the facet orbits in the labels.
+    pub fn with_label_facets(mut self, label_facets: bool) -> Self {
+        self.label_facets = label_facets;
+        self
+    }
+
+    /// Sets whether to output the facetings and their facets.
+    pub fn with_save(mut self, save: bool, save_facets: bool) -> Self {
+        self.save = save;
+        self.save_facets = save_facets;
+        self
+    }
+
+    /// Sets the directory to write the results to.
+    pub fn with_save_to_file(mut self, save_to_file: Option<PathBuf>) -> Self {
+        self.save_to_file = save_to_file;
+        self
+    }
+
+    /// Sets the number of facetings after which to stop.
+    pub fn with_max_results(mut self, max_results: Option<usize>) -> Self {
+        self.max_results = max_results;
+        self
+    }
+
+    /// Sets the file to save checkpoints to and resume from.
+    pub fn with_checkpoint(mut self, checkpoint: Option<PathBuf>) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Returns the options with everything that only affects how the results
+    /// are output set to its default, so that they can be compared to check
+    /// whether two facetings would find the same facetings.
+    fn search_options(&self) -> Self {
+        let default = Self::default();
+        Self {
+            mark_fissary: default.mark_fissary,
+            label_facets: default.label_facets,
+            save: default.save,
+            save_facets: default.save_facets,
+            save_to_file: default.save_to_file,
+            max_results: default.max_results,
+            checkpoint: default.checkpoint,
+            ..self.clone()
+        }
+    }
+}
+
+/// Sends the facetings found in a background thread to a [`FacetingIter`].
+struct IterProgress {
+    /// The sender for the results.
+    sender: SyncSender<(Concrete, Option<String>)>,
+
+    /// Whether the iterator has been dropped.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl FacetingProgress for IterProgress {
+    fn stage(&mut self, _stage: FacetingStage) {}
+
+    fn message(&mut self, _message: String) {}
+
+    fn status(&mut self, _status: String) {}
+
+    fn output(&mut self, poly: Concrete, label: Option<String>) {
+        if self.sender.send((poly, label)).is_err() {
+            self.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// An iterator over the facetings of a polytope, returned by
+/// [`Concrete::faceting_iter`]. The facetings are computed in a background
+/// thread, which stops once the iterator is dropped.
+pub struct FacetingIter {
+    /// The receiver for the results.
+    receiver: Receiver<(Concrete, Option<String>)>,
+
+    /// Whether the iterator has been dropped.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Iterator for FacetingIter {
+    type Item = (Concrete, Option<String>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for FacetingIter {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The partial facetings left to explore while combining, each with the
+/// minimum hyperplane that can still be added and the cached ridge
+/// multiplicities.
+type FacetsQueue = VecDeque<(
+    Vec<(usize, usize)>, // list of facets
+    usize, // min hyperplane
+    Vec<usize> // cached ridge muls
+)>;
+
+/// The orbits of hyperplanes, each with its vertices and number of copies.
+type HyperplaneOrbits = Vec<(Subspace<f64>, Vec<usize>, usize)>;
+
+/// The possible facets of each hyperplane orbit, each with its ridges.
+type PossibleFacets = Vec<Vec<(Ranks, Vec<(usize, usize)>)>>;
+
+/// The state of a faceting, which is periodically saved to a file so that it
+/// can be resumed if it's interrupted. Each stage fills in more of the fields.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Checkpoint {
+    /// The vertices of the polytope being faceted.
+    vertices: Vec<Point<f64>>,
+
+    /// The vertex map of the symmetry group.
+    vertex_map: Vec<Vec<usize>>,
+
+    /// The options that affect which facetings are found.
+    options: FacetingOptions,
+
+    /// The index of the edge length being checked, if checking every single
+    /// edge length.
+    edge_length_idx: usize,
+
+    /// The number of facetings found with the previous edge lengths, and with
+    /// the current one once building has started.
+    result_count: usize,
+
+    /// The orbits of hyperplanes, once they've been enumerated.
+    hyperplane_orbits: Option<HyperplaneOrbits>,
+
+    /// The possible facets of the hyperplane orbits faceted so far.
+    possible_facets: PossibleFacets,
+
+    /// The possible facets with global vertex indices.
+    possible_facets_global: PossibleFacets,
+
+    /// The components of the compound facets of each hyperplane orbit.
+    compound_facets: Vec<HashMap<usize, (usize, usize)>>,
+
+    /// The possible ridges of each hyperplane orbit. These are dropped once
+    /// they've been sorted into orbits.
+    ridges: Vec<Vec<Vec<Ranks>>>,
+
+    /// The number of copies of each possible ridge of each hyperplane orbit.
+    ff_counts: Vec<Vec<usize>>,
+
+    /// The orbit of each possible ridge, once they've been computed.
+    ridge_idx_orbits: Vec<Vec<Vec<usize>>>,
+
+    /// The number of ridges in each orbit.
+    ridge_counts: Vec<usize>,
+
+    /// The partial facetings left to explore, once combining has started.
+    facets_queue: Option<FacetsQueue>,
+
+    /// The facetings found while combining.
+    output_facets: Vec<Vec<(usize, usize)>>,
+
+    /// The number of facetings built so far and the index of the next one to
+    /// be output, once building has started.
+    building: Option<(usize, usize)>,
+}
+
+impl Checkpoint {
+    /// Loads a checkpoint from a file, as long as it was saved by a faceting
+    /// of the same polytope with the same symmetry and options as `base`.
+    fn load(path: &Path, base: &Self, progress: &mut dyn FacetingProgress) -> Option<Self> {
+        let checkpoint: Self = match ron::from_str(&fs::read_to_string(path).ok()?) {
+            Ok(checkpoint) => checkpoint,
+            Err(err) => {
+                progress.message(format!("Could not read the checkpoint {}: {}", path.display(), err));
+                return None;
+            }
+        };
+
+        let same_vertices = checkpoint.vertices.len() == base.vertices.len()
+            && checkpoint.vertices.iter().zip(&base.vertices).all(|(v, w)| (v - w).norm() < f64::EPS);
+
+        if same_vertices && checkpoint.vertex_map == base.vertex_map && checkpoint.options == base.options {
+            progress.message(format!("Resuming from checkpoint {}", path.display()));
+            Some(checkpoint)
+        } else {
+            progress.message("The checkpoint is from a different faceting, starting over".to_string());
+            None
+        }
+    }
+}
+
+/// Saves the checkpoints of a faceting.
+struct Checkpointer {
+    /// The file to save the checkpoints to, if any.
+    path: Option<PathBuf>,
+
+    /// When the last checkpoint was saved.
+    last: Instant,
+}
+
+impl Checkpointer {
+    /// Returns whether checkpoints are being saved at all.
+    fn enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Returns whether enough time has passed to save another checkpoint.
+    fn due(&self) -> bool {
+        self.enabled() && self.last.elapsed().as_millis() > CHECKPOINT_DELAY
+    }
+
+    /// Saves a checkpoint. It's written to a temporary file first, so that an
+    /// interruption while saving doesn't corrupt the previous checkpoint.
+    fn save(&mut self, checkpoint: &Checkpoint, progress: &mut dyn FacetingProgress) {
+        if let Some(path) = &self.path {
+            let tmp_path = path.with_extension("tmp");
+            let result = ron::to_string(checkpoint)
+                .map_err(|err| err.to_string())
+                .and_then(|ron| fs::write(&tmp_path, ron).map_err(|err| err.to_string()))
+                .and_then(|_| fs::rename(&tmp_path, path).map_err(|err| err.to_string()));
+
+            if let Err(err) = result {
+                progress.message(format!("Could not save the checkpoint {}: {}", path.display(), err));
+            }
+            self.last = Instant::now();
+        }
+    }
+
+    /// Removes the checkpoint once the faceting is complete.
+    fn remove(&self) {
+        if let Some(path) = &self.path {
+            fs::remove_file(path).ok();
+        }
+    }
+}
+
 const CL: &str = "\r                                                                                                                   \r";
 
 const DELAY: u128 = 200;
 
+/// The time in milliseconds between checkpoints.
+const CHECKPOINT_DELAY: u128 = 60_000;
+
 impl Ranks {
     /// Sorts some stuff in a way that's useful for the faceting algorithm.
     pub fn element_sort_strong(&mut self) {
@@ -252,7 +760,8 @@ fn faceting_subdim(
     max_per_hyperplane: Option<usize>,
 	uniform: bool,
     noble_package: Option<(&Vec<Vec<usize>>, &Vec<usize>, usize)>,
-	print_faceting_count: bool
+	print_faceting_count: bool,
+    progress: &mut dyn FacetingProgress,
 ) ->
     (Vec<(Ranks, Vec<(usize, usize)>)>, // Vec of facetings, along with the facet types of each of them
     Vec<usize>, // Counts of each hyperplane orbit
@@ -587,7 +1096,7 @@ fn faceting_subdim(
         }
 
         let (possible_facets_row, ff_counts_row, ridges_row, compound_facets_row) =
-            faceting_subdim(rank-1, hp, points, new_stabilizer.clone(), min_edge_length, max_edge_length, max_per_hyperplane, uniform, None, false);
+            faceting_subdim(rank-1, hp, points, new_stabilizer.clone(), min_edge_length, max_edge_length, max_per_hyperplane, uniform, None, false, progress);
 
         let mut possible_facets_global_row = Vec::new();
         for f in &possible_facets_row {
@@ -736,20 +1245,19 @@ fn faceting_subdim(
 
 	let mut skipped = 0;
     'l: while let Some((facets, min_hp, cached_ridge_muls)) = facets_queue.pop_back() {
-        if uniform {
-            if now.elapsed().as_millis() > DELAY && print_faceting_count {
-                print!("{}", CL);
-                print!("{:.115}", format!("{} facets found, {} skipped, {:?}", output.len(), skipped, facets));
-                std::io::stdout().flush().unwrap();
-                now = Instant::now();
+        if now.elapsed().as_millis() > DELAY {
+            if progress.cancelled() {
+                break;
             }
-        } else {
-            if now.elapsed().as_millis() > DELAY && print_faceting_count {
-                print!("{}", CL);
-                print!("{:.115}", format!("{} facets found, {:?}", output.len(), facets));
-                std::io::stdout().flush().unwrap();
-                now = Instant::now();
+
+            if print_faceting_count {
+                if uniform {
+                    progress.status(format!("{} facets found, {} skipped, {:?}", output.len(), skipped, facets));
+                } else {
+                    progress.status(format!("{} facets found, {:?}", output.len(), facets));
+                }
             }
+            now = Instant::now();
         }
         
         let mut new_ridge_muls = cached_ridge_muls.clone();
@@ -1087,34 +1595,87 @@ fn faceting_subdim(
 impl Concrete {
     /// Enumerates the facetings of a polytope under a provided symmetry group or vertex map.
     /// If the symmetry group is not provided, it uses the full symmetry of the polytope.
+    ///
+    /// The progress is printed to standard output. See [`Self::faceting_with_progress`]
+    /// for a version that reports it elsewhere and can be cancelled, and
+    /// [`Self::faceting_iter`] for one that yields the facetings as they're built.
     pub fn faceting(
         &mut self,
         vertices: Vec<Point<f64>>,
         symmetry: GroupEnum,
-        any_single_edge_length: bool,
-        mut min_edge_length: Option<f64>,
-        mut max_edge_length: Option<f64>,
-        min_inradius: Option<f64>,
-        max_inradius: Option<f64>,
-        exclude_hemis: bool,
-        only_below_vertex: bool,
-        noble: Option<usize>,
-        max_per_hyperplane: Option<usize>,
-		uniform: bool,
-        include_compounds: bool,
-        mark_fissary: bool,
-        label_facets: bool,
-        save: bool,
-        save_facets: bool,
-        save_to_file: bool,
-        file_path: String
+        options: &FacetingOptions,
     ) -> Vec<(Concrete, Option<String>)> {
+        let mut progress = PrintProgress::default();
+        self.faceting_with_progress(vertices, symmetry, options, &mut progress);
+        progress.output
+    }
+
+    /// Returns an iterator over the facetings of a polytope, computed in a
+    /// background thread as they're needed. The options are the same as for
+    /// [`Self::faceting`]. Dropping the iterator stops the computation.
+    pub fn faceting_iter(
+        mut self,
+        vertices: Vec<Point<f64>>,
+        symmetry: GroupEnum,
+        options: FacetingOptions,
+    ) -> FacetingIter {
+        let (sender, receiver) = mpsc::sync_channel(0);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut progress = IterProgress {
+            sender,
+            cancelled: Arc::clone(&cancelled),
+        };
+
+        thread::spawn(move || {
+            self.faceting_with_progress(vertices, symmetry, &options, &mut progress);
+        });
+
+        FacetingIter { receiver, cancelled }
+    }
+
+    /// Enumerates the facetings of a polytope like [`Self::faceting`], reporting
+    /// the progress and the results to a [`FacetingProgress`] as they're found.
+    /// The computation stops early if the [`FacetingProgress`] is cancelled.
+    ///
+    /// If [`FacetingOptions::checkpoint`] is set, the state of the faceting is
+    /// saved after every stage, every minute while faceting the hyperplanes
+    /// and combining the facets, and when cancelled. A matching checkpoint is
+    /// resumed from, and it's removed once the faceting is complete.
+    pub fn faceting_with_progress(
+        &mut self,
+        vertices: Vec<Point<f64>>,
+        symmetry: GroupEnum,
+        options: &FacetingOptions,
+        progress: &mut dyn FacetingProgress,
+    ) {
+        let FacetingOptions {
+            any_single_edge_length,
+            mut min_edge_length,
+            mut max_edge_length,
+            min_inradius,
+            max_inradius,
+            exclude_hemis,
+            only_below_vertex,
+            noble,
+            max_per_hyperplane,
+            uniform,
+            include_compounds,
+            mark_fissary,
+            label_facets,
+            save,
+            save_facets,
+            ref save_to_file,
+            max_results,
+            checkpoint: ref checkpoint_path,
+        } = *options;
+
         let rank = self.rank();
         let mut now = Instant::now();
+        let mut result_count = 0;
 
         if rank < 4 {
-            println!("\nFaceting polytopes of rank less than 3 is not supported!\n");
-            return Vec::new()
+            progress.message("Faceting polytopes of rank less than 3 is not supported!".to_string());
+            return
         }
 
         let mut vertices_ord = Vec::<PointOrd<f64>>::new();
@@ -1124,29 +1685,40 @@ impl Concrete {
 
         let vertex_map = match symmetry {
             GroupEnum::ConcGroup(group) => {
-                println!("\nComputing vertex map...");
+                progress.stage(FacetingStage::Symmetry);
                 self.get_vertex_map(group)
             },
             GroupEnum::VertexMap(a) => a,
             GroupEnum::Chiral(chiral) => {
+                progress.stage(FacetingStage::Symmetry);
                 if chiral {
-                    println!("\nComputing rotation symmetry group...");
                     let g = self.get_rotation_group().unwrap();
-                    println!("Rotation symmetry order {}", g.0.count());
+                    progress.message(format!("Rotation symmetry order {}", g.0.count()));
                     g.1
                 }
                 else {
-                    println!("\nComputing symmetry group...");
                     let g = self.get_symmetry_group().unwrap();
-                    println!("Symmetry order {}", g.0.count());
+                    progress.message(format!("Symmetry order {}", g.0.count()));
                     g.1
                 }
             },
         };
 
-        let mut output = Vec::new();
+        let mut checkpointer = Checkpointer {
+            path: checkpoint_path.clone(),
+            last: Instant::now(),
+        };
+        let base = Checkpoint {
+            vertices: vertices.clone(),
+            vertex_map: vertex_map.clone(),
+            options: options.search_options(),
+            ..Default::default()
+        };
+        let mut resume = checkpoint_path
+            .as_ref()
+            .and_then(|path| Checkpoint::load(path, &base, progress));
 
-        println!("\nMatching vertices...");
+        progress.stage(FacetingStage::Vertices);
 
         // Checking every r-tuple of vertices would take too long, so we put pairs into orbits first to reduce the number.
         // I don't think we need to store the whole orbits at this point, but they might be useful if we want to improve the algorithm.
@@ -1173,13 +1745,13 @@ impl Concrete {
             }
         }
 
-        println!("{} vertices in {} orbit{}", vertices.len(), orbit_idx, if orbit_idx == 1 {""} else {"s"});
+        progress.message(format!("{} vertices in {} orbit{}", vertices.len(), orbit_idx, if orbit_idx == 1 {""} else {"s"}));
 
         let mut possible_lengths_set = BTreeSet::<OrderedFloat<f64>>::new();
         let mut possible_lengths = Vec::new();
 
         if any_single_edge_length {
-            println!("\nComputing edge lengths...");
+            progress.stage(FacetingStage::EdgeLengths);
 
             for orbit in &vertex_orbits {
                 let rep = orbit[0];
@@ -1201,23 +1773,34 @@ impl Concrete {
                 }
             }
 
-            println!("Found {} edge lengths: {:?}", possible_lengths.len(), possible_lengths);
+            progress.message(format!("Found {} edge lengths: {:?}", possible_lengths.len(), possible_lengths));
         }
         let mut edge_length_idx = 0;
+        if let Some(resume) = &resume {
+            edge_length_idx = resume.edge_length_idx;
+            result_count = resume.result_count;
+        }
         
         loop {
+            let mut resumed = resume.take().unwrap_or_default();
+
             if any_single_edge_length {
                 let edge_length = possible_lengths[edge_length_idx];
                 min_edge_length = Some(edge_length);
                 max_edge_length = Some(edge_length);
-                println!("\nChecking edge length {} ({}/{})", edge_length, edge_length_idx+1, possible_lengths.len());
+                progress.message(format!("Checking edge length {} ({}/{})", edge_length, edge_length_idx+1, possible_lengths.len()));
             }
 
-            println!("\nEnumerating hyperplanes...");
+            progress.stage(FacetingStage::Hyperplanes);
 
             let mut hyperplane_orbits = Vec::new();
 
-            if only_below_vertex {
+            let enumerate_hyperplanes = resumed.hyperplane_orbits.is_none();
+
+            if let Some(orbits) = resumed.hyperplane_orbits.take() {
+                hyperplane_orbits = orbits;
+            }
+            else if only_below_vertex {
                 for v_orbit in &vertex_orbits {
                     let mut map = BTreeMap::<OrderedFloat<f64>, Vec<usize>>::new();
                     let rep = v_orbit[0];
@@ -1241,8 +1824,11 @@ impl Concrete {
                         list.sort_unstable();
 
                         if now.elapsed().as_millis() > DELAY {
-                            print!("{}loop {}, verts {:?}", CL, dbg_count, list);
-                            std::io::stdout().flush().unwrap();
+                            if progress.cancelled() {
+                                return;
+                            }
+
+                            progress.status(format!("loop {}, verts {:?}", dbg_count, list));
                             now = Instant::now();
                         }
                         dbg_count += 1;
@@ -1329,8 +1915,11 @@ impl Concrete {
                     let rep = orbit[0]; // We only need one representative per orbit.
                     for vertex in rep+1..vertices.len() {
                         if now.elapsed().as_millis() > DELAY {
-                            print!("{}{} edge orbits, verts [{}, {}]", CL, pair_orbits.len(), rep, vertex);
-                            std::io::stdout().flush().unwrap();
+                            if progress.cancelled() {
+                                return;
+                            }
+
+                            progress.status(format!("{} edge orbits, verts [{}, {}]", pair_orbits.len(), rep, vertex));
                             now = Instant::now();
                         }
 
@@ -1362,7 +1951,7 @@ impl Concrete {
                     }
                 }
 
-                println!("{}{} edge orbit{}", CL, pair_orbits.len(), if pair_orbits.len() == 1 {""} else {"s"});
+                progress.message(format!("{} edge orbit{}", pair_orbits.len(), if pair_orbits.len() == 1 {""} else {"s"}));
 
                 // Enumerate subspaces between lines and hyperplanes
 
@@ -1374,8 +1963,11 @@ impl Concrete {
                     for tuple in tuple_orbits {
                         for new_vertex in tuple[tuple.len()-1]..vertices.len() {
                             if now.elapsed().as_millis() > DELAY {
-                                print!("{}{} {}-plane orbits, verts {:?}", CL, new_tuple_orbits.len(), number-1, tuple);
-                                std::io::stdout().flush().unwrap();
+                                if progress.cancelled() {
+                                    return;
+                                }
+
+                                progress.status(format!("{} {}-plane orbits, verts {:?}", new_tuple_orbits.len(), number-1, tuple));
                                 now = Instant::now();
                             }
 
@@ -1423,7 +2015,7 @@ impl Concrete {
                             checked.insert(new_tuple);
                         }
                     }
-                    println!("{}{} {}-plane orbit{}", CL, new_tuple_orbits.len(), number-1, if new_tuple_orbits.len() == 1 {""} else {"s"});
+                    progress.message(format!("{} {}-plane orbit{}", new_tuple_orbits.len(), number-1, if new_tuple_orbits.len() == 1 {""} else {"s"}));
                     tuple_orbits = new_tuple_orbits.iter().map(|x| x.clone()).collect();
                 }
 
@@ -1438,8 +2030,11 @@ impl Concrete {
                         tuple.push(new_vertex);
 
                         if now.elapsed().as_millis() > DELAY {
-                            print!("{}{} hyperplane orbits, verts {:?}", CL, hyperplane_orbits.len(), tuple);
-                            std::io::stdout().flush().unwrap();
+                            if progress.cancelled() {
+                                return;
+                            }
+
+                            progress.status(format!("{} hyperplane orbits, verts {:?}", hyperplane_orbits.len(), tuple));
                             now = Instant::now();
                         }
 
@@ -1522,18 +2117,28 @@ impl Concrete {
                 sum += count as u64;
             }
 
-            println!("{}{} hyperplanes in {} orbit{}", CL, sum, hyperplane_orbits.len(), if hyperplane_orbits.len() == 1 {""} else {"s"});
+            progress.message(format!("{} hyperplanes in {} orbit{}", sum, hyperplane_orbits.len(), if hyperplane_orbits.len() == 1 {""} else {"s"}));
 
-            println!("\nFaceting hyperplanes...");
+            if enumerate_hyperplanes && checkpointer.enabled() {
+                checkpointer.save(&Checkpoint {
+                    edge_length_idx,
+                    result_count,
+                    hyperplane_orbits: Some(hyperplane_orbits.clone()),
+                    ..base.clone()
+                }, progress);
+            }
 
-            // Facet the hyperplanes
-            let mut possible_facets = Vec::new();
-            let mut possible_facets_global: Vec<Vec<(Ranks, Vec<(usize,usize)>)>> = Vec::new(); // copy of above but with global vertex indices
-            let mut compound_facets: Vec<HashMap<usize, (usize,usize)>> = Vec::new();
-            let mut ridges: Vec<Vec<Vec<Ranks>>> = Vec::new();
-            let mut ff_counts = Vec::new();
+            progress.stage(FacetingStage::FacetingHyperplanes);
 
-            for (idx, orbit) in hyperplane_orbits.iter().enumerate() {
+            // Facet the hyperplanes
+            let mut possible_facets = std::mem::take(&mut resumed.possible_facets);
+            let mut possible_facets_global: Vec<Vec<(Ranks, Vec<(usize,usize)>)>> = std::mem::take(&mut resumed.possible_facets_global); // copy of above but with global vertex indices
+            let mut compound_facets: Vec<HashMap<usize, (usize,usize)>> = std::mem::take(&mut resumed.compound_facets);
+            let mut ridges: Vec<Vec<Vec<Ranks>>> = std::mem::take(&mut resumed.ridges);
+            let mut ff_counts = std::mem::take(&mut resumed.ff_counts);
+            let faceted_hyperplanes = possible_facets.len();
+
+            for (idx, orbit) in hyperplane_orbits.iter().enumerate().skip(faceted_hyperplanes) {
                 let (hp, hp_v) = (orbit.0.clone(), orbit.1.clone());
                 let mut stabilizer = Vec::new();
                 for row in &vertex_map {
@@ -1574,7 +2179,25 @@ impl Concrete {
                 };
 
                 let (possible_facets_row, ff_counts_row, ridges_row, compound_facets_row) =
-                    faceting_subdim(rank-1, hp, points, new_stabilizer, min_edge_length, max_edge_length, max_per_hyperplane, uniform, noble_package, true);
+                    faceting_subdim(rank-1, hp, points, new_stabilizer, min_edge_length, max_edge_length, max_per_hyperplane, uniform, noble_package, true, progress);
+
+                if progress.cancelled() {
+                    // The facets of this hyperplane are incomplete, so we only save the previous ones.
+                    if checkpointer.enabled() && possible_facets.len() > faceted_hyperplanes {
+                        checkpointer.save(&Checkpoint {
+                            edge_length_idx,
+                            result_count,
+                            hyperplane_orbits: Some(hyperplane_orbits.clone()),
+                            possible_facets: possible_facets.clone(),
+                            possible_facets_global: possible_facets_global.clone(),
+                            compound_facets: compound_facets.clone(),
+                            ridges: ridges.clone(),
+                            ff_counts: ff_counts.clone(),
+                            ..base.clone()
+                        }, progress);
+                    }
+                    return;
+                }
 
                 let mut possible_facets_global_row = Vec::new();
                 for f in &possible_facets_row {
@@ -1598,15 +2221,29 @@ impl Concrete {
                 ridges.push(ridges_row);
                 ff_counts.push(ff_counts_row);
 
-                println!("{}{}: {} facets, {} verts, {} copies", CL, idx, possible_facets_row.len(), hp_v.len(), orbit.2);
-                std::io::stdout().flush().unwrap();
+                progress.message(format!("{}: {} facets, {} verts, {} copies", idx, possible_facets_row.len(), hp_v.len(), orbit.2));
+
+                if checkpointer.due() || (checkpointer.enabled() && idx + 1 == hyperplane_orbits.len()) {
+                    checkpointer.save(&Checkpoint {
+                        edge_length_idx,
+                        result_count,
+                        hyperplane_orbits: Some(hyperplane_orbits.clone()),
+                        possible_facets: possible_facets.clone(),
+                        possible_facets_global: possible_facets_global.clone(),
+                        compound_facets: compound_facets.clone(),
+                        ridges: ridges.clone(),
+                        ff_counts: ff_counts.clone(),
+                        ..base.clone()
+                    }, progress);
+                }
             }
 
-            println!("\nComputing ridges...");
+            progress.stage(FacetingStage::Ridges);
 
-            let mut ridge_idx_orbits = Vec::new();
+            // If we're resuming after the ridges were sorted, they were dropped and this does nothing.
+            let mut ridge_idx_orbits = std::mem::take(&mut resumed.ridge_idx_orbits);
             let mut ridge_orbits = HashMap::new();
-            let mut ridge_counts = Vec::new(); // Counts the number of ridges in each orbit
+            let mut ridge_counts = std::mem::take(&mut resumed.ridge_counts); // Counts the number of ridges in each orbit
             let mut orbit_idx = 0;
 
             for (hp_i, ridges_row) in ridges.iter_mut().enumerate() {
@@ -1748,8 +2385,11 @@ impl Concrete {
                             orbit_idx += 1;
                             
                             if now.elapsed().as_millis() > DELAY {
-                                print!("{}{}/{} hp, {} ridges", CL, hp_i, hyperplane_orbits.len(), ridge_orbits.len());
-                                std::io::stdout().flush().unwrap();
+                                if progress.cancelled() {
+                                    return;
+                                }
+
+                                progress.status(format!("{}/{} hp, {} ridges", hp_i, hyperplane_orbits.len(), ridge_orbits.len()));
                                 now = Instant::now();
                             }
                         }
@@ -1758,12 +2398,31 @@ impl Concrete {
                 }
                 ridge_idx_orbits.push(r_i_o_row);
 
-                print!("{}{}/{} hp, {} ridges", CL, hp_i+1, hyperplane_orbits.len(), ridge_orbits.len());
-                std::io::stdout().flush().unwrap();
+                progress.status(format!("{}/{} hp, {} ridges", hp_i+1, hyperplane_orbits.len(), ridge_orbits.len()));
+            }
+
+            // Everything needed from here on, without the ridges.
+            let checkpoint_base = checkpointer.enabled().then(|| Checkpoint {
+                edge_length_idx,
+                result_count,
+                hyperplane_orbits: Some(hyperplane_orbits.clone()),
+                possible_facets: possible_facets.clone(),
+                possible_facets_global: possible_facets_global.clone(),
+                compound_facets: compound_facets.clone(),
+                ff_counts: ff_counts.clone(),
+                ridge_idx_orbits: ridge_idx_orbits.clone(),
+                ridge_counts: ridge_counts.clone(),
+                building: resumed.building,
+                ..base.clone()
+            });
+            if let Some(checkpoint) = &checkpoint_base {
+                if resumed.facets_queue.is_none() {
+                    checkpointer.save(checkpoint, progress);
+                }
             }
 
             // Actually do the faceting
-            println!("\n\nCombining...");
+            progress.stage(FacetingStage::Combining);
 
             let mut ridge_muls = Vec::new();
             let mut ones = vec![Vec::<(usize, usize)>::new(); ridge_counts.len()];
@@ -1794,30 +2453,55 @@ impl Concrete {
                 ridge_muls.push(ridge_muls_hp);
             }
 
-            let mut output_facets = Vec::new();
+            let mut output_facets = std::mem::take(&mut resumed.output_facets);
 
-            let mut facets_queue = VecDeque::<(
-                Vec<(usize, usize)>, // list of facets
-                usize, // min hyperplane
-                Vec<usize> // cached ridge muls
-            )>::new();
+            let mut facets_queue = FacetsQueue::new();
 
-            for (hp, list) in possible_facets.iter().enumerate() {
-                for f in 0..list.len() {
-                    facets_queue.push_back((
-                        vec![(hp, f)],
-                        hp,
-                        vec![0; ridge_counts.len()]
-                    ));
+            if let Some(queue) = resumed.facets_queue.take() {
+                facets_queue = queue;
+            } else {
+                for (hp, list) in possible_facets.iter().enumerate() {
+                    for f in 0..list.len() {
+                        facets_queue.push_back((
+                            vec![(hp, f)],
+                            hp,
+                            vec![0; ridge_counts.len()]
+                        ));
+                    }
                 }
             }
 
-            while let Some((facets, min_hp, cached_ridge_muls)) = facets_queue.pop_back() {
+            loop {
+                if let Some(checkpoint) = &checkpoint_base {
+                    if checkpointer.due() || facets_queue.is_empty() {
+                        checkpointer.save(&Checkpoint {
+                            facets_queue: Some(facets_queue.clone()),
+                            output_facets: output_facets.clone(),
+                            ..checkpoint.clone()
+                        }, progress);
+                    }
+                }
+
+                let (facets, min_hp, cached_ridge_muls) = match facets_queue.pop_back() {
+                    Some(next) => next,
+                    None => break,
+                };
 
                 if now.elapsed().as_millis() > DELAY {
-                    print!("{}", CL);
-                    print!("{:.115}", format!("{} facetings, {:?}", output_facets.len(), facets));
-                    std::io::stdout().flush().unwrap();
+                    if progress.cancelled() {
+                        // Saves the state before this faceting was taken out of the queue.
+                        if let Some(checkpoint) = checkpoint_base {
+                            facets_queue.push_back((facets, min_hp, cached_ridge_muls));
+                            checkpointer.save(&Checkpoint {
+                                facets_queue: Some(facets_queue),
+                                output_facets,
+                                ..checkpoint
+                            }, progress);
+                        }
+                        return;
+                    }
+
+                    progress.status(format!("{} facetings, {:?}", output_facets.len(), facets));
                     now = Instant::now();
                 }
 
@@ -1928,12 +2612,20 @@ impl Concrete {
                 }
             }
 
-            println!("{}{} facetings", CL, output_facets.len());
+            progress.message(format!("{} facetings", output_facets.len()));
 
             output_facets.sort_unstable();
 
+            // Everything needed to resume building, so that the facetings
+            // already output aren't output again.
+            let building_base = checkpoint_base.map(|checkpoint| Checkpoint {
+                facets_queue: Some(FacetsQueue::new()),
+                output_facets: output_facets.clone(),
+                ..checkpoint
+            });
+
             if !include_compounds {
-                println!("\nFiltering mixed compounds...");
+                progress.stage(FacetingStage::Filtering);
                 let output_idxs = filter_irc(&output_facets);
                 let mut output_new = Vec::new();
                 for idx in output_idxs {
@@ -1944,20 +2636,44 @@ impl Concrete {
 
             // Output the faceted polytopes. We will build them from their sets of facet orbits.
 
-            println!("Found {} facetings", output_facets.len());
-            println!("\nBuilding...");
+            progress.message(format!("Found {} facetings", output_facets.len()));
+            progress.stage(FacetingStage::Building);
             let mut used_facets = HashMap::new(); // used for outputting the facets at the end if `save_facets` is `true`.
-            let mut faceting_idx = 0; // We used to use `output.len()` but this doesn't work if you skip outputting the polytopes.
+            let (skip, mut faceting_idx) = resumed.building.unwrap_or_default(); // We used to use `output.len()` but this doesn't work if you skip outputting the polytopes.
+
+            for (built, facets) in output_facets.into_iter().enumerate() {
+                if let Some(checkpoint) = &building_base {
+                    if checkpointer.due() || progress.cancelled() {
+                        checkpointer.save(&Checkpoint {
+                            result_count,
+                            building: Some((built.max(skip), faceting_idx)),
+                            ..checkpoint.clone()
+                        }, progress);
+                    }
+                }
+                if progress.cancelled() {
+                    return;
+                }
+
+                // The facetings before `skip` were output before the checkpoint.
+                // We only build them again if we need their facets.
+                let already_output = built < skip;
+                if already_output && !save_facets {
+                    continue;
+                }
+                if !already_output && matches!(max_results, Some(max) if result_count >= max) {
+                    break;
+                }
 
-            for facets in output_facets {
                 if !save && !save_facets {
                     let mut facets_fmt = String::new();
                     for facet in &facets {
                         facets_fmt.push_str(&format!(" ({},{})", facet.0, facet.1));
                     }
-                    println!("Faceting {}:{}", faceting_idx, facets_fmt);
+                    progress.message(format!("Faceting {}:{}", faceting_idx, facets_fmt));
 
                     faceting_idx += 1;
+                    result_count += 1;
                     continue
                 }
 
@@ -1975,13 +2691,17 @@ impl Concrete {
                     }
 
                     if already_found_all { 
+                        if already_output {
+                            continue;
+                        }
                         let mut facets_fmt = String::new();
                         for facet in &facets {
                             facets_fmt.push_str(&format!(" ({},{})", facet.0, facet.1));
                         }
-                        println!("Faceting {}:{}", faceting_idx, facets_fmt);
+                        progress.message(format!("Faceting {}:{}", faceting_idx, facets_fmt));
 
                         faceting_idx += 1;
+                        result_count += 1;
                         continue
                     }
                 }
@@ -2142,7 +2862,7 @@ impl Concrete {
                             facets_fmt.push_str(&format!(" ({},{})", facet.0, facet.1));
                         }
 
-                        if save {
+                        if save && !already_output {
                             let name = format!("faceting {}{}{}{}",
                                 if any_single_edge_length {edge_length_idx.to_string() + "."} else {"".to_string()},
                                 faceting_idx,
@@ -2150,15 +2870,15 @@ impl Concrete {
                                 fissary_status
                             );
 
-                            if save_to_file {
-                                let mut path = PathBuf::from(&file_path);
+                            if let Some(dir) = save_to_file {
+                                let mut path = dir.clone();
                                 path.push(format!("{}.off", name));
                                 match poly.to_path(&path, Default::default()) {
                                     Err(why) => panic!("couldn't write to {}: {}", path.display(), why),
                                     Ok(_) => (),
                                 }
                             } else {
-                                output.push((poly.clone(), Some(name)));
+                                progress.output(poly.clone(), Some(name));
                             }
                         }
 
@@ -2167,10 +2887,13 @@ impl Concrete {
                                 used_facets.insert(orbit, poly.facet(idx).unwrap());
                             }
                         }
-                        
-                        println!("Faceting {}:{}{}", faceting_idx, facets_fmt, fissary_status);
 
-                        faceting_idx += 1;
+                        if !already_output {
+                            progress.message(format!("Faceting {}:{}{}", faceting_idx, facets_fmt, fissary_status));
+
+                            faceting_idx += 1;
+                            result_count += 1;
+                        }
                     }
                 }
             }
@@ -2187,28 +2910,267 @@ impl Concrete {
                     } else {
                         poly.recenter();
                     }
-                    if save_to_file {
-                        let mut path = PathBuf::from(&file_path);
+                    if let Some(dir) = save_to_file {
+                        let mut path = dir.clone();
                         path.push(format!("facet ({},{}).off", i.0.0, i.0.1));
                         match poly.to_path(&path, Default::default()) {
                             Err(why) => panic!("couldn't write to {}: {}", path.display(), why),
                             Ok(_) => (),
                         }
                     } else {  
-                        output.push((poly, Some(format!("facet ({},{})", i.0.0, i.0.1))));
+                        progress.output(poly, Some(format!("facet ({},{})", i.0.0, i.0.1)));
                     }
                 }
             }
 
-            if any_single_edge_length {
+            let reached_max = matches!(max_results, Some(max) if result_count >= max);
+            if any_single_edge_length && !reached_max {
                 edge_length_idx += 1;
                 if edge_length_idx < possible_lengths.len() {
+                    if checkpointer.enabled() {
+                        checkpointer.save(&Checkpoint {
+                            edge_length_idx,
+                            result_count,
+                            ..base.clone()
+                        }, progress);
+                    }
                     continue;
                 }
             }
 
-            println!("\nFaceting complete\n");
-            return output
+            checkpointer.remove();
+            progress.stage(FacetingStage::Done);
+            return
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collects the stages and results of a faceting, and cancels it after a
+    /// given number of stages.
+    #[derive(Default)]
+    struct TestProgress {
+        /// The stages that have been started.
+        stages: Vec<FacetingStage>,
+
+        /// The messages logged.
+        messages: Vec<String>,
+
+        /// The facetings found.
+        output: Vec<(Concrete, Option<String>)>,
+
+        /// The number of stages after which to cancel.
+        cancel_after: Option<usize>,
+
+        /// The number of facetings after which to cancel.
+        cancel_after_output: Option<usize>,
+    }
+
+    impl FacetingProgress for TestProgress {
+        fn stage(&mut self, stage: FacetingStage) {
+            self.stages.push(stage);
+        }
+
+        fn message(&mut self, message: String) {
+            self.messages.push(message);
         }
+
+        fn status(&mut self, _status: String) {}
+
+        fn output(&mut self, poly: Concrete, label: Option<String>) {
+            self.output.push((poly, label));
+        }
+
+        fn cancelled(&self) -> bool {
+            matches!(self.cancel_after, Some(n) if self.stages.len() >= n)
+                || matches!(self.cancel_after_output, Some(n) if self.output.len() >= n)
+        }
+    }
+
+    /// Facets a cube with the given options, reporting to the given progress.
+    fn facet_cube_with(options: &FacetingOptions, progress: &mut TestProgress) {
+        let mut cube = Concrete::cube();
+        let vertices = cube.vertices.clone();
+        cube.faceting_with_progress(vertices, GroupEnum::Chiral(false), options, progress);
+    }
+
+    /// Facets a cube, reporting to the given progress.
+    fn facet_cube(progress: &mut TestProgress) {
+        facet_cube_with(
+            &FacetingOptions::new()
+                .with_mark_fissary(false)
+                .with_label_facets(false),
+            progress,
+        );
     }
-}
\ No newline at end of file
+
+    /// Checks that the results of a faceting are reported as they're found.
+    #[test]
+    fn progress() {
+        let mut progress = TestProgress::default();
+        facet_cube(&mut progress);
+
+        assert_eq!(progress.stages.first(), Some(&FacetingStage::Symmetry));
+        assert_eq!(progress.stages.last(), Some(&FacetingStage::Done));
+        assert!(progress.output.iter().any(|(poly, _)| poly.abs.el_count_iter().eq([1, 8, 12, 6, 1])));
+    }
+
+    /// Checks that a cancelled faceting stops early.
+    #[test]
+    fn cancel() {
+        let mut progress = TestProgress {
+            cancel_after: Some(4),
+            ..Default::default()
+        };
+        facet_cube(&mut progress);
+
+        assert!(progress.output.is_empty());
+        assert!(!progress.stages.contains(&FacetingStage::Done));
+    }
+
+    /// Checks that the faceting stops after the given number of results.
+    #[test]
+    fn max_results() {
+        let mut all = TestProgress::default();
+        facet_cube(&mut all);
+        assert!(all.output.len() > 1);
+
+        let mut progress = TestProgress::default();
+        facet_cube_with(&FacetingOptions::new().with_max_results(Some(1)), &mut progress);
+        assert_eq!(progress.output.len(), 1);
+        assert_eq!(progress.stages.last(), Some(&FacetingStage::Done));
+    }
+
+    /// Checks that the facetings can be iterated over lazily.
+    #[test]
+    fn iter() {
+        let cube = Concrete::cube();
+        let vertices = cube.vertices.clone();
+        let mut progress = TestProgress::default();
+        facet_cube(&mut progress);
+
+        let facetings: Vec<_> = cube
+            .clone()
+            .faceting_iter(vertices.clone(), GroupEnum::Chiral(false), FacetingOptions::new())
+            .collect();
+        assert_eq!(facetings.len(), progress.output.len());
+
+        let mut iter = cube.faceting_iter(vertices, GroupEnum::Chiral(false), FacetingOptions::new());
+        assert!(iter.next().is_some());
+        drop(iter);
+    }
+
+    /// Checks that the options can be saved and loaded as presets.
+    #[test]
+    fn options_ron() {
+        let options = FacetingOptions::new()
+            .with_edge_length(1.0)
+            .with_noble(Some(2))
+            .with_save_to_file(Some(PathBuf::from("facetings")))
+            .with_max_results(Some(10));
+
+        let preset = ron::to_string(&options).unwrap();
+        assert_eq!(ron::from_str::<FacetingOptions>(&preset).unwrap(), options);
+
+        // Missing options take their default values.
+        let partial: FacetingOptions = ron::from_str("(uniform: true)").unwrap();
+        assert_eq!(partial, FacetingOptions::new().with_uniform(true));
+    }
+
+    /// Checks that a cancelled faceting can be resumed from its checkpoint,
+    /// finding the same facetings as an uninterrupted one.
+    #[test]
+    fn resume() {
+        let mut all = TestProgress::default();
+        facet_cube_with(&FacetingOptions::new(), &mut all);
+        let labels: Vec<_> = all.output.into_iter().map(|(_, label)| label).collect();
+
+        for cancel_after in 4..=7 {
+            let path = std::env::temp_dir().join(format!("miratope-faceting-checkpoint-{}.ron", cancel_after));
+            let options = FacetingOptions::new().with_checkpoint(Some(path.clone()));
+
+            let mut cancelled = TestProgress {
+                cancel_after: Some(cancel_after),
+                ..Default::default()
+            };
+            facet_cube_with(&options, &mut cancelled);
+            assert!(path.exists());
+
+            let mut resumed = TestProgress::default();
+            facet_cube_with(&options, &mut resumed);
+            assert!(resumed.messages.iter().any(|message| message.starts_with("Resuming")));
+
+            let resumed_labels: Vec<_> = cancelled
+                .output
+                .into_iter()
+                .chain(resumed.output)
+                .map(|(_, label)| label)
+                .collect();
+            assert_eq!(resumed_labels, labels);
+            assert!(!path.exists());
+        }
+    }
+
+    /// Checks that a faceting cancelled while building can be resumed without
+    /// outputting any faceting twice.
+    #[test]
+    fn resume_building() {
+        // The facets are output as well, to check that they're still found
+        // after resuming.
+        let options = FacetingOptions::new().with_save(true, true);
+        let mut all = TestProgress::default();
+        facet_cube_with(&options, &mut all);
+        let labels: Vec<_> = all.output.into_iter().map(|(_, label)| label).collect();
+
+        let path = std::env::temp_dir().join("miratope-faceting-checkpoint-building.ron");
+        let options = options.with_checkpoint(Some(path.clone()));
+
+        let mut cancelled = TestProgress {
+            cancel_after_output: Some(1),
+            ..Default::default()
+        };
+        facet_cube_with(&options, &mut cancelled);
+        assert_eq!(cancelled.stages.last(), Some(&FacetingStage::Building));
+        assert_eq!(cancelled.output.len(), 1);
+        assert!(path.exists());
+
+        let mut resumed = TestProgress::default();
+        facet_cube_with(&options, &mut resumed);
+        assert!(resumed.messages.iter().any(|message| message.starts_with("Resuming")));
+
+        let resumed_labels: Vec<_> = cancelled
+            .output
+            .into_iter()
+            .chain(resumed.output)
+            .map(|(_, label)| label)
+            .collect();
+        assert_eq!(resumed_labels, labels);
+        assert!(!path.exists());
+    }
+
+    /// Checks that a checkpoint from a different faceting is ignored.
+    #[test]
+    fn checkpoint_mismatch() {
+        let path = std::env::temp_dir().join("miratope-faceting-checkpoint-mismatch.ron");
+        let options = FacetingOptions::new().with_checkpoint(Some(path.clone()));
+
+        let mut cancelled = TestProgress {
+            cancel_after: Some(6),
+            ..Default::default()
+        };
+        facet_cube_with(&options.clone().with_uniform(true), &mut cancelled);
+        assert!(path.exists());
+
+        let mut all = TestProgress::default();
+        facet_cube_with(&FacetingOptions::new(), &mut all);
+        let mut restarted = TestProgress::default();
+        facet_cube_with(&options, &mut restarted);
+
+        assert!(restarted.messages.iter().any(|message| message.contains("starting over")));
+        assert_eq!(restarted.stages, all.stages);
+        assert_eq!(restarted.output.len(), all.output.len());
+        assert!(!path.exists());
+    }
+}
@@ -1,9 +1,13 @@
 //! Declares the [`Concrete`] polytope type and all associated data structures.
 
+pub mod alternate;
+pub mod classify;
+pub mod convex;
 pub mod cycle;
 pub mod element_types;
 pub mod faceting;
 pub mod symmetry;
+pub mod wythoff;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -0,0 +1,602 @@
+//! Contains the code to build polytopes from their Coxeter diagrams via the
+//! [Wythoff construction](https://polytope.miraheze.org/wiki/Wythoffian_polytope).
+//!
+//! Rather than building the polytope geometrically, we build its face lattice
+//! directly from the Coxeter group. Every subset of nodes in the diagram such
+//! that each of its connected components contains a ringed node determines a
+//! type of element, whose elements correspond to the cosets of a certain
+//! parabolic subgroup. Two elements are incident whenever their cosets
+//! intersect. This makes the construction exact, and allows it to work just as
+//! well for star polytopes.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{element_types::ElementType, Concrete};
+use crate::{
+    abs::{AbstractBuilder, ElementMap, Ranked, SubelementList, Subelements},
+    cox::cd::{Cd, Node},
+    float::Float,
+    geometry::{Matrix, MatrixOrd, Point, Vector},
+};
+
+use vec_like::VecLike;
+
+/// The Coxeter group of a diagram, stored as an explicit list of matrices
+/// together with its right multiplication table by the generators.
+struct CoxeterTable {
+    /// The elements of the group.
+    elements: Vec<Matrix<f64>>,
+
+    /// The index of the product of each element by each generator on the
+    /// right.
+    mul: Vec<Vec<usize>>,
+
+    /// The index of the identity.
+    identity: usize,
+}
+
+impl CoxeterTable {
+    /// Enumerates the Coxeter group of a diagram. Returns `None` if the group
+    /// isn't spherical.
+    fn new(cd: &Cd) -> Option<Self> {
+        let gen_iter = cd.cox().gen_iter()?;
+        let gens = gen_iter.gens.clone();
+        let elements: Vec<_> = gen_iter.collect();
+
+        let indices: BTreeMap<_, _> = elements
+            .iter()
+            .enumerate()
+            .map(|(idx, el)| (MatrixOrd::new(el.clone()), idx))
+            .collect();
+
+        let mul = elements
+            .iter()
+            .map(|el| {
+                gens.iter()
+                    .map(|gen| indices[&MatrixOrd::new(el * gen)])
+                    .collect()
+            })
+            .collect();
+
+        let identity = indices[&MatrixOrd::new(Matrix::identity(cd.dim(), cd.dim()))];
+        Some(Self {
+            elements,
+            mul,
+            identity,
+        })
+    }
+
+    /// Returns the number of elements in the group.
+    fn order(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Labels every element of the group by the index of its left coset with
+    /// respect to the subgroup generated by a set of generators. Returns the
+    /// labels, together with a representative for each coset.
+    fn cosets(&self, gens: &[usize]) -> (Vec<usize>, Vec<usize>) {
+        let mut labels = vec![usize::MAX; self.order()];
+        let mut reps = Vec::new();
+
+        for start in 0..self.order() {
+            if labels[start] != usize::MAX {
+                continue;
+            }
+
+            let label = reps.len();
+            reps.push(start);
+            labels[start] = label;
+
+            // Floods the coset by right multiplication by the generators.
+            let mut stack = vec![start];
+            while let Some(el) = stack.pop() {
+                for &gen in gens {
+                    let next = self.mul[el][gen];
+                    if labels[next] == usize::MAX {
+                        labels[next] = label;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+
+        (labels, reps)
+    }
+}
+
+/// A type of element in a Wythoffian polytope, determined by a subset of the
+/// nodes of its diagram.
+struct WythoffType {
+    /// The nodes whose mirrors generate the element.
+    nodes: Vec<usize>,
+
+    /// The index of the element containing each group element.
+    labels: Vec<usize>,
+
+    /// A group element mapping the base element of this type into each
+    /// element.
+    reps: Vec<usize>,
+}
+
+/// Returns whether every connected component of the subgraph induced by a set
+/// of nodes contains a ringed node.
+fn is_valid(nodes: &[usize], ringed: &[bool], adjacent: &[Vec<bool>]) -> bool {
+    let mut visited = vec![false; nodes.len()];
+
+    for start in 0..nodes.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let mut has_ring = false;
+        let mut stack = vec![start];
+
+        while let Some(i) = stack.pop() {
+            has_ring |= ringed[nodes[i]];
+
+            for j in 0..nodes.len() {
+                if !visited[j] && adjacent[nodes[i]][nodes[j]] {
+                    visited[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+
+        if !has_ring {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// A Wythoffian polytope, together with the data from its construction.
+struct Wythoffian {
+    /// The polytope itself.
+    poly: Concrete,
+
+    /// The element types of the polytope.
+    types: Vec<Vec<ElementType>>,
+
+    /// The type of each element.
+    type_of_element: ElementMap<usize>,
+
+    /// A group element mapping the generator point into each vertex.
+    vertex_reps: Vec<usize>,
+}
+
+impl Wythoffian {
+    /// Builds the Wythoffian polytope described by a Coxeter diagram, whose
+    /// group has already been enumerated. Snub nodes are treated as ringed
+    /// nodes. Returns `None` if the Coxeter group isn't spherical.
+    fn new(cd: &Cd, table: &CoxeterTable) -> Option<Self> {
+        let generator = cd.wythoff_generator(cd.node_vector())?;
+
+        let dim = cd.dim();
+        let cox = cd.cox();
+        let ringed: Vec<_> = cd.node_iter().map(|node| node != Node::Unringed).collect();
+        let adjacent: Vec<Vec<_>> = (0..dim)
+            .map(|i| {
+                (0..dim)
+                    .map(|j| i != j && (cox[(i, j)] - 2.0).fabs() > f64::EPS)
+                    .collect()
+            })
+            .collect();
+
+        // Finds the element types of every rank, as bitmasks of nodes. The
+        // nodes in components without rings can't belong to any of them, so
+        // the types of the top rank are contained in the types below.
+        let mut types_by_rank: Vec<Vec<WythoffType>> = Vec::new();
+        for mask in 0..(1usize << dim) {
+            let nodes: Vec<_> = (0..dim).filter(|&i| mask & (1 << i) != 0).collect();
+            if !is_valid(&nodes, &ringed, &adjacent) {
+                continue;
+            }
+
+            // The stabilizer of the element is generated by its own mirrors,
+            // together with the mirrors that contain it and are orthogonal to
+            // all of them.
+            let stabilizer: Vec<_> = (0..dim)
+                .filter(|&i| {
+                    mask & (1 << i) != 0 || (!ringed[i] && nodes.iter().all(|&j| !adjacent[i][j]))
+                })
+                .collect();
+            let (labels, reps) = table.cosets(&stabilizer);
+
+            let rank = nodes.len() + 1;
+            if types_by_rank.len() <= rank {
+                types_by_rank.resize_with(rank + 1, Vec::new);
+            }
+            types_by_rank[rank].push(WythoffType {
+                nodes,
+                labels,
+                reps,
+            });
+        }
+
+        let rank = types_by_rank.len() - 1;
+
+        // The index of the first element of each type in its rank.
+        let offsets: Vec<Vec<usize>> = types_by_rank
+            .iter()
+            .map(|types| {
+                types
+                    .iter()
+                    .scan(0, |offset, ty| {
+                        let res = *offset;
+                        *offset += ty.reps.len();
+                        Some(res)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Builds the face lattice. Two elements are incident whenever some
+        // group element maps both base elements into them.
+        let mut builder = AbstractBuilder::with_rank_capacity(rank);
+        builder.push_min();
+        builder.push_vertices(types_by_rank[1][0].reps.len());
+
+        for r in 2..=rank {
+            let mut subelements = SubelementList::new();
+
+            for ty in &types_by_rank[r] {
+                let mut subs = vec![BTreeSet::new(); ty.reps.len()];
+
+                for (sub_idx, sub_ty) in types_by_rank[r - 1].iter().enumerate() {
+                    if !sub_ty.nodes.iter().all(|i| ty.nodes.contains(i)) {
+                        continue;
+                    }
+
+                    for g in 0..table.order() {
+                        subs[ty.labels[g]].insert(offsets[r - 1][sub_idx] + sub_ty.labels[g]);
+                    }
+                }
+
+                for el_subs in subs {
+                    subelements.push(el_subs.into_iter().collect::<Subelements>());
+                }
+            }
+
+            builder.push(subelements);
+        }
+
+        // Builds the vertices as the images of the generator point.
+        let vertex_reps = types_by_rank[1][0].reps.clone();
+        let vertices = vertex_reps
+            .iter()
+            .map(|&g| &table.elements[g] * &generator)
+            .collect::<Vec<Point<f64>>>();
+
+        // Each element type consists of the cosets of a single subgroup.
+        let mut types = vec![Vec::new(); rank + 1];
+        let mut type_of_element = ElementMap::new();
+        type_of_element.push(vec![0]);
+        for r in 1..=rank {
+            let mut types_rank = Vec::new();
+            let mut type_of_element_rank = Vec::new();
+
+            for (idx, ty) in types_by_rank[r].iter().enumerate() {
+                types_rank.push(ElementType {
+                    example: offsets[r][idx],
+                    count: ty.reps.len(),
+                });
+                type_of_element_rank.resize(type_of_element_rank.len() + ty.reps.len(), idx);
+            }
+
+            if r < rank {
+                types[r] = types_rank;
+            }
+            type_of_element.push(type_of_element_rank);
+        }
+
+        // Safety: the cosets of the parabolic subgroups of a Coxeter group,
+        // ordered by intersection, form a valid abstract polytope.
+        let poly = Concrete::new(vertices, unsafe { builder.build() });
+        Some(Self {
+            poly,
+            types,
+            type_of_element,
+            vertex_reps,
+        })
+    }
+}
+
+/// The maximum number of iterations used to find uniform edge lengths for a
+/// snub polytope.
+const SNUB_ITERATIONS: usize = 100;
+
+impl Concrete {
+    /// Builds the Wythoffian polytope described by a Coxeter diagram, along
+    /// with its element types, in the same format as
+    /// [`Self::element_types_common`]. Every element type corresponds to a
+    /// subset of the nodes of the diagram, and the polytope has one element
+    /// per coset of the parabolic subgroup stabilizing it.
+    ///
+    /// Snub nodes are treated as ringed nodes, use [`Self::from_cd`] to
+    /// alternate them. The edge lengths are given by the values of the ringed
+    /// nodes. If no node is ringed, the result is a point. Returns `None` if
+    /// the Coxeter group isn't spherical.
+    pub fn from_cd_with_types(cd: &Cd) -> Option<(Self, Vec<Vec<ElementType>>, ElementMap<usize>)> {
+        let table = CoxeterTable::new(cd)?;
+        let wythoffian = Wythoffian::new(cd, &table)?;
+        Some((
+            wythoffian.poly,
+            wythoffian.types,
+            wythoffian.type_of_element,
+        ))
+    }
+
+    /// Builds the polytope described by a Coxeter diagram. If the diagram has
+    /// snub nodes, this builds the corresponding snub via
+    /// [`Self::snub_from_cd`]. Otherwise, see [`Self::from_cd_with_types`]
+    /// for more details.
+    pub fn from_cd(cd: &Cd) -> Option<Self> {
+        if cd.node_iter().any(|node| matches!(node, Node::Snub(_))) {
+            Self::snub_from_cd(cd)
+        } else {
+            Self::from_cd_with_types(cd).map(|(poly, _, _)| poly)
+        }
+    }
+
+    /// Builds the [snub](https://polytope.miraheze.org/wiki/Snub) described by
+    /// a Coxeter diagram. This is the alternation of the Wythoffian polytope
+    /// with the snub nodes ringed, keeping the vertices that are the images of
+    /// the generator point under an even number of snub node reflections.
+    /// Afterwards, the distances from the generator point to the mirrors of
+    /// the ringed nodes are solved for numerically, so that all of the edges
+    /// have unit length.
+    ///
+    /// Returns `None` if the Coxeter group isn't spherical, if the diagram
+    /// can't be alternated (which happens when a snub node and a non-snub node
+    /// are joined by an odd edge), or if no uniform solution is found.
+    pub fn snub_from_cd(cd: &Cd) -> Option<Self> {
+        let table = CoxeterTable::new(cd)?;
+        let wythoffian = Wythoffian::new(cd, &table)?;
+        let snub: Vec<_> = cd
+            .node_iter()
+            .map(|node| matches!(node, Node::Snub(_)))
+            .collect();
+
+        // Finds the parity of the number of snub reflections in each group
+        // element, making sure that it's well-defined.
+        let mut parity = vec![None; table.order()];
+        parity[table.identity] = Some(false);
+        let mut stack = vec![table.identity];
+        while let Some(el) = stack.pop() {
+            let p = parity[el].unwrap();
+
+            for (i, &next) in table.mul[el].iter().enumerate() {
+                let q = p ^ snub[i];
+                match parity[next] {
+                    None => {
+                        parity[next] = Some(q);
+                        stack.push(next);
+                    }
+                    Some(r) if r != q => return None,
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let kept: Vec<_> = (0..wythoffian.vertex_reps.len())
+            .filter(|&v| parity[wythoffian.vertex_reps[v]] == Some(false))
+            .collect();
+        let alternate = wythoffian.poly.alternate(&kept)?;
+        let reps: Vec<_> = kept.iter().map(|&v| wythoffian.vertex_reps[v]).collect();
+
+        // The generator point depends linearly on the node values. We only
+        // vary those of the ringed nodes, as the generator must lie on the
+        // other mirrors.
+        let ringed: Vec<_> = cd
+            .node_iter()
+            .enumerate()
+            .filter(|(_, node)| *node != Node::Unringed)
+            .map(|(i, _)| i)
+            .collect();
+        let dim = cd.dim();
+        let mut basis = Matrix::zeros(dim, ringed.len());
+        for (j, &i) in ringed.iter().enumerate() {
+            basis.set_column(
+                j,
+                &cd.wythoff_generator(Vector::from_fn(dim, |k, _| if k == i { 1.0 } else { 0.0 }))?,
+            );
+        }
+        let mut values =
+            Vector::from_iterator(ringed.len(), ringed.iter().map(|&i| cd.nodes()[i].value()));
+
+        // For each edge, the matrix mapping the node values to the edge's
+        // vector, and the Gram matrix of this map.
+        let mut edges = Vec::new();
+        if alternate.rank() >= 2 {
+            for edge in &alternate[2] {
+                let m = (&table.elements[reps[edge.subs[0]]] - &table.elements[reps[edge.subs[1]]])
+                    * &basis;
+                edges.push(m.transpose() * m);
+            }
+        }
+
+        // Makes every squared edge length equal to 1 via Gauss–Newton.
+        if !edges.is_empty() {
+            let mut converged = false;
+
+            for _ in 0..SNUB_ITERATIONS {
+                let residuals = Vector::from_iterator(
+                    edges.len(),
+                    edges.iter().map(|q| values.dot(&(q * &values)) - 1.0),
+                );
+                if residuals.amax() < f64::EPS {
+                    converged = true;
+                    break;
+                }
+
+                let mut jacobian = Matrix::zeros(edges.len(), ringed.len());
+                for (mut row, q) in jacobian.row_iter_mut().zip(&edges) {
+                    row.copy_from(&(q * &values * 2.0).transpose());
+                }
+
+                values += jacobian.svd(true, true).solve(&-residuals, f64::EPS).ok()?;
+            }
+
+            if !converged {
+                return None;
+            }
+        }
+
+        let generator = basis * values;
+        let vertices = reps
+            .iter()
+            .map(|&g| &table.elements[g] * &generator)
+            .collect();
+
+        Some(Self::new(vertices, alternate.abs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{conc::ConcretePolytope, test};
+
+    /// Builds a polytope from a Coxeter diagram and checks its element counts
+    /// and that all of its edges have unit length.
+    fn test_cd<I: IntoIterator<Item = usize>>(cd: &str, element_counts: I) {
+        let poly = Concrete::from_cd(&Cd::parse(cd).unwrap()).unwrap();
+        assert!(poly.is_equilateral_with(1.0), "{} is not equilateral", cd);
+        test(&poly, element_counts)
+    }
+
+    /// Diagrams without rings give points.
+    #[test]
+    fn point() {
+        test_cd("o", [1, 1]);
+        test_cd("o4o3o", [1, 1]);
+    }
+
+    /// Polygons and their truncations.
+    #[test]
+    fn polygon() {
+        test_cd("x", [1, 2, 1]);
+        test_cd("x5o", [1, 5, 5, 1]);
+        test_cd("x5x", [1, 10, 10, 1]);
+        test_cd("x5/2o", [1, 5, 5, 1]);
+        test_cd("x x", [1, 4, 4, 1]);
+    }
+
+    /// The uniform polyhedra in the cube's symmetry.
+    #[test]
+    fn cubic() {
+        test_cd("x4o3o", [1, 8, 12, 6, 1]);
+        test_cd("o4x3o", [1, 12, 24, 14, 1]);
+        test_cd("o4o3x", [1, 6, 12, 8, 1]);
+        test_cd("x4x3o", [1, 24, 36, 14, 1]);
+        test_cd("x4o3x", [1, 24, 48, 26, 1]);
+        test_cd("x4x3x", [1, 48, 72, 26, 1]);
+    }
+
+    /// Polytopes in four dimensions and up.
+    #[test]
+    fn higher() {
+        test_cd("x4o3o3o", [1, 16, 32, 24, 8, 1]);
+        test_cd("o3x3o4o", [1, 24, 96, 96, 24, 1]);
+        test_cd("x3o3o3o3o", [1, 6, 15, 20, 15, 6, 1]);
+        test_cd("x3o x3o", [1, 9, 18, 15, 6, 1]);
+    }
+
+    /// Nonconvex polytopes.
+    #[test]
+    fn star() {
+        test_cd("x5/2o3o", [1, 20, 30, 12, 1]);
+        test_cd("x5/2o5o", [1, 12, 30, 12, 1]);
+        test_cd("x5o5/2o", [1, 12, 30, 12, 1]);
+        test_cd("x3o5/2o", [1, 12, 30, 20, 1]);
+    }
+
+    /// Diagrams with an odd loop, like that of the octahemioctahedron.
+    #[test]
+    fn loops() {
+        test_cd("x3o3x3/2*a", [1, 12, 24, 12, 1]);
+    }
+
+    /// Lower-dimensional polytopes embedded in higher-dimensional space.
+    #[test]
+    fn embedded() {
+        test_cd("x3o o", [1, 3, 3, 1]);
+    }
+
+    /// Every element type of a Wythoffian polytope is a single orbit.
+    #[test]
+    fn types() {
+        let (poly, types, type_of_element) =
+            Concrete::from_cd_with_types(&Cd::parse("x4x3o").unwrap()).unwrap();
+        let counts: Vec<Vec<_>> = types
+            .iter()
+            .map(|t| t.iter().map(|t| t.count).collect())
+            .collect();
+        assert_eq!(
+            counts,
+            vec![vec![], vec![24], vec![12, 24], vec![6, 8], vec![]]
+        );
+
+        for r in 0..=poly.rank() {
+            assert_eq!(type_of_element[r].len(), poly.el_count(r));
+        }
+    }
+
+    /// Alternated Wythoffians that are already uniform.
+    #[test]
+    fn alternated() {
+        test_cd("s4o3o", [1, 4, 6, 4, 1]);
+        test_cd("s4o3o3o", [1, 8, 24, 32, 16, 1]);
+        test_cd("s2s2s", [1, 4, 6, 4, 1]);
+    }
+
+    /// Snubs whose edge lengths have to be solved for.
+    #[test]
+    fn snub() {
+        test_cd("s2s3s", [1, 6, 12, 8, 1]);
+        test_cd("s2s4s", [1, 8, 16, 10, 1]);
+        test_cd("s3s3s", [1, 12, 30, 20, 1]);
+        test_cd("s4s3s", [1, 24, 60, 38, 1]);
+        test_cd("s5s3s", [1, 60, 150, 92, 1]);
+        test_cd("s3s4o3o", [1, 96, 432, 480, 144, 1]);
+    }
+
+    /// Snub nodes can't be joined to other nodes by odd edges.
+    #[test]
+    fn invalid_snub() {
+        assert!(Concrete::from_cd(&Cd::parse("s3o3o").unwrap()).is_none());
+    }
+
+    /// Checks the circumradius of a polytope built from a Coxeter diagram, to
+    /// make sure that the correct region of space was used for its generator.
+    fn test_circumradius(cd: &str, radius: f64) {
+        let poly = Concrete::from_cd(&Cd::parse(cd).unwrap()).unwrap();
+        let sphere = poly.circumsphere().unwrap();
+        assert!(
+            (sphere.radius() - radius).abs() < f64::EPS,
+            "{} has circumradius {}, expected {}",
+            cd,
+            sphere.radius(),
+            radius
+        );
+    }
+
+    /// Convex and star polytopes have the expected circumradii.
+    #[test]
+    fn circumradius() {
+        test_circumradius("x4x", (1.0 + 0.5f64.sqrt()).sqrt());
+        test_circumradius("x4x3x", (13.0 + 6.0 * 2f64.sqrt()).sqrt() / 2.0);
+        test_circumradius("x5/2o3o", 3f64.sqrt() * (5f64.sqrt() - 1.0) / 4.0);
+        test_circumradius("s3s3s", (10.0 + 2.0 * 5f64.sqrt()).sqrt() / 4.0);
+        test_circumradius("s2s4s", ((4.0 + 2f64.sqrt()) / 8.0).sqrt());
+        test_circumradius("s4s3s", 1.343_713_373_744_6);
+    }
+
+    /// Non-spherical groups fail.
+    #[test]
+    fn non_spherical() {
+        assert!(Concrete::from_cd(&Cd::parse("x4o4o").unwrap()).is_none());
+    }
+}
@@ -446,7 +446,7 @@ impl Cd {
     }
 
     /// Returns a point in the position specified by the Coxeter diagram,
-    /// using the set of mirrors generated by [`Cox::normals`].    
+    /// using the set of mirrors generated by [`Cox::normals`].
     pub fn generator(&self) -> Option<Point<f64>> {
         let mut vector = self.node_vector();
 
@@ -455,6 +455,24 @@ impl Cd {
             .solve_upper_triangular_mut(&mut vector)
             .then(|| vector)
     }
+
+    /// Returns the generator point of the Wythoffian polytope that would
+    /// result from the Coxeter diagram if its nodes had the specified values,
+    /// using the set of mirrors generated by [`Cox::inner_normals`]. The
+    /// distance from this point to each mirror is half the value of the
+    /// corresponding node, so that reflecting it through the mirror gives an
+    /// edge of that length.
+    pub(crate) fn wythoff_generator(&self, values: Vector<f64>) -> Option<Point<f64>> {
+        let mut vector = values / 2.0;
+
+        // The rows of the transposed matrix are the mirror normals. Since
+        // they point into the fundamental domain, so does the generator.
+        self.cox()
+            .inner_normals()?
+            .transpose()
+            .solve_lower_triangular_mut(&mut vector)
+            .then(|| vector)
+    }
 }
 
 impl From<Cd> for Cox<f64> {
@@ -9,7 +9,7 @@ use std::{
 };
 
 use crate::float::Float;
-use crate::group::Group;
+use crate::group::{coxeter_plane::CoxeterPlane, Group};
 use crate::{geometry::Matrix, group::GenIter};
 
 use nalgebra::dmatrix;
@@ -98,6 +98,41 @@ impl Cox<f64> {
         Self::from_lin_diagram_iter(diagram.iter().copied(), diagram.len())
     }
 
+    /// Creates a Coxeter matrix from the entries above its diagonal, read row
+    /// by row. This is how programs like Jenn3D take Coxeter matrices as
+    /// input. Returns `None` if the number of entries doesn't match the
+    /// dimension.
+    pub fn from_upper_triangle(entries: &[f64], dim: usize) -> Option<Self> {
+        if entries.len() != dim * dim.saturating_sub(1) / 2 {
+            return None;
+        }
+
+        let mut cox = Self(Matrix::identity(dim, dim));
+        let mut entries = entries.iter();
+        for i in 0..dim {
+            for j in i + 1..dim {
+                cox.link(i, j, *entries.next()?);
+            }
+        }
+
+        Some(cox)
+    }
+
+    /// Returns the entries above the diagonal of the Coxeter matrix, read row
+    /// by row. See [`Self::from_upper_triangle`].
+    pub fn upper_triangle(&self) -> Vec<f64> {
+        let dim = self.dim();
+        let mut entries = Vec::with_capacity(dim * dim.saturating_sub(1) / 2);
+
+        for i in 0..dim {
+            for j in i + 1..dim {
+                entries.push(self[(i, j)]);
+            }
+        }
+
+        entries
+    }
+
     /// Returns the Coxeter matrix for the I2(x) group.
     pub fn i2(x: f64) -> Self {
         Self::from_lin_diagram(&[x])
@@ -143,6 +178,23 @@ impl Cox<f64> {
     /// Returns an upper triangular matrix whose columns are unit normal vectors
     /// for the hyperplanes described by the Coxeter matrix.
     pub fn normals(&self) -> Option<Matrix<f64>> {
+        self.normals_with_sign(1.0)
+    }
+
+    /// Returns an upper triangular matrix whose columns are the inner unit
+    /// normals of the fundamental domain of the Coxeter group, so that the dot
+    /// product of any two of them is −cos(π / m), where m is the corresponding
+    /// entry of the matrix. Unlike the vectors from [`Self::normals`], these
+    /// can't always be gotten by flipping some of those, as is the case for
+    /// diagrams with odd loops.
+    pub fn inner_normals(&self) -> Option<Matrix<f64>> {
+        self.normals_with_sign(-1.0)
+    }
+
+    /// Returns an upper triangular matrix whose columns are unit vectors, the
+    /// dot product of any two of which is `sign` times cos(π / m), where m is
+    /// the corresponding entry of the matrix.
+    fn normals_with_sign(&self, sign: f64) -> Option<Matrix<f64>> {
         let dim = self.dim();
         let mut mat = Matrix::zeros(dim, dim);
 
@@ -154,7 +206,7 @@ impl Cox<f64> {
             for (j, n_j) in prev_gens.column_iter().enumerate() {
                 // All other entries in the dot product between columns are zero.
                 let dot = n_i.rows_range(0..=j).dot(&n_j.rows_range(0..=j));
-                n_i[j] = ((f64::PI / self[(i, j)]).fcos() - dot) / n_j[j];
+                n_i[j] = (sign * (f64::PI / self[(i, j)]).fcos() - dot) / n_j[j];
             }
 
             // If the vector doesn't fit in spherical space.
@@ -171,7 +223,7 @@ impl Cox<f64> {
 
     /// Returns an iterator over the elements of the Coxeter group.
     pub fn gen_iter(&self) -> Option<GenIter<Matrix<f64>>> {
-        let normals = self.normals()?;
+        let normals = self.inner_normals()?;
         let dim = normals.nrows();
 
         // Builds a reflection matrix from a vector.
@@ -197,4 +249,81 @@ impl Cox<f64> {
     pub fn group(&self) -> Option<Group<GenIter<Matrix<f64>>>> {
         self.gen_iter().map(Into::into)
     }
+
+    /// Returns the product of the generators of the Coxeter group with the
+    /// specified indices, from left to right. Returns `None` if the group
+    /// isn't spherical, or if some index is out of bounds.
+    pub fn product(&self, word: &[usize]) -> Option<Matrix<f64>> {
+        let gens = self.gen_iter()?.gens;
+        let dim = self.dim();
+
+        word.iter().try_fold(Matrix::identity(dim, dim), |mat, &idx| {
+            gens.get(idx).map(|gen| mat * gen)
+        })
+    }
+
+    /// Returns the plane of the Coxeter element given by the product of all of
+    /// the generators of the group, in order. See [`CoxeterPlane`].
+    pub fn coxeter_plane(&self) -> Option<CoxeterPlane> {
+        let word: Vec<_> = (0..self.dim()).collect();
+        CoxeterPlane::new(self.product(&word)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+
+    /// Checks that the dot product of every two columns of the matrix is
+    /// `sign` times cos(π / m), for the corresponding entry m of `cox`.
+    fn test_normals(cox: &Cox<f64>, normals: &Matrix<f64>, sign: f64) {
+        for i in 0..cox.dim() {
+            for j in 0..i {
+                let dot = normals.column(i).dot(&normals.column(j));
+                let expected = sign * (f64::PI / cox[(i, j)]).fcos();
+                assert!(
+                    (dot - expected).abs() < f64::EPS,
+                    "normals {} and {} have dot product {}, expected {}",
+                    i,
+                    j,
+                    dot,
+                    expected
+                );
+            }
+        }
+    }
+
+    /// The normals have the dot products given by the Coxeter matrix, and the
+    /// inner normals have the opposite ones.
+    #[test]
+    fn normals() {
+        for cox in [Cox::a(4), Cox::b(4), Cox::d(4), Cox::h(4), Cox::e(6)] {
+            test_normals(&cox, &cox.normals().unwrap(), 1.0);
+            test_normals(&cox, &cox.inner_normals().unwrap(), -1.0);
+        }
+    }
+
+    /// Non-spherical groups have no normals.
+    #[test]
+    fn non_spherical() {
+        let cox = Cox::from_lin_diagram_iter([4.0, 4.0].iter().copied(), 3);
+        assert!(cox.normals().is_none());
+        assert!(cox.inner_normals().is_none());
+    }
+
+    /// The generator points are placed using [`Cox::normals`].
+    #[test]
+    fn generator() {
+        for (cd, expected) in [("x2x", [1.0, 1.0]), ("x3o", [1.0, 0.0])] {
+            let generator = Cd::parse(cd).unwrap().generator().unwrap();
+            assert!(
+                (&generator - Point::from_row_slice(&expected)).norm() < f64::EPS,
+                "{} has generator {:?}, expected {:?}",
+                cd,
+                generator.as_slice(),
+                expected
+            );
+        }
+    }
 }
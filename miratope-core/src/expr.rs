@@ -0,0 +1,1113 @@
+//! Contains a small expression language to build polytopes by composing
+//! operations, like `dual(prism(star(5, 2))) x cube` or
+//! `compound(hypercube(4), dual(hypercube(4)))`.
+//!
+//! # Syntax
+//!
+//! An expression is a sequence of terms joined by product operators, which are
+//! evaluated from left to right:
+//!
+//! * `x` or `×` for the [prism product](https://polytope.miraheze.org/wiki/Prism_product).
+//! * `+` for the [tegum product](https://polytope.miraheze.org/wiki/Tegum_product).
+//! * `∨` for the [pyramid product](https://polytope.miraheze.org/wiki/Pyramid_product).
+//! * `□` for the [comb product](https://polytope.miraheze.org/wiki/Comb_product).
+//!
+//! A term is either a parenthesized expression, a number, a function call
+//! like `antiprism(pentagon)`, or one of the following atoms:
+//!
+//! * A path to an OFF or GGB file in double quotes, like `"lib/cube.off"`.
+//! * A Coxeter diagram in square brackets, like `[x4o3o]`.
+//!
+//! Polychora can also be built from the parameters of Jenn3D, as in
+//! `jenn(5, 2, 2, 3, 2, 3)` or `jenn(5, 2, 2, 3, 2, 3, 1, 0, 0, 1)`. See
+//! [`Jenn`] for details.
+//!
+//! The parentheses after a function without arguments may be left out, so
+//! that `cube` and `cube()` are the same. Dimensions are given as usual, rather
+//! than as ranks, so that `hypercube(4)` is the tesseract.
+
+use std::{fmt::Display, iter::Peekable, str::CharIndices};
+
+use crate::{
+    abs::Ranked,
+    conc::{Concrete, ConcretePolytope},
+    cox::cd::{Cd, CdError},
+    file::{jenn::Jenn, FromFile},
+    geometry::Point,
+    Polytope,
+};
+
+/// The result of parsing or evaluating an expression.
+pub type ExprResult<T> = Result<T, ExprError>;
+
+/// Represents an error while parsing or evaluating an expression.
+#[derive(Clone, Debug)]
+pub enum ExprError {
+    /// An invalid symbol was found.
+    InvalidSymbol {
+        /// The position at which the parser found the error.
+        pos: usize,
+    },
+
+    /// The expression ended unexpectedly.
+    UnexpectedEnding {
+        /// The position at which the parser found the error.
+        pos: usize,
+    },
+
+    /// A token was found where something else was expected.
+    UnexpectedToken {
+        /// What the parser expected instead.
+        expected: &'static str,
+
+        /// The position at which the parser found the error.
+        pos: usize,
+    },
+
+    /// A string or a diagram was opened but not closed.
+    Unclosed {
+        /// The position at which the string or diagram was opened.
+        pos: usize,
+    },
+
+    /// A number couldn't be parsed.
+    ParseError {
+        /// The position at which the parser found the error.
+        pos: usize,
+    },
+
+    /// A function that doesn't exist was called.
+    UnknownFunction {
+        /// The name of the function.
+        name: String,
+
+        /// The position of the function call.
+        pos: usize,
+    },
+
+    /// A function was called with the wrong number of arguments.
+    ArgumentCount {
+        /// The name of the function.
+        name: String,
+
+        /// The number of arguments the function takes.
+        expected: &'static str,
+
+        /// The number of arguments it was called with.
+        found: usize,
+
+        /// The position of the function call.
+        pos: usize,
+    },
+
+    /// A number was found where a polytope was expected.
+    ExpectedPolytope {
+        /// The position of the number.
+        pos: usize,
+    },
+
+    /// A polytope was found where a number was expected.
+    ExpectedNumber {
+        /// The position of the polytope.
+        pos: usize,
+    },
+
+    /// A number was found where a non-negative integer, or one in a certain
+    /// range, was expected.
+    InvalidInteger {
+        /// The least value allowed.
+        min: usize,
+
+        /// The position of the number.
+        pos: usize,
+    },
+
+    /// A Coxeter diagram couldn't be parsed.
+    Cd {
+        /// The error within the diagram.
+        err: CdError,
+
+        /// The position of the diagram.
+        pos: usize,
+    },
+
+    /// A Coxeter diagram doesn't describe a spherical group.
+    NotSpherical {
+        /// The position of the diagram.
+        pos: usize,
+    },
+
+    /// A file couldn't be loaded.
+    File {
+        /// The path to the file.
+        path: String,
+
+        /// The error message.
+        message: String,
+
+        /// The position of the path.
+        pos: usize,
+    },
+
+    /// An operation couldn't be applied to a polytope.
+    OperationFailed {
+        /// The name of the operation.
+        name: String,
+
+        /// The reason it failed.
+        message: String,
+
+        /// The position of the function call.
+        pos: usize,
+    },
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // An invalid symbol was found.
+            Self::InvalidSymbol { pos } => write!(f, "invalid symbol found at position {}", pos),
+
+            // The expression ended unexpectedly.
+            Self::UnexpectedEnding { pos } => {
+                write!(f, "expression ended unexpectedly at position {}", pos)
+            }
+
+            // A token was found where something else was expected.
+            Self::UnexpectedToken { expected, pos } => {
+                write!(f, "expected {} at position {}", expected, pos)
+            }
+
+            // A string or a diagram was opened but not closed.
+            Self::Unclosed { pos } => write!(f, "unclosed delimiter at position {}", pos),
+
+            // A number couldn't be parsed.
+            Self::ParseError { pos } => write!(f, "parsing failed at position {}", pos),
+
+            // A function that doesn't exist was called.
+            Self::UnknownFunction { name, pos } => {
+                write!(f, "unknown function \"{}\" at position {}", name, pos)
+            }
+
+            // A function was called with the wrong number of arguments.
+            Self::ArgumentCount {
+                name,
+                expected,
+                found,
+                pos,
+            } => write!(
+                f,
+                "{} takes {} arguments but was given {} at position {}",
+                name, expected, found, pos
+            ),
+
+            // A number was found where a polytope was expected.
+            Self::ExpectedPolytope { pos } => {
+                write!(f, "expected a polytope at position {}", pos)
+            }
+
+            // A polytope was found where a number was expected.
+            Self::ExpectedNumber { pos } => write!(f, "expected a number at position {}", pos),
+
+            // A number was found where an integer was expected.
+            Self::InvalidInteger { min, pos } => write!(
+                f,
+                "expected an integer greater or equal to {} at position {}",
+                min, pos
+            ),
+
+            // A Coxeter diagram couldn't be parsed.
+            Self::Cd { err, pos } => {
+                write!(f, "invalid Coxeter diagram at position {}: {}", pos, err)
+            }
+
+            // A Coxeter diagram doesn't describe a spherical group.
+            Self::NotSpherical { pos } => write!(
+                f,
+                "the Coxeter group of the diagram at position {} is not spherical",
+                pos
+            ),
+
+            // A file couldn't be loaded.
+            Self::File { path, message, pos } => write!(
+                f,
+                "could not load \"{}\" at position {}: {}",
+                path, pos, message
+            ),
+
+            // An operation couldn't be applied to a polytope.
+            Self::OperationFailed { name, message, pos } => {
+                write!(f, "{} failed at position {}: {}", name, pos, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+/// A product of two polytopes, written as an infix operator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Product {
+    /// The prism product, written `x` or `×`.
+    Prism,
+
+    /// The tegum product, written `+`.
+    Tegum,
+
+    /// The pyramid product, written `∨`.
+    Pyramid,
+
+    /// The comb product, written `□`.
+    Comb,
+}
+
+impl Product {
+    /// Takes the product of two polytopes.
+    fn apply(self, p: &Concrete, q: &Concrete) -> Concrete {
+        match self {
+            Self::Prism => p.duoprism(q),
+            Self::Tegum => p.duotegum(q),
+            Self::Pyramid => p.duopyramid(q),
+            Self::Comb => p.duocomb(q),
+        }
+    }
+}
+
+impl Display for Product {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Prism => "×",
+            Self::Tegum => "+",
+            Self::Pyramid => "∨",
+            Self::Comb => "□",
+        })
+    }
+}
+
+/// A parsed expression. Every node stores the position in the source at which
+/// it starts, so that errors can point to it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// A number, used as an argument to a function.
+    Number {
+        /// The value of the number.
+        value: f64,
+
+        /// The position of the number.
+        pos: usize,
+    },
+
+    /// A path to a file to load the polytope from.
+    File {
+        /// The path to the file.
+        path: String,
+
+        /// The position of the path.
+        pos: usize,
+    },
+
+    /// A Coxeter diagram to build the polytope from.
+    Cd {
+        /// The diagram in inline ASCII notation.
+        diagram: String,
+
+        /// The position of the diagram.
+        pos: usize,
+    },
+
+    /// A function applied to some arguments.
+    Call {
+        /// The name of the function.
+        name: String,
+
+        /// The arguments of the function.
+        args: Vec<Expr>,
+
+        /// The position of the function name.
+        pos: usize,
+    },
+
+    /// A product of two polytopes.
+    Product {
+        /// The kind of product.
+        product: Product,
+
+        /// The left factor.
+        lhs: Box<Expr>,
+
+        /// The right factor.
+        rhs: Box<Expr>,
+
+        /// The position of the operator.
+        pos: usize,
+    },
+}
+
+/// A token in an expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A number.
+    Number(f64),
+
+    /// The name of a function, or the `x` operator.
+    Ident(String),
+
+    /// A file path, given in double quotes.
+    Str(String),
+
+    /// A Coxeter diagram, given in square brackets.
+    Cd(String),
+
+    /// A product operator other than `x`.
+    Product(Product),
+
+    /// A minus sign, which may only precede a number.
+    Minus,
+
+    /// An opening parenthesis.
+    Open,
+
+    /// A closing parenthesis.
+    Close,
+
+    /// A comma between arguments.
+    Comma,
+}
+
+/// Splits an expression into tokens, paired with their positions.
+fn tokenize(input: &str) -> ExprResult<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut iter: Peekable<CharIndices<'_>> = input.char_indices().peekable();
+
+    // Reads characters until a closing delimiter, which is skipped.
+    let delimited = |iter: &mut Peekable<CharIndices<'_>>, pos: usize, close: char| {
+        let start = pos + 1;
+        for (idx, c) in iter {
+            if c == close {
+                return Ok(input[start..idx].to_string());
+            }
+        }
+        Err(ExprError::Unclosed { pos })
+    };
+
+    while let Some((pos, c)) = iter.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '-' => Token::Minus,
+            '×' => Token::Product(Product::Prism),
+            '+' => Token::Product(Product::Tegum),
+            '∨' => Token::Product(Product::Pyramid),
+            '□' => Token::Product(Product::Comb),
+            '"' => Token::Str(delimited(&mut iter, pos, '"')?),
+            '[' => Token::Cd(delimited(&mut iter, pos, ']')?),
+
+            // Numbers.
+            '0'..='9' | '.' => {
+                let mut end = pos + c.len_utf8();
+                while let Some(&(idx, c)) = iter.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    end = idx + c.len_utf8();
+                    iter.next();
+                }
+
+                Token::Number(
+                    input[pos..end]
+                        .parse()
+                        .map_err(|_| ExprError::ParseError { pos })?,
+                )
+            }
+
+            // Identifiers.
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = pos + c.len_utf8();
+                while let Some(&(idx, c)) = iter.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = idx + c.len_utf8();
+                    iter.next();
+                }
+
+                Token::Ident(input[pos..end].to_string())
+            }
+
+            _ => return Err(ExprError::InvalidSymbol { pos }),
+        };
+
+        tokens.push((token, pos));
+    }
+
+    Ok(tokens)
+}
+
+/// Helper struct that parses an [`Expr`] from a list of tokens by recursive
+/// descent.
+struct Parser {
+    /// The tokens, paired with their positions, in reverse order.
+    tokens: Vec<(Token, usize)>,
+
+    /// The length of the expression, where an unexpected ending is reported.
+    len: usize,
+}
+
+impl Parser {
+    /// Peeks at the next token.
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.last()
+    }
+
+    /// Gets the next token, or returns an [`ExprError::UnexpectedEnding`].
+    fn next_or(&mut self) -> ExprResult<(Token, usize)> {
+        self.tokens
+            .pop()
+            .ok_or(ExprError::UnexpectedEnding { pos: self.len })
+    }
+
+    /// Gets the next token, and checks that it's the expected one.
+    fn expect(&mut self, token: Token, expected: &'static str) -> ExprResult<()> {
+        match self.next_or()? {
+            (next, _) if next == token => Ok(()),
+            (_, pos) => Err(ExprError::UnexpectedToken { expected, pos }),
+        }
+    }
+
+    /// Parses the product operator at the front, if any.
+    fn product(&mut self) -> Option<(Product, usize)> {
+        let product = match self.peek()? {
+            (Token::Product(product), pos) => (*product, *pos),
+            (Token::Ident(name), pos) if name == "x" => (Product::Prism, *pos),
+            _ => return None,
+        };
+
+        self.tokens.pop();
+        Some(product)
+    }
+
+    /// Parses a sequence of terms joined by product operators.
+    fn expr(&mut self) -> ExprResult<Expr> {
+        let mut lhs = self.term()?;
+
+        while let Some((product, pos)) = self.product() {
+            let rhs = self.term()?;
+            lhs = Expr::Product {
+                product,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                pos,
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    /// Parses a single term.
+    fn term(&mut self) -> ExprResult<Expr> {
+        match self.next_or()? {
+            (Token::Number(value), pos) => Ok(Expr::Number { value, pos }),
+            (Token::Minus, pos) => match self.next_or()? {
+                (Token::Number(value), _) => Ok(Expr::Number { value: -value, pos }),
+                (_, pos) => Err(ExprError::UnexpectedToken {
+                    expected: "a number",
+                    pos,
+                }),
+            },
+            (Token::Str(path), pos) => Ok(Expr::File { path, pos }),
+            (Token::Cd(diagram), pos) => Ok(Expr::Cd { diagram, pos }),
+            (Token::Open, _) => {
+                let expr = self.expr()?;
+                self.expect(Token::Close, "a closing parenthesis")?;
+                Ok(expr)
+            }
+            (Token::Ident(name), pos) => {
+                let mut args = Vec::new();
+
+                if let Some((Token::Open, _)) = self.peek() {
+                    self.tokens.pop();
+
+                    if let Some((Token::Close, _)) = self.peek() {
+                        self.tokens.pop();
+                    } else {
+                        loop {
+                            args.push(self.expr()?);
+                            match self.next_or()? {
+                                (Token::Comma, _) => {}
+                                (Token::Close, _) => break,
+                                (_, pos) => {
+                                    return Err(ExprError::UnexpectedToken {
+                                        expected: "a comma or a closing parenthesis",
+                                        pos,
+                                    })
+                                }
+                            }
+                        }
+                    }
+                }
+
+                Ok(Expr::Call { name, args, pos })
+            }
+            (_, pos) => Err(ExprError::UnexpectedToken {
+                expected: "a polytope or a number",
+                pos,
+            }),
+        }
+    }
+}
+
+/// The value of an expression.
+enum Value {
+    /// A number.
+    Number(f64),
+
+    /// A polytope.
+    Polytope(Concrete),
+}
+
+/// The evaluated arguments of a function call.
+struct Args<'a> {
+    /// The name of the function.
+    name: &'a str,
+
+    /// The position of the function call.
+    pos: usize,
+
+    /// The values of the arguments, paired with their positions.
+    values: Vec<(Value, usize)>,
+}
+
+impl<'a> Args<'a> {
+    /// Checks that the function was called with between `min` and `max`
+    /// arguments. The description of the expected count is used in the error.
+    fn count(&self, min: usize, max: usize, expected: &'static str) -> ExprResult<()> {
+        let found = self.values.len();
+        if (min..=max).contains(&found) {
+            Ok(())
+        } else {
+            Err(ExprError::ArgumentCount {
+                name: self.name.to_string(),
+                expected,
+                found,
+                pos: self.pos,
+            })
+        }
+    }
+
+    /// Returns the polytope in the given argument.
+    fn polytope(&self, idx: usize) -> ExprResult<&Concrete> {
+        match &self.values[idx] {
+            (Value::Polytope(poly), _) => Ok(poly),
+            (Value::Number(_), pos) => Err(ExprError::ExpectedPolytope { pos: *pos }),
+        }
+    }
+
+    /// Returns the polytopes in all of the arguments.
+    fn polytopes(&self) -> ExprResult<Vec<&Concrete>> {
+        (0..self.values.len())
+            .map(|idx| self.polytope(idx))
+            .collect()
+    }
+
+    /// Returns the number in the given argument.
+    fn number(&self, idx: usize) -> ExprResult<f64> {
+        match self.values[idx] {
+            (Value::Number(value), _) => Ok(value),
+            (Value::Polytope(_), pos) => Err(ExprError::ExpectedNumber { pos }),
+        }
+    }
+
+    /// Returns the number in the given argument if it was given, or a default
+    /// value otherwise.
+    fn number_or(&self, idx: usize, default: f64) -> ExprResult<f64> {
+        if idx < self.values.len() {
+            self.number(idx)
+        } else {
+            Ok(default)
+        }
+    }
+
+    /// Returns the integer in the given argument, which must be at least
+    /// `min`.
+    fn integer(&self, idx: usize, min: usize) -> ExprResult<usize> {
+        let value = self.number(idx)?;
+        if value.fract() == 0.0 && value >= min as f64 {
+            Ok(value as usize)
+        } else {
+            Err(ExprError::InvalidInteger {
+                min,
+                pos: self.values[idx].1,
+            })
+        }
+    }
+
+    /// Returns the non-negative integer in the given argument if it was given,
+    /// or a default value otherwise.
+    fn integer_or(&self, idx: usize, default: usize) -> ExprResult<usize> {
+        if idx < self.values.len() {
+            self.integer(idx, 0)
+        } else {
+            Ok(default)
+        }
+    }
+
+    /// Returns an [`ExprError::OperationFailed`] for this function.
+    fn failed<T: Display>(&self, message: T) -> ExprError {
+        ExprError::OperationFailed {
+            name: self.name.to_string(),
+            message: message.to_string(),
+            pos: self.pos,
+        }
+    }
+
+    /// Evaluates the function with these arguments.
+    fn call(self) -> ExprResult<Concrete> {
+        /// Checks the number of arguments of a function that takes none.
+        macro_rules! constant {
+            ($poly:expr) => {{
+                self.count(0, 0, "no")?;
+                $poly
+            }};
+        }
+
+        Ok(match self.name {
+            // Basic polytopes.
+            "nullitope" => constant!(Concrete::nullitope()),
+            "point" => constant!(Concrete::point()),
+            "dyad" => constant!(Concrete::dyad()),
+            "triangle" => constant!(Concrete::star_polygon_with_edge(3, 1, 1.0)),
+            "square" => constant!(Concrete::star_polygon_with_edge(4, 1, 1.0)),
+            "pentagon" => constant!(Concrete::star_polygon_with_edge(5, 1, 1.0)),
+            "hexagon" => constant!(Concrete::star_polygon_with_edge(6, 1, 1.0)),
+            "tetrahedron" => constant!(Concrete::tetrahedron()),
+            "cube" => constant!(Concrete::cube()),
+            "octahedron" => constant!(Concrete::octahedron()),
+
+            // Polygons, with unit edge length.
+            "polygon" => {
+                self.count(1, 1, "1")?;
+                Concrete::star_polygon_with_edge(self.integer(0, 2)?, 1, 1.0)
+            }
+            "star" => {
+                self.count(2, 2, "2")?;
+                let (n, d) = (self.integer(0, 2)?, self.integer(1, 1)?);
+                if d % n == 0 {
+                    return Err(self.failed(format!("{}/{} is not a valid star polygon", n, d)));
+                }
+                Concrete::star_polygon_with_edge(n, d, 1.0)
+            }
+
+            // Families of polytopes in any dimension.
+            "simplex" => {
+                self.count(1, 1, "1")?;
+                Concrete::simplex(self.integer(0, 0)? + 1)
+            }
+            "hypercube" => {
+                self.count(1, 1, "1")?;
+                Concrete::hypercube(self.integer(0, 0)? + 1)
+            }
+            "orthoplex" => {
+                self.count(1, 1, "1")?;
+                Concrete::orthoplex(self.integer(0, 0)? + 1)
+            }
+
+            // Wythoffian polychora from the Coxeter matrix and the weights
+            // that Jenn3D takes.
+            "jenn" => {
+                if self.values.len() != 10 {
+                    self.count(6, 6, "6 or 10")?;
+                }
+
+                let mut matrix = [2; 6];
+                for (idx, entry) in matrix.iter_mut().enumerate() {
+                    *entry = self.integer(idx, 2)? as u32;
+                }
+
+                let mut jenn = Jenn::new(matrix);
+                if self.values.len() == 10 {
+                    for (idx, weight) in jenn.weights.iter_mut().enumerate() {
+                        *weight = self.number(idx + 6)?;
+                    }
+                }
+
+                let cd = jenn.cd().map_err(|err| self.failed(err))?;
+                Concrete::from_cd(&cd)
+                    .ok_or_else(|| self.failed("the Coxeter group is not spherical"))?
+            }
+
+            // Operations on a single polytope.
+            "dual" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?
+                    .try_dual()
+                    .map_err(|err| self.failed(err))?
+            }
+            "prism" => {
+                self.count(1, 2, "1 or 2")?;
+                self.polytope(0)?.prism_with(self.number_or(1, 1.0)?)
+            }
+            "tegum" => {
+                self.count(1, 2, "1 or 2")?;
+                let height = self.number_or(1, 1.0)?;
+                self.polytope(0)?.duotegum(&Concrete::dyad_with(height))
+            }
+            "pyramid" => {
+                self.count(1, 2, "1 or 2")?;
+                let poly = self.polytope(0)?;
+                poly.duopyramid_with(
+                    &Concrete::point(),
+                    &Point::zeros(poly.dim_or()),
+                    &Point::zeros(0),
+                    self.number_or(1, 1.0)?,
+                )
+            }
+            "antiprism" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?
+                    .try_antiprism()
+                    .map_err(|err| self.failed(err))?
+            }
+            "petrial" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?
+                    .petrial()
+                    .ok_or_else(|| self.failed("the Petrial is not a valid polytope"))?
+            }
+            "ditope" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?.ditope()
+            }
+            "hosotope" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?.hosotope()
+            }
+            "omnitruncate" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?.omnitruncate()
+            }
+            "hull" => {
+                self.count(1, 1, "1")?;
+                self.polytope(0)?.hull()
+            }
+            "scale" => {
+                self.count(2, 2, "2")?;
+                let mut poly = self.polytope(0)?.clone();
+                poly.scale(self.number(1)?);
+                poly
+            }
+            "recenter" => {
+                self.count(1, 1, "1")?;
+                let mut poly = self.polytope(0)?.clone();
+                poly.recenter();
+                poly
+            }
+
+            // Elements of a polytope.
+            "facet" => {
+                self.count(1, 2, "1 or 2")?;
+                let idx = self.integer_or(1, 0)?;
+                let mut facet = self
+                    .polytope(0)?
+                    .facet(idx)
+                    .ok_or_else(|| self.failed(format!("there is no facet {}", idx)))?;
+                facet.flatten();
+                facet
+            }
+            "verf" => {
+                self.count(1, 2, "1 or 2")?;
+                let idx = self.integer_or(1, 0)?;
+                let mut verf = self
+                    .polytope(0)?
+                    .verf(idx)
+                    .map_err(|err| self.failed(err))?
+                    .ok_or_else(|| self.failed(format!("there is no vertex {}", idx)))?;
+                verf.flatten();
+                verf
+            }
+
+            // Operations on any number of polytopes.
+            "compound" => {
+                self.count(1, usize::MAX, "at least 1")?;
+                let polys = self.polytopes()?;
+                let first = polys[0];
+                if polys
+                    .iter()
+                    .any(|poly| poly.rank() != first.rank() || poly.dim() != first.dim())
+                {
+                    return Err(self.failed("the components have different ranks or dimensions"));
+                }
+                Concrete::compound(polys.into_iter().cloned())
+            }
+            "duoprism" => {
+                self.count(1, usize::MAX, "at least 1")?;
+                Concrete::multiprism(self.polytopes()?)
+            }
+            "duotegum" => {
+                self.count(1, usize::MAX, "at least 1")?;
+                Concrete::multitegum(self.polytopes()?)
+            }
+            "duopyramid" => {
+                self.count(1, usize::MAX, "at least 1")?;
+                Concrete::multipyramid(self.polytopes()?)
+            }
+            "duocomb" => {
+                self.count(1, usize::MAX, "at least 1")?;
+                Concrete::multicomb(self.polytopes()?)
+            }
+            "star_product" => {
+                self.count(2, 2, "2")?;
+                self.polytope(0)?.star_product(self.polytope(1)?)
+            }
+
+            _ => {
+                return Err(ExprError::UnknownFunction {
+                    name: self.name.to_string(),
+                    pos: self.pos,
+                })
+            }
+        })
+    }
+}
+
+impl Expr {
+    /// Parses an expression.
+    pub fn parse(input: &str) -> ExprResult<Self> {
+        let mut tokens = tokenize(input)?;
+        tokens.reverse();
+
+        let mut parser = Parser {
+            tokens,
+            len: input.len(),
+        };
+        let expr = parser.expr()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(&(_, pos)) => Err(ExprError::UnexpectedToken {
+                expected: "a product operator",
+                pos,
+            }),
+        }
+    }
+
+    /// Returns the position in the source at which the expression starts.
+    pub fn pos(&self) -> usize {
+        match self {
+            Self::Number { pos, .. }
+            | Self::File { pos, .. }
+            | Self::Cd { pos, .. }
+            | Self::Call { pos, .. } => *pos,
+            Self::Product { lhs, .. } => lhs.pos(),
+        }
+    }
+
+    /// Evaluates an expression into a number or a polytope.
+    fn value(&self) -> ExprResult<Value> {
+        Ok(Value::Polytope(match self {
+            Self::Number { value, .. } => return Ok(Value::Number(*value)),
+
+            Self::File { path, pos } => {
+                Concrete::from_path(path).map_err(|err| ExprError::File {
+                    path: path.clone(),
+                    message: err.to_string(),
+                    pos: *pos,
+                })?
+            }
+
+            Self::Cd { diagram, pos } => {
+                let cd = Cd::parse(diagram).map_err(|err| ExprError::Cd { err, pos: *pos })?;
+                Concrete::from_cd(&cd).ok_or(ExprError::NotSpherical { pos: *pos })?
+            }
+
+            Self::Call { name, args, pos } => Args {
+                name,
+                pos: *pos,
+                values: args
+                    .iter()
+                    .map(|arg| Ok((arg.value()?, arg.pos())))
+                    .collect::<ExprResult<_>>()?,
+            }
+            .call()?,
+
+            Self::Product {
+                product, lhs, rhs, ..
+            } => product.apply(&lhs.eval()?, &rhs.eval()?),
+        }))
+    }
+
+    /// Evaluates an expression into a polytope. Relative file paths are read
+    /// from the current directory.
+    pub fn eval(&self) -> ExprResult<Concrete> {
+        match self.value()? {
+            Value::Polytope(poly) => Ok(poly),
+            Value::Number(_) => Err(ExprError::ExpectedPolytope { pos: self.pos() }),
+        }
+    }
+}
+
+impl Concrete {
+    /// Parses and evaluates an expression into a polytope. See the
+    /// [module-level documentation](crate::expr) for the syntax.
+    pub fn from_expr(input: &str) -> ExprResult<Self> {
+        Expr::parse(input)?.eval()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    /// Evaluates an expression, and checks its element counts.
+    fn test_expr<I: IntoIterator<Item = usize>>(input: &str, element_counts: I) -> Concrete {
+        let poly = Concrete::from_expr(input).unwrap();
+        test(&poly, element_counts);
+        poly
+    }
+
+    /// Checks that an expression fails to parse or evaluate.
+    fn test_err(input: &str) -> ExprError {
+        Concrete::from_expr(input).unwrap_err()
+    }
+
+    /// Checks some basic constructions.
+    #[test]
+    fn basic() {
+        test_expr("point", [1, 1, 1]);
+        test_expr("polygon(5)", [1, 5, 5, 1]);
+        test_expr("star(5, 2)", [1, 5, 5, 1]);
+        test_expr("cube()", [1, 8, 12, 6, 1]);
+        test_expr("hypercube(4)", [1, 16, 32, 24, 8, 1]);
+        test_expr("simplex(3)", [1, 4, 6, 4, 1]);
+        test_expr("jenn(4, 2, 2, 3, 2, 3)", [1, 16, 32, 24, 8, 1]);
+        test_expr("jenn(3, 2, 2, 3, 2, 3, 1, 0, 0, 1)", [1, 20, 60, 70, 30, 1]);
+    }
+
+    /// Checks that operations compose.
+    #[test]
+    fn operations() {
+        test_expr("dual(prism(star(5, 2)))", [1, 7, 15, 10, 1]);
+        test_expr(
+            "dual(prism(star(5,2))) x cube",
+            [1, 56, 204, 302, 225, 87, 16, 1],
+        );
+        test_expr("pyramid(square, 2)", [1, 5, 8, 5, 1]);
+        test_expr("antiprism(triangle)", [1, 6, 12, 8, 1]);
+        test_expr(
+            "compound(hypercube(4), dual(hypercube(4)))",
+            [1, 24, 56, 56, 24, 1],
+        );
+    }
+
+    /// Checks the product operators and their associativity.
+    #[test]
+    fn products() {
+        test_expr("dyad x dyad × dyad", [1, 8, 12, 6, 1]);
+        test_expr("dyad + dyad + dyad", [1, 6, 12, 8, 1]);
+        test_expr("point ∨ point ∨ point ∨ point", [1, 4, 6, 4, 1]);
+        test_expr("triangle □ square", [1, 12, 24, 12, 1]);
+        test_expr("(dyad + dyad) x dyad", [1, 8, 12, 6, 1]);
+        test_expr(
+            "duoprism(triangle, square, pentagon)",
+            [1, 60, 180, 227, 154, 59, 12, 1],
+        );
+    }
+
+    /// Checks that Coxeter diagrams are built.
+    #[test]
+    fn cd() {
+        test_expr("[x4o3o]", [1, 8, 12, 6, 1]);
+        test_expr("[x3o] x [x3o]", [1, 9, 18, 15, 6, 1]);
+
+        assert!(matches!(
+            test_err("[x4o3x]("),
+            ExprError::UnexpectedToken { pos: 7, .. }
+        ));
+        assert!(matches!(test_err("[x4o3o"), ExprError::Unclosed { pos: 0 }));
+        assert!(matches!(test_err("[x4o3]"), ExprError::Cd { pos: 0, .. }));
+        assert!(matches!(
+            test_err("[x7o3o]"),
+            ExprError::NotSpherical { pos: 0 }
+        ));
+    }
+
+    /// Checks that files are loaded.
+    #[test]
+    fn file() {
+        let path = std::env::temp_dir().join("miratope-expr-cube.off");
+        Concrete::cube().to_path(&path, Default::default()).unwrap();
+        test_expr(
+            &format!("prism(\"{}\")", path.display()),
+            [1, 16, 32, 24, 8, 1],
+        );
+        std::fs::remove_file(path).unwrap();
+
+        assert!(matches!(
+            test_err("\"does not exist.off\""),
+            ExprError::File { pos: 0, .. }
+        ));
+    }
+
+    /// Checks that errors point to the right place.
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            test_err("prism(cube"),
+            ExprError::UnexpectedEnding { pos: 10 }
+        ));
+        assert!(matches!(
+            test_err("cube x"),
+            ExprError::UnexpectedEnding { pos: 6 }
+        ));
+        assert!(matches!(
+            test_err("cube cube"),
+            ExprError::UnexpectedToken { pos: 5, .. }
+        ));
+        assert!(matches!(
+            test_err("cube $"),
+            ExprError::InvalidSymbol { pos: 5 }
+        ));
+        assert!(matches!(
+            test_err("frobnicate(cube)"),
+            ExprError::UnknownFunction { pos: 0, .. }
+        ));
+        assert!(matches!(
+            test_err("prism(1)"),
+            ExprError::ExpectedPolytope { pos: 6 }
+        ));
+        assert!(matches!(
+            test_err("3"),
+            ExprError::ExpectedPolytope { pos: 0 }
+        ));
+        assert!(matches!(
+            test_err("prism(cube, cube)"),
+            ExprError::ExpectedNumber { pos: 12 }
+        ));
+        assert!(matches!(
+            test_err("polygon(2.5)"),
+            ExprError::InvalidInteger { min: 2, pos: 8 }
+        ));
+        assert!(matches!(
+            test_err("dual(cube, cube)"),
+            ExprError::ArgumentCount { found: 2, .. }
+        ));
+        assert!(matches!(
+            test_err("verf(cube, 8)"),
+            ExprError::OperationFailed { pos: 0, .. }
+        ));
+        assert!(matches!(
+            test_err("star(5, 5)"),
+            ExprError::OperationFailed { pos: 0, .. }
+        ));
+        assert!(matches!(
+            test_err("compound(cube, square)"),
+            ExprError::OperationFailed { pos: 0, .. }
+        ));
+        assert!(matches!(
+            test_err("compound(cube, ditope(square))"),
+            ExprError::OperationFailed { pos: 0, .. }
+        ));
+    }
+}
@@ -1,156 +1,132 @@
 //! Contains the code that opens a GGB file and parses it into a polytope.
+//! Also the code that writes a GGB file of a polytope.
+//!
+//! A GGB file is a ZIP file containing a `geogebra.xml` file, which lists the
+//! elements in a GeoGebra construction together with the commands that built
+//! them. We read the coordinates of all points, and build the polytope out of
+//! the `Segment`, `Polygon` and `Polyhedron` commands. The faces of the 3D
+//! solids built by the `Cube`, `Tetrahedron`, `Octahedron`, `Dodecahedron`,
+//! `Icosahedron`, `Prism` and `Pyramid` commands are recovered from the convex
+//! hulls of their vertices. Commands that only build points are ignored, any
+//! other command is reported as unsupported.
 
-// This code is unfinished.
-#![allow(dead_code)]
-#![allow(clippy::collapsible_match)]
-
-use std::io::Result as IoResult;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    io::{Cursor, Error as IoError, Write},
+    path::Path,
+};
 
-use crate::{conc::Concrete, geometry::Point};
+use crate::{
+    abs::{AbstractBuilder, AbstractError, Ranked, SubelementList, Subelements},
+    conc::{cycle::CycleList, Concrete, ConcretePolytope},
+    geometry::{Point, PointOrd},
+};
 
 use nalgebra::dvector;
+use vec_like::VecLike;
 use xml::{
     attribute::OwnedAttribute,
-    reader::{EventReader, XmlEvent},
+    reader::{self, EventReader, XmlEvent},
+    writer::{self, EmitterConfig, EventWriter},
 };
-use zip::result::ZipError;
-
-type Events<'a> = xml::reader::Events<&'a [u8]>;
-
-/// A wrapper around an iterator over events in an XML file.
-pub struct XmlReader<'a>(Events<'a>);
+use zip::{result::ZipError, write::FileOptions, ZipWriter};
 
-impl<'a> Iterator for XmlReader<'a> {
-    type Item = xml::reader::Result<XmlEvent>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
-    }
-}
-
-impl<'a> AsRef<Events<'a>> for XmlReader<'a> {
-    fn as_ref(&self) -> &Events<'a> {
-        &self.0
-    }
-}
-
-impl<'a> AsMut<Events<'a>> for XmlReader<'a> {
-    fn as_mut(&mut self) -> &mut Events<'a> {
-        &mut self.0
-    }
-}
-
-impl<'a> XmlReader<'a> {
-    /// Initializes a new XML reader from a source XML string.
-    pub fn new(xml: &'a str) -> Self {
-        Self(EventReader::from_str(xml).into_iter())
-    }
-
-    /// Reads an XML file until an XML element with a given name is found.
-    /// Returns its attributes.
-    fn read_until(&mut self, search: &str) -> GgbResult<Vec<OwnedAttribute>> {
-        for xml_result in self.as_mut() {
-            match xml_result {
-                // The next XML event to process:
-                Ok(xml_event) => {
-                    // If we have an element and it has the correct name, we return
-                    // this XML event.
-                    if let XmlEvent::StartElement {
-                        ref name,
-                        attributes,
-                        ..
-                    } = xml_event
-                    {
-                        if name.local_name == search {
-                            return Ok(attributes);
-                        }
-                    } else {
-                        todo!()
-                    }
-                }
-                // Something went wrong while fetching the next XML event.
-                Err(_) => return Err(GgbError::InvalidXml),
-            }
-        }
-
-        // We didn't find the element we were looking for.
-        Err(GgbError::MissingElement)
-    }
-
-    /// Reads a point from the GGB file, assuming that we're currently in an XML
-    /// label of the form
-    /// ```xml
-    /// <element type="point3d" label="A">
-    /// ```
-    fn read_point(&mut self, attributes: &[OwnedAttribute]) -> GgbResult<Vertex<f64>> {
-        let label = attribute(attributes, "label").unwrap_or_default();
-        let coord_attributes = self.read_until("coords")?;
-
-        /// Reads any of the coordinates of a point, saves it in a variable with
-        /// the same name.
-        macro_rules! read_coord {
-            ($x:ident) => {
-                let $x: f64;
-
-                if let Some(c) = attribute(&coord_attributes, stringify!($x)) {
-                    if let Ok(c) = c.parse() {
-                        $x = c;
-                    } else {
-                        return Err(GgbError::ParseError);
-                    };
-                } else {
-                    return Err(GgbError::MissingAttribute);
-                }
-            };
-        }
-
-        read_coord!(x);
-        read_coord!(y);
-        read_coord!(z);
-        read_coord!(w);
-
-        Ok(Vertex {
-            coords: dvector![x / w, y / w, z / w],
-            label: label.to_string(),
-        })
-    }
-
-    fn read_edge(&self) -> IoResult<Edge> {
-        todo!()
-    }
-}
+/// The commands that build a convex 3D solid, whose vertices are among their
+/// inputs and outputs.
+const SOLID_COMMANDS: [&str; 7] = [
+    "Cube",
+    "Tetrahedron",
+    "Octahedron",
+    "Dodecahedron",
+    "Icosahedron",
+    "Prism",
+    "Pyramid",
+];
 
 /// Possible errors while reading a GGB file.
 #[derive(Debug)]
 pub enum GgbError {
     /// An attribute of an XML tag wasn't found.
-    MissingAttribute,
+    MissingAttribute {
+        /// The name of the XML tag.
+        tag: String,
 
-    /// We couldn't find the next <element> tag.
-    MissingElement,
+        /// The name of the missing attribute.
+        attribute: &'static str,
+    },
 
     /// An error occured while reading the ZIP file.
     ZipError(ZipError),
 
     /// The XML file is not valid.
-    InvalidXml,
+    InvalidXml(reader::Error),
 
     /// The GGB file is not valid.
     InvalidGgb,
 
     /// Some number could not be parsed.
-    ParseError,
+    ParseError {
+        /// The text that couldn't be parsed.
+        value: String,
+    },
+
+    /// A point has a zero homogeneous coordinate.
+    PointAtInfinity {
+        /// The label of the point.
+        label: String,
+    },
+
+    /// A command that doesn't build points, segments, polygons or polyhedra
+    /// was found.
+    UnsupportedCommand {
+        /// The name of the command.
+        name: String,
+    },
+
+    /// A command was called with inputs other than points, like
+    /// `Polygon(A, B, 5)`.
+    UnsupportedInput {
+        /// The name of the command.
+        command: String,
+
+        /// The unsupported input.
+        input: String,
+    },
+
+    /// The points of a polyhedron don't span 3D space.
+    FlatPolyhedron {
+        /// The name of the command that built the polyhedron.
+        command: String,
+    },
+
+    /// The file has no segments, polygons or polyhedra.
+    Empty,
+
+    /// The segments, polygons and polyhedra don't form a valid polytope.
+    InvalidPolytope(AbstractError),
 }
 
-impl std::fmt::Display for GgbError {
+impl Display for GgbError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::MissingAttribute => write!(f, "missing XML attribute"),
-            Self::MissingElement => write!(f, "missing XML element"),
-            Self::InvalidXml => write!(f, "invalid XML"),
-            Self::InvalidGgb => write!(f, "invalid GGB"),
+            Self::MissingAttribute { tag, attribute } => {
+                write!(f, "missing attribute \"{}\" in <{}>", attribute, tag)
+            }
             Self::ZipError(err) => write!(f, "ZIP error: {}", err),
-            Self::ParseError => write!(f, "parse error"),
+            Self::InvalidXml(err) => write!(f, "invalid XML: {}", err),
+            Self::InvalidGgb => write!(f, "invalid GGB"),
+            Self::ParseError { value } => write!(f, "could not parse \"{}\"", value),
+            Self::PointAtInfinity { label } => write!(f, "point {} is at infinity", label),
+            Self::UnsupportedCommand { name } => write!(f, "unsupported command {}", name),
+            Self::UnsupportedInput { command, input } => {
+                write!(f, "unsupported input \"{}\" in command {}", input, command)
+            }
+            Self::FlatPolyhedron { command } => {
+                write!(f, "the points of command {} don't span 3D space", command)
+            }
+            Self::Empty => write!(f, "no segments, polygons or polyhedra found"),
+            Self::InvalidPolytope(err) => write!(f, "invalid polytope: {}", err),
         }
     }
 }
@@ -166,10 +142,6 @@ pub type GgbResult<T> = Result<T, GgbError>;
 
 impl std::error::Error for GgbError {}
 
-enum Element {
-    Point3D { label: String },
-}
-
 /// Returns the value of an attribute with a given name in an XML element.
 ///
 /// This method does a simple linear search over all attributes. This isn't
@@ -185,6 +157,34 @@ fn attribute<'a>(attributes: &'a [OwnedAttribute], idx: &str) -> Option<&'a str>
     None
 }
 
+/// Returns the value of an attribute with a given name in an XML element, or
+/// a [`GgbError::MissingAttribute`] if it doesn't exist.
+fn required_attribute<'a>(
+    attributes: &'a [OwnedAttribute],
+    tag: &str,
+    idx: &'static str,
+) -> GgbResult<&'a str> {
+    attribute(attributes, idx).ok_or_else(|| GgbError::MissingAttribute {
+        tag: tag.to_string(),
+        attribute: idx,
+    })
+}
+
+/// Returns the arguments of the `<input>` or `<output>` of a command, which
+/// are stored in the attributes `a0`, `a1`, and so on.
+fn arguments(attributes: &[OwnedAttribute]) -> Vec<String> {
+    let mut arguments: Vec<_> = attributes
+        .iter()
+        .filter_map(|att| {
+            let idx: usize = att.name.local_name.strip_prefix('a')?.parse().ok()?;
+            Some((idx, att.value.clone()))
+        })
+        .collect();
+
+    arguments.sort_unstable_by_key(|(idx, _)| *idx);
+    arguments.into_iter().map(|(_, value)| value).collect()
+}
+
 /// A vertex in a GGB file.
 #[derive(Debug)]
 struct Vertex<T> {
@@ -195,63 +195,909 @@ struct Vertex<T> {
     label: String,
 }
 
-struct Edge {
-    label: String,
+impl Vertex<f64> {
+    /// Reads a point from the attributes of its `<coords>` tag. These are
+    /// homogeneous coordinates `x`, `y`, `z` for 2D points, and `x`, `y`, `z`,
+    /// `w` for 3D points.
+    fn new(label: &str, attributes: &[OwnedAttribute], is_3d: bool) -> GgbResult<Self> {
+        let coord = |idx| {
+            let value = required_attribute(attributes, "coords", idx)?;
+            value.parse::<f64>().map_err(|_| GgbError::ParseError {
+                value: value.to_string(),
+            })
+        };
+
+        let (x, y) = (coord("x")?, coord("y")?);
+        let (z, w) = if is_3d {
+            (coord("z")?, coord("w")?)
+        } else {
+            (0.0, coord("z")?)
+        };
+
+        if w == 0.0 {
+            return Err(GgbError::PointAtInfinity {
+                label: label.to_string(),
+            });
+        }
+
+        Ok(Self {
+            coords: dvector![x / w, y / w, z / w],
+            label: label.to_string(),
+        })
+    }
 }
 
-struct Face {
-    vertices: Vec<String>,
-    edges: Vec<String>,
+/// A command in a GGB file.
+#[derive(Debug)]
+struct Command {
+    /// The name of the command, like `Polygon`.
+    name: String,
+
+    /// The labels of the inputs of the command.
+    inputs: Vec<String>,
+
+    /// The labels of the outputs of the command.
+    outputs: Vec<String>,
 }
 
-fn read_face() -> Face {
-    todo!()
+/// The parts of the construction in a GGB file that we care about.
+#[derive(Default)]
+struct Construction {
+    /// The points in the construction, in order.
+    points: Vec<Vertex<f64>>,
+
+    /// Whether any of the points is a 3D point.
+    is_3d: bool,
+
+    /// The type of every labeled element in the construction.
+    types: HashMap<String, String>,
+
+    /// The commands in the construction, in order.
+    commands: Vec<Command>,
 }
 
-/// Parses the `geogebra.xml` file to produce a polytope.
-pub(super) fn parse_xml(xml: &str) -> GgbResult<Concrete> {
-    let mut vertices: Vec<Vertex<f64>> = Vec::new();
-    let mut edges = Vec::new();
-    let mut xml = XmlReader::new(xml);
-
-    loop {
-        match xml.as_mut().next() {
-            // If the document isn't yet over:
-            Some(xml_result) => match xml_result {
-                // The next XML event to process:
-                Ok(xml_event) => {
-                    if let XmlEvent::StartElement {
-                        name, attributes, ..
-                    } = xml_event
-                    {
-                        let name = name.local_name;
-
-                        if name == "element" {
-                            if let Some(el_type) = attribute(&attributes, "type") {
-                                // We found a point.
-                                if el_type == "point3d" {
-                                    vertices.push(xml.read_point(&attributes)?);
-                                }
-                                // We found an edge.
-                                else if el_type == "segment3d" {
-                                    if let Ok(edge) = xml.read_edge() {
-                                        edges.push(edge);
-                                    }
-                                }
+impl Construction {
+    /// Reads the points, the element types and the commands from the
+    /// `geogebra.xml` file.
+    fn new(xml: &str) -> GgbResult<Self> {
+        let mut construction = Self::default();
+
+        // The type and label of the element we're reading, if any.
+        let mut element: Option<(String, String)> = None;
+
+        // The command we're reading, if any.
+        let mut command: Option<Command> = None;
+
+        for event in EventReader::from_str(xml) {
+            match event.map_err(GgbError::InvalidXml)? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => match name.local_name.as_str() {
+                    "element" => {
+                        let el_type = required_attribute(&attributes, "element", "type")?;
+                        let label = required_attribute(&attributes, "element", "label")?;
+
+                        construction
+                            .types
+                            .insert(label.to_string(), el_type.to_string());
+                        element = Some((el_type.to_string(), label.to_string()));
+                    }
+
+                    // The coordinates of an element. We only care about those
+                    // of points.
+                    "coords" => {
+                        if let Some((el_type, label)) = &element {
+                            if el_type == "point" || el_type == "point3d" {
+                                let is_3d = el_type == "point3d";
+                                construction.is_3d |= is_3d;
+                                construction
+                                    .points
+                                    .push(Vertex::new(label, &attributes, is_3d)?);
                             }
                         }
+                    }
 
-                        /*  "element" => read_edge(&mut xml),
-                        "command" => read_face(&mut xml), */
+                    "command" => {
+                        command = Some(Command {
+                            name: required_attribute(&attributes, "command", "name")?.to_string(),
+                            inputs: Vec::new(),
+                            outputs: Vec::new(),
+                        })
                     }
-                }
+                    "input" => {
+                        if let Some(command) = &mut command {
+                            command.inputs = arguments(&attributes);
+                        }
+                    }
+                    "output" => {
+                        if let Some(command) = &mut command {
+                            command.outputs = arguments(&attributes);
+                        }
+                    }
+
+                    _ => {}
+                },
+
+                XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                    "element" => element = None,
+                    "command" => construction.commands.extend(command.take()),
+                    _ => {}
+                },
+
+                _ => {}
+            }
+        }
+
+        Ok(construction)
+    }
+
+    /// Returns whether the element with a given label is a point.
+    fn is_point(&self, label: &str) -> bool {
+        matches!(
+            self.types.get(label).map(String::as_str),
+            Some("point") | Some("point3d")
+        )
+    }
+}
+
+/// Builds a polytope out of the segments, polygons and polyhedra in a
+/// [`Construction`].
+struct GgbBuilder<'a> {
+    /// The construction we're reading.
+    construction: &'a Construction,
+
+    /// The index of every point in the construction, by label.
+    point_indices: HashMap<&'a str, usize>,
+
+    /// The vertices of every polygon built by a `Polygon` command, by label.
+    polygons: HashMap<&'a str, Vec<usize>>,
+
+    /// The endpoints of the edges found so far, as indices of points.
+    edges: Vec<[usize; 2]>,
+
+    /// The index of every edge, by its sorted endpoints.
+    edge_indices: HashMap<[usize; 2], usize>,
+
+    /// The edges of the faces found so far.
+    faces: Vec<Vec<usize>>,
+
+    /// The index of every face, by its sorted edges.
+    face_indices: HashMap<Vec<usize>, usize>,
+
+    /// The edges built by `Segment` commands.
+    segments: Vec<usize>,
+}
+
+impl<'a> GgbBuilder<'a> {
+    /// Initializes a new builder from a construction.
+    fn new(construction: &'a Construction) -> Self {
+        Self {
+            construction,
+            point_indices: construction
+                .points
+                .iter()
+                .enumerate()
+                .map(|(idx, v)| (v.label.as_str(), idx))
+                .collect(),
+            polygons: HashMap::new(),
+            edges: Vec::new(),
+            edge_indices: HashMap::new(),
+            faces: Vec::new(),
+            face_indices: HashMap::new(),
+            segments: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the point with a given label, or a
+    /// [`GgbError::UnsupportedInput`] if there's no such point.
+    fn point(&self, command: &Command, label: &str) -> GgbResult<usize> {
+        self.point_indices
+            .get(label)
+            .copied()
+            .ok_or_else(|| GgbError::UnsupportedInput {
+                command: command.name.clone(),
+                input: label.to_string(),
+            })
+    }
+
+    /// Adds an edge between two points if it doesn't exist yet, and returns
+    /// its index.
+    fn add_edge(&mut self, v0: usize, v1: usize) -> usize {
+        let key = if v0 < v1 { [v0, v1] } else { [v1, v0] };
+        let edges = &mut self.edges;
+
+        *self.edge_indices.entry(key).or_insert_with(|| {
+            edges.push(key);
+            edges.len() - 1
+        })
+    }
+
+    /// Adds a face with some given edges if it doesn't exist yet.
+    fn add_face(&mut self, mut face: Vec<usize>) {
+        face.sort_unstable();
 
-                // Something went wrong while fetching the next XML event.
-                Err(_) => return Err(GgbError::InvalidXml),
+        if !self.face_indices.contains_key(&face) {
+            self.face_indices.insert(face.clone(), self.faces.len());
+            self.faces.push(face);
+        }
+    }
+
+    /// Adds a polygon through a cycle of points.
+    fn add_polygon(&mut self, vertices: &[usize]) {
+        let face = (0..vertices.len())
+            .map(|i| self.add_edge(vertices[i], vertices[(i + 1) % vertices.len()]))
+            .collect();
+        self.add_face(face);
+    }
+
+    /// Adds the faces of the convex hull of some points.
+    fn add_solid(&mut self, command: &Command, vertices: &[usize]) -> GgbResult<()> {
+        let points = &self.construction.points;
+        let hull =
+            Concrete::convex_hull(vertices.iter().map(|&v| points[v].coords.clone()).collect());
+
+        if hull.rank() != 4 {
+            return Err(GgbError::FlatPolyhedron {
+                command: command.name.clone(),
+            });
+        }
+
+        // The hull's vertices are copies of the points.
+        let indices: BTreeMap<_, _> = vertices
+            .iter()
+            .map(|&v| (PointOrd::new(points[v].coords.clone()), v))
+            .collect();
+        let hull_vertices: Vec<_> = hull
+            .vertices
+            .iter()
+            .map(|v| indices[&PointOrd::new(v.clone())])
+            .collect();
+
+        let hull_edges: Vec<_> = hull[2]
+            .iter()
+            .map(|edge| self.add_edge(hull_vertices[edge.subs[0]], hull_vertices[edge.subs[1]]))
+            .collect();
+
+        for face in &hull[3] {
+            self.add_face(face.subs.iter().map(|&e| hull_edges[e]).collect());
+        }
+
+        Ok(())
+    }
+
+    /// Reads a command, adding whatever elements it builds.
+    fn read_command(&mut self, command: &'a Command) -> GgbResult<()> {
+        let construction = self.construction;
+
+        match command.name.as_str() {
+            // A segment between two points.
+            "Segment" => match command.inputs.as_slice() {
+                [v0, v1] => {
+                    let edge = self.add_edge(self.point(command, v0)?, self.point(command, v1)?);
+                    self.segments.push(edge);
+                }
+                _ => {
+                    return Err(GgbError::UnsupportedInput {
+                        command: command.name.clone(),
+                        input: command.inputs.join(", "),
+                    })
+                }
             },
 
-            // The file has finished being read. Time for processing!
-            None => todo!(),
+            // A polygon through some points, given either as separate inputs
+            // or as a list.
+            "Polygon" => {
+                let labels: Vec<&str> = match command.inputs.as_slice() {
+                    [list] if list.starts_with('{') && list.ends_with('}') => {
+                        list[1..list.len() - 1].split(',').map(str::trim).collect()
+                    }
+                    inputs => inputs.iter().map(String::as_str).collect(),
+                };
+
+                let vertices = labels
+                    .into_iter()
+                    .map(|label| self.point(command, label))
+                    .collect::<GgbResult<Vec<_>>>()?;
+
+                if vertices.len() < 3 {
+                    return Err(GgbError::UnsupportedInput {
+                        command: command.name.clone(),
+                        input: command.inputs.join(", "),
+                    });
+                }
+
+                self.add_polygon(&vertices);
+                if let Some(label) = command.outputs.first() {
+                    self.polygons.insert(label, vertices);
+                }
+            }
+
+            // The convex hull of some points.
+            "Polyhedron" => {
+                let vertices = command
+                    .inputs
+                    .iter()
+                    .map(|label| self.point(command, label))
+                    .collect::<GgbResult<Vec<_>>>()?;
+                self.add_solid(command, &vertices)?;
+            }
+
+            // A convex solid, whose vertices are either inputs, vertices of
+            // input polygons, or outputs. Any other inputs, like heights or
+            // directions, don't change the vertices.
+            name if SOLID_COMMANDS.contains(&name) => {
+                let mut vertices = Vec::new();
+
+                for label in command.inputs.iter().chain(&command.outputs) {
+                    if let Some(&v) = self.point_indices.get(label.as_str()) {
+                        vertices.push(v);
+                    } else if let Some(polygon) = self.polygons.get(label.as_str()) {
+                        vertices.extend(polygon);
+                    }
+                }
+
+                self.add_solid(command, &vertices)?;
+            }
+
+            // Commands that only build points, like `Midpoint`, are taken
+            // care of by reading the coordinates of the points.
+            _ => {
+                if !command
+                    .outputs
+                    .iter()
+                    .all(|label| construction.is_point(label))
+                {
+                    return Err(GgbError::UnsupportedCommand {
+                        name: command.name.clone(),
+                    });
+                }
+            }
         }
+
+        Ok(())
+    }
+
+    /// Builds the polytope. If there are at least two faces, this is a
+    /// polyhedron with all of them, and any segments that aren't edges of
+    /// these faces are ignored. Otherwise, this is a polygon, either with the
+    /// single face or with all of the segments.
+    fn build(self) -> GgbResult<Concrete> {
+        let polygon_edges: Vec<usize> = match self.faces.as_slice() {
+            [] => self.segments.clone(),
+            [face] => face.clone(),
+            _ => Vec::new(),
+        };
+
+        // The edges that make it into the polytope, in order.
+        let mut edges: Vec<usize> = if self.faces.len() >= 2 {
+            self.faces.iter().flatten().copied().collect()
+        } else {
+            polygon_edges
+        };
+        edges.sort_unstable();
+        edges.dedup();
+
+        if edges.is_empty() {
+            return Err(GgbError::Empty);
+        }
+
+        // The vertices that make it into the polytope, in order.
+        let mut vertices: Vec<usize> = edges.iter().flat_map(|&e| self.edges[e]).collect();
+        vertices.sort_unstable();
+        vertices.dedup();
+
+        let vertex_map: HashMap<_, _> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let edge_map: HashMap<_, _> = edges.iter().enumerate().map(|(i, &e)| (e, i)).collect();
+
+        let mut builder = AbstractBuilder::with_rank_capacity(4);
+        builder.push_min();
+        builder.push_vertices(vertices.len());
+        builder.push(
+            edges
+                .iter()
+                .map(|&e| {
+                    let [v0, v1] = self.edges[e];
+                    Subelements::from(vec![vertex_map[&v0], vertex_map[&v1]])
+                })
+                .collect::<SubelementList>(),
+        );
+
+        if self.faces.len() >= 2 {
+            builder.push(
+                self.faces
+                    .iter()
+                    .map(|face| face.iter().map(|e| edge_map[e]).collect::<Subelements>())
+                    .collect::<SubelementList>(),
+            );
+        }
+        builder.push_max();
+
+        builder
+            .ranks()
+            .is_valid()
+            .map_err(GgbError::InvalidPolytope)?;
+
+        let vertices = vertices
+            .into_iter()
+            .map(|v| {
+                let coords = &self.construction.points[v].coords;
+
+                // Polytopes made only out of 2D points are kept in 2D.
+                if self.construction.is_3d {
+                    coords.clone()
+                } else {
+                    coords.rows(0, 2).into_owned()
+                }
+            })
+            .collect();
+
+        // Safety: we've just checked that the polytope is valid.
+        Ok(Concrete::new(vertices, unsafe { builder.build() }))
+    }
+}
+
+/// Parses the `geogebra.xml` file to produce a polytope.
+pub(super) fn parse_xml(xml: &str) -> GgbResult<Concrete> {
+    let construction = Construction::new(xml)?;
+    let mut builder = GgbBuilder::new(&construction);
+
+    for command in &construction.commands {
+        builder.read_command(command)?;
+    }
+
+    builder.build()
+}
+
+/// An error while writing a GGB file.
+#[derive(Debug)]
+pub enum GgbWriteError {
+    /// The polytope doesn't fit in 3D space.
+    TooManyDimensions {
+        /// The number of dimensions of the polytope.
+        dim: usize,
+    },
+
+    /// An error occured while writing the XML file.
+    XmlError(writer::Error),
+
+    /// An error occured while writing the ZIP file.
+    ZipError(ZipError),
+
+    /// There was a problem saving the file.
+    IoError(IoError),
+}
+
+impl Display for GgbWriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyDimensions { dim } => write!(
+                f,
+                "cannot write {}-dimensional polytope, GeoGebra only supports up to 3 dimensions",
+                dim
+            ),
+            Self::XmlError(err) => write!(f, "XML error: {}", err),
+            Self::ZipError(err) => write!(f, "ZIP error: {}", err),
+            Self::IoError(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for GgbWriteError {}
+
+impl From<writer::Error> for GgbWriteError {
+    fn from(err: writer::Error) -> Self {
+        Self::XmlError(err)
+    }
+}
+
+impl From<ZipError> for GgbWriteError {
+    fn from(err: ZipError) -> Self {
+        Self::ZipError(err)
+    }
+}
+
+impl From<IoError> for GgbWriteError {
+    fn from(err: IoError) -> Self {
+        Self::IoError(err)
+    }
+}
+
+/// The result of trying to write a GGB file.
+pub type GgbWriteResult<T> = Result<T, GgbWriteError>;
+
+/// Returns the label of the element with a given index, like `V_{0}`.
+fn label(prefix: char, idx: usize) -> String {
+    format!("{}_{{{}}}", prefix, idx)
+}
+
+/// An auxiliary struct to write a polytope into the `geogebra.xml` file.
+///
+/// Vertices, edges and faces are written as 3D points, segments and polygons
+/// respectively, labeled `V_{i}`, `E_{i}` and `F_{i}`. Since a polygon in
+/// GeoGebra has a single boundary, faces with more than one component are
+/// written as one polygon per component.
+struct GgbWriter<'a, W: Write> {
+    /// The XML writer.
+    xml: EventWriter<W>,
+
+    /// The polytope that we're converting into a GGB file.
+    poly: &'a Concrete,
+
+    /// The number of polygons written so far.
+    polygon_count: usize,
+}
+
+impl<'a, W: Write> GgbWriter<'a, W> {
+    /// Initializes a new GGB writer from a polytope and the writer for the
+    /// `geogebra.xml` file.
+    fn new(poly: &'a Concrete, sink: W) -> Self {
+        Self {
+            xml: EmitterConfig::new()
+                .perform_indent(true)
+                .create_writer(sink),
+            poly,
+            polygon_count: 0,
+        }
+    }
+
+    /// Opens an XML element with some attributes.
+    fn start(&mut self, name: &str, attributes: &[(&str, &str)]) -> GgbWriteResult<()> {
+        let event = attributes.iter().fold(
+            writer::XmlEvent::start_element(name),
+            |event, (key, value)| event.attr(*key, value),
+        );
+
+        Ok(self.xml.write(event)?)
+    }
+
+    /// Closes the last XML element that was opened.
+    fn end(&mut self) -> GgbWriteResult<()> {
+        Ok(self.xml.write(writer::XmlEvent::end_element())?)
+    }
+
+    /// Writes an XML element with some attributes and no children.
+    fn empty(&mut self, name: &str, attributes: &[(&str, &str)]) -> GgbWriteResult<()> {
+        self.start(name, attributes)?;
+        self.end()
+    }
+
+    /// Writes a command with its inputs and a single output.
+    fn command(&mut self, name: &str, inputs: &[&str], output: &str) -> GgbWriteResult<()> {
+        let keys: Vec<_> = (0..inputs.len()).map(|i| format!("a{}", i)).collect();
+        let inputs: Vec<_> = keys
+            .iter()
+            .map(String::as_str)
+            .zip(inputs.iter().copied())
+            .collect();
+
+        self.start("command", &[("name", name)])?;
+        self.empty("input", &inputs)?;
+        self.empty("output", &[("a0", output)])?;
+        self.end()
+    }
+
+    /// Writes the vertices of the polytope as free 3D points.
+    fn write_vertices(&mut self) -> GgbWriteResult<()> {
+        for (idx, v) in self.poly.vertices.iter().enumerate() {
+            let coord = |i: usize| v.get(i).copied().unwrap_or_default().to_string();
+
+            self.start(
+                "element",
+                &[("type", "point3d"), ("label", &label('V', idx))],
+            )?;
+            self.empty("show", &[("object", "true"), ("label", "false")])?;
+            self.empty(
+                "coords",
+                &[
+                    ("x", &coord(0)),
+                    ("y", &coord(1)),
+                    ("z", &coord(2)),
+                    ("w", "1"),
+                ],
+            )?;
+            self.end()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the edges of the polytope as segments between its vertices.
+    fn write_edges(&mut self) -> GgbWriteResult<()> {
+        for idx in 0..self.poly.el_count(2) {
+            let subs = &self.poly[(2, idx)].subs;
+            let output = label('E', idx);
+
+            self.command(
+                "Segment",
+                &[&label('V', subs[0]), &label('V', subs[1])],
+                &output,
+            )?;
+            self.start("element", &[("type", "segment3d"), ("label", &output)])?;
+            self.empty("show", &[("object", "true"), ("label", "false")])?;
+            self.end()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the faces of the polytope as polygons through its vertices.
+    fn write_faces(&mut self) -> GgbWriteResult<()> {
+        for idx in 0..self.poly.el_count(3) {
+            let cycles = CycleList::from_edges(
+                self.poly[(3, idx)]
+                    .subs
+                    .iter()
+                    .map(|&i| &self.poly[(2, i)].subs),
+            );
+
+            for cycle in cycles {
+                let vertices: Vec<_> = cycle.iter().map(|&v| label('V', v)).collect();
+                let output = label('F', self.polygon_count);
+                self.polygon_count += 1;
+
+                // The polygon command with a list of points doesn't create
+                // any segments, since we've already written them.
+                self.command(
+                    "Polygon",
+                    &[&format!("{{{}}}", vertices.join(", "))],
+                    &output,
+                )?;
+                self.start("element", &[("type", "polygon3d"), ("label", &output)])?;
+                self.empty("show", &[("object", "true"), ("label", "false")])?;
+                self.end()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the whole `geogebra.xml` file.
+    fn write(mut self) -> GgbWriteResult<()> {
+        self.start(
+            "geogebra",
+            &[("format", "5.0"), ("app", "classic"), ("subApp", "3d")],
+        )?;
+        self.start("construction", &[])?;
+
+        self.write_vertices()?;
+        if self.poly.rank() >= 2 {
+            self.write_edges()?;
+        }
+        if self.poly.rank() >= 3 {
+            self.write_faces()?;
+        }
+
+        self.end()?;
+        self.end()
+    }
+}
+
+impl Concrete {
+    /// Converts a polytope in at most 3D into a GGB file, which is really a
+    /// ZIP file containing a `geogebra.xml` file. Only the vertices, edges and
+    /// faces are written, as GeoGebra has no notion of higher elements.
+    pub fn to_ggb(&self) -> GgbWriteResult<Vec<u8>> {
+        let dim = self.dim_or();
+        if dim > 3 {
+            return Err(GgbWriteError::TooManyDimensions { dim });
+        }
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("geogebra.xml", FileOptions::default())?;
+        GgbWriter::new(self, &mut zip).write()?;
+
+        Ok(zip.finish()?.into_inner())
+    }
+
+    /// Writes a polytope's GGB file in a specified file path.
+    pub fn to_ggb_path<P: AsRef<Path>>(&self, fp: P) -> GgbWriteResult<()> {
+        std::fs::write(fp, self.to_ggb()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test, Polytope};
+
+    use std::io::Read;
+
+    /// Writes a polytope into a GGB file and reads back its XML.
+    fn ggb_xml(poly: &Concrete) -> String {
+        let ggb = poly.to_ggb().unwrap();
+        let mut xml = String::new();
+
+        zip::ZipArchive::new(Cursor::new(ggb))
+            .unwrap()
+            .by_name("geogebra.xml")
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+
+        xml
+    }
+
+    /// Counts the elements of a given type in a GGB file.
+    fn count(xml: &str, el_type: &str) -> usize {
+        xml.matches(&format!("type=\"{}\"", el_type)).count()
+    }
+
+    /// Checks that the vertices, edges and faces of a cube are written.
+    #[test]
+    fn cube() {
+        let xml = ggb_xml(&Concrete::cube());
+
+        assert_eq!(count(&xml, "point3d"), 8);
+        assert_eq!(count(&xml, "segment3d"), 12);
+        assert_eq!(count(&xml, "polygon3d"), 6);
+        assert_eq!(xml.matches("<command name=\"Polygon\">").count(), 6);
+        assert!(xml.contains("a0=\"{V_{"));
+    }
+
+    /// Checks that polygons are padded into 3D, and that compound polygons are
+    /// split into components.
+    #[test]
+    fn polygon() {
+        let xml = ggb_xml(&Concrete::star_polygon(6, 2));
+
+        assert_eq!(count(&xml, "point3d"), 6);
+        assert_eq!(count(&xml, "segment3d"), 6);
+        assert_eq!(count(&xml, "polygon3d"), 2);
+        assert!(xml.contains("z=\"0\""));
+    }
+
+    /// Checks that polytopes in more than 3D can't be written.
+    #[test]
+    fn too_many_dimensions() {
+        assert!(matches!(
+            Concrete::hypercube(5).to_ggb(),
+            Err(GgbWriteError::TooManyDimensions { dim: 4 })
+        ));
+    }
+
+    /// Wraps some elements and commands into a `geogebra.xml` file.
+    fn construction(body: &str) -> String {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+            <geogebra format=\"5.0\"><construction>{}</construction></geogebra>",
+            body
+        )
+    }
+
+    /// A 2D point in a GGB file.
+    fn point(label: &str, x: f64, y: f64) -> String {
+        format!(
+            "<element type=\"point\" label=\"{}\"><coords x=\"{}\" y=\"{}\" z=\"1\"/></element>",
+            label, x, y
+        )
+    }
+
+    /// A 3D point in a GGB file.
+    fn point3d(label: &str, x: f64, y: f64, z: f64) -> String {
+        format!(
+            "<element type=\"point3d\" label=\"{}\">\
+            <coords x=\"{}\" y=\"{}\" z=\"{}\" w=\"1\"/></element>",
+            label, x, y, z
+        )
+    }
+
+    /// A command in a GGB file.
+    fn command(name: &str, inputs: &[&str], output: &str) -> String {
+        let inputs: String = inputs
+            .iter()
+            .enumerate()
+            .map(|(idx, input)| format!(" a{}=\"{}\"", idx, input))
+            .collect();
+
+        format!(
+            "<command name=\"{}\"><input{}/><output a0=\"{}\"/></command>",
+            name, inputs, output
+        )
+    }
+
+    /// The points of a unit square.
+    fn square() -> String {
+        point("A", 0.0, 0.0) + &point("B", 1.0, 0.0) + &point("C", 1.0, 1.0) + &point("D", 0.0, 1.0)
+    }
+
+    /// Checks that polyhedra and polygons survive being written and read back.
+    #[test]
+    fn round_trip() {
+        test(
+            &parse_xml(&ggb_xml(&Concrete::cube())).unwrap(),
+            [1, 8, 12, 6, 1],
+        );
+        test(
+            &parse_xml(&ggb_xml(&Concrete::polygon(5))).unwrap(),
+            [1, 5, 5, 1],
+        );
+    }
+
+    /// Checks that polygons can be given by points or by lists of points.
+    #[test]
+    fn polygon_inputs() {
+        let points = construction(&(square() + &command("Polygon", &["A", "B", "C", "D"], "p")));
+        let list = construction(&(square() + &command("Polygon", &["{A, B, C, D}"], "p")));
+
+        for xml in [points, list] {
+            let poly = parse_xml(&xml).unwrap();
+            test(&poly, [1, 4, 4, 1]);
+            assert_eq!(poly.dim(), Some(2));
+        }
+    }
+
+    /// Checks that a polygon can be made out of segments.
+    #[test]
+    fn segments() {
+        let xml = construction(
+            &(square()
+                + &command("Segment", &["A", "B"], "a")
+                + &command("Segment", &["B", "C"], "b")
+                + &command("Segment", &["C", "D"], "c")
+                + &command("Segment", &["D", "A"], "d")
+                + &point("M", 0.5, 0.0)
+                + &command("Midpoint", &["a"], "M")),
+        );
+
+        test(&parse_xml(&xml).unwrap(), [1, 4, 4, 1]);
+    }
+
+    /// Checks that polyhedra are read as the convex hulls of their points.
+    #[test]
+    fn polyhedron() {
+        let xml = construction(
+            &(point3d("A", 0.0, 0.0, 0.0)
+                + &point3d("B", 1.0, 0.0, 0.0)
+                + &point3d("C", 0.0, 1.0, 0.0)
+                + &point3d("D", 0.0, 0.0, 1.0)
+                + &command("Polyhedron", &["A", "B", "C", "D"], "t")),
+        );
+
+        test(&parse_xml(&xml).unwrap(), [1, 4, 6, 4, 1]);
+    }
+
+    /// Checks that a solid built on a polygon includes its vertices.
+    #[test]
+    fn pyramid() {
+        let xml = construction(
+            &(square()
+                + &command("Polygon", &["A", "B", "C", "D"], "p")
+                + &point3d("E", 0.5, 0.5, 1.0)
+                + &command("Pyramid", &["p", "E"], "q")),
+        );
+
+        test(&parse_xml(&xml).unwrap(), [1, 5, 8, 5, 1]);
+    }
+
+    /// Checks that unsupported commands and inputs are reported.
+    #[test]
+    fn unsupported() {
+        let circle = construction(
+            &(square()
+                + "<element type=\"conic\" label=\"c\"/>"
+                + &command("Circle", &["A", "B"], "c")),
+        );
+        assert!(matches!(
+            parse_xml(&circle),
+            Err(GgbError::UnsupportedCommand { name }) if name == "Circle"
+        ));
+
+        let regular = construction(&(square() + &command("Polygon", &["A", "B", "5"], "p")));
+        assert!(matches!(
+            parse_xml(&regular),
+            Err(GgbError::UnsupportedInput { input, .. }) if input == "5"
+        ));
+
+        let flat = construction(&(square() + &command("Polyhedron", &["A", "B", "C", "D"], "p")));
+        assert!(matches!(
+            parse_xml(&flat),
+            Err(GgbError::FlatPolyhedron { .. })
+        ));
+
+        assert!(matches!(
+            parse_xml(&construction(&square())),
+            Err(GgbError::Empty)
+        ));
     }
 }
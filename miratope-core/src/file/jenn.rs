@@ -0,0 +1,349 @@
+//! Contains the code that reads and writes the parameters Jenn3D uses to
+//! describe a polytope.
+//!
+//! [Jenn3D](http://jenn3d.org/) draws the polytopes whose symmetry group is a
+//! Coxeter group of rank 4. It takes the six entries above the diagonal of the
+//! Coxeter matrix with the `-c` option, and the weights of the generator point
+//! with the `-v` option, as in `jenn -c 5 2 2 3 2 3 -v 1 0 0 0`. We read the
+//! weights as the values of the nodes of a Coxeter diagram, so that nonzero
+//! weights correspond to ringed nodes. The other options only change how
+//! Jenn3D draws the polytope, so we don't support them.
+
+use std::fmt::Display;
+
+use crate::{
+    conc::Concrete,
+    cox::{
+        cd::{Cd, Edge, Node},
+        Cox,
+    },
+};
+
+use petgraph::graph::NodeIndex;
+
+/// The number of nodes in the Coxeter diagrams Jenn3D supports.
+const RANK: usize = 4;
+
+/// The number of entries above the diagonal of a Coxeter matrix of rank 4.
+const ENTRIES: usize = RANK * (RANK - 1) / 2;
+
+/// Possible errors while reading the Jenn3D parameters.
+#[derive(Debug)]
+pub enum JennError {
+    /// The `-c` option is missing.
+    MissingMatrix,
+
+    /// An option wasn't followed by enough numbers.
+    MissingValues {
+        /// The name of the option.
+        option: &'static str,
+
+        /// The number of values the option takes.
+        expected: usize,
+    },
+
+    /// Some number could not be parsed.
+    ParseError {
+        /// The text that couldn't be parsed.
+        value: String,
+    },
+
+    /// An entry of the Coxeter matrix isn't an integer of at least 2.
+    InvalidEntry {
+        /// The invalid entry.
+        value: String,
+    },
+
+    /// An option that we don't support was found.
+    UnsupportedOption {
+        /// The name of the option.
+        option: String,
+    },
+
+    /// The Coxeter group isn't spherical, so it doesn't describe a polytope.
+    NotSpherical,
+}
+
+impl Display for JennError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingMatrix => write!(f, "missing Coxeter matrix (-c)"),
+            Self::MissingValues { option, expected } => {
+                write!(f, "{} must be followed by {} numbers", option, expected)
+            }
+            Self::ParseError { value } => write!(f, "could not parse \"{}\"", value),
+            Self::InvalidEntry { value } => write!(
+                f,
+                "invalid Coxeter matrix entry \"{}\", expected an integer of at least 2",
+                value
+            ),
+            Self::UnsupportedOption { option } => write!(f, "unsupported option {}", option),
+            Self::NotSpherical => write!(f, "the Coxeter group is not spherical"),
+        }
+    }
+}
+
+impl std::error::Error for JennError {}
+
+/// The result of trying to read the Jenn3D parameters.
+pub type JennResult<T> = Result<T, JennError>;
+
+/// Reads the values after an option.
+fn values<'a, I: Iterator<Item = &'a str>>(
+    tokens: &mut I,
+    option: &'static str,
+    expected: usize,
+) -> JennResult<Vec<&'a str>> {
+    let values: Vec<_> = tokens.take(expected).collect();
+
+    if values.len() == expected {
+        Ok(values)
+    } else {
+        Err(JennError::MissingValues { option, expected })
+    }
+}
+
+/// The parameters Jenn3D uses to describe a polytope.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Jenn {
+    /// The entries above the diagonal of the Coxeter matrix, read row by row.
+    pub matrix: [u32; ENTRIES],
+
+    /// The weights of the generator point, one for each node.
+    pub weights: [f64; RANK],
+}
+
+impl Jenn {
+    /// Initializes the parameters for the regular polytope with a given
+    /// Coxeter matrix, whose first node is ringed.
+    pub fn new(matrix: [u32; ENTRIES]) -> Self {
+        Self {
+            matrix,
+            weights: [1.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// Parses the Jenn3D parameters, optionally preceded by the name of the
+    /// program. If the weights are omitted, only the first node is ringed, so
+    /// that the polytope is regular.
+    pub fn parse(input: &str) -> JennResult<Self> {
+        let mut tokens = input.split_whitespace().peekable();
+        tokens.next_if_eq(&"jenn");
+
+        let mut matrix = None;
+        let mut weights = None;
+
+        while let Some(option) = tokens.next() {
+            match option {
+                "-c" => {
+                    let mut entries = [0; ENTRIES];
+                    for (entry, value) in
+                        entries.iter_mut().zip(values(&mut tokens, "-c", ENTRIES)?)
+                    {
+                        *entry = match value.parse() {
+                            Ok(m) if m >= 2 => m,
+                            _ => {
+                                return Err(JennError::InvalidEntry {
+                                    value: value.to_string(),
+                                })
+                            }
+                        };
+                    }
+
+                    matrix = Some(entries);
+                }
+
+                "-v" => {
+                    let mut node_weights = [0.0; RANK];
+                    for (weight, value) in
+                        node_weights
+                            .iter_mut()
+                            .zip(values(&mut tokens, "-v", RANK)?)
+                    {
+                        *weight = value.parse().map_err(|_| JennError::ParseError {
+                            value: value.to_string(),
+                        })?;
+                    }
+
+                    weights = Some(node_weights);
+                }
+
+                option => {
+                    return Err(JennError::UnsupportedOption {
+                        option: option.to_string(),
+                    })
+                }
+            }
+        }
+
+        let mut jenn = Self::new(matrix.ok_or(JennError::MissingMatrix)?);
+        if let Some(weights) = weights {
+            jenn.weights = weights;
+        }
+
+        Ok(jenn)
+    }
+
+    /// Returns the parameters for the polytope described by a Coxeter diagram,
+    /// if Jenn3D can describe it. This requires the diagram to have four
+    /// nodes, none of them snub, and only integral edges.
+    pub fn from_cd(cd: &Cd) -> Option<Self> {
+        if cd.node_count() != RANK {
+            return None;
+        }
+
+        let mut weights = [0.0; RANK];
+        for (weight, node) in weights.iter_mut().zip(cd.node_iter()) {
+            *weight = match node {
+                Node::Unringed => 0.0,
+                Node::Ringed(x) => x,
+                Node::Snub(_) => return None,
+            };
+        }
+
+        let mut matrix = [2; ENTRIES];
+        for (entry, value) in matrix.iter_mut().zip(cd.cox().upper_triangle()) {
+            if value.fract() != 0.0 {
+                return None;
+            }
+
+            *entry = value as u32;
+        }
+
+        Some(Self { matrix, weights })
+    }
+
+    /// Returns the Coxeter matrix of the symmetry group.
+    pub fn cox(&self) -> Cox<f64> {
+        let entries: Vec<_> = self.matrix.iter().map(|&m| m as f64).collect();
+
+        // The number of entries always matches the rank.
+        Cox::from_upper_triangle(&entries, RANK).unwrap()
+    }
+
+    /// Returns the Coxeter diagram of the polytope, whose node values are the
+    /// weights.
+    pub fn cd(&self) -> JennResult<Cd> {
+        let mut cd = Cd::new();
+
+        for &weight in &self.weights {
+            cd.add_node(if weight == 0.0 {
+                Node::Unringed
+            } else {
+                Node::ringed(weight)
+            });
+        }
+
+        let mut entries = self.matrix.iter();
+        for i in 0..RANK {
+            for j in i + 1..RANK {
+                // There's as many entries as pairs of nodes.
+                let m = *entries.next().unwrap();
+                let invalid = |_| JennError::InvalidEntry {
+                    value: m.to_string(),
+                };
+
+                cd.add_edge(
+                    NodeIndex::new(i),
+                    NodeIndex::new(j),
+                    Edge::int(m, 0).map_err(invalid)?,
+                )
+                .map_err(invalid)?;
+            }
+        }
+
+        Ok(cd)
+    }
+}
+
+impl Display for Jenn {
+    /// Writes the parameters as Jenn3D takes them in the command line, without
+    /// the name of the program.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "-c")?;
+        for m in &self.matrix {
+            write!(f, " {}", m)?;
+        }
+
+        write!(f, " -v")?;
+        for w in &self.weights {
+            write!(f, " {}", w)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Concrete {
+    /// Builds the polytope described by the Jenn3D parameters. See
+    /// [`Jenn::parse`] for the syntax.
+    pub fn from_jenn(input: &str) -> JennResult<Self> {
+        let cd = Jenn::parse(input)?.cd()?;
+        Self::from_cd(&cd).ok_or(JennError::NotSpherical)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test;
+
+    /// Checks that the regular polychora can be read.
+    #[test]
+    fn regular() {
+        test(
+            &Concrete::from_jenn("jenn -c 3 2 2 3 2 3").unwrap(),
+            [1, 5, 10, 10, 5, 1],
+        );
+        test(
+            &Concrete::from_jenn("-c 4 2 2 3 2 3 -v 0 0 0 1").unwrap(),
+            [1, 8, 24, 32, 16, 1],
+        );
+        test(
+            &Concrete::from_jenn("-c 3 2 2 4 2 3").unwrap(),
+            [1, 24, 96, 96, 24, 1],
+        );
+    }
+
+    /// Checks that parameters survive being written and read back, and that
+    /// they're mapped onto the right Coxeter matrix.
+    #[test]
+    fn round_trip() {
+        let cd = Cd::parse("x5o3o3x").unwrap();
+        let jenn = Jenn::from_cd(&cd).unwrap();
+
+        assert_eq!(jenn.to_string(), "-c 5 2 2 3 2 3 -v 1 0 0 1");
+        assert_eq!(Jenn::parse(&jenn.to_string()).unwrap(), jenn);
+        assert_eq!(jenn.cox(), cd.cox());
+        assert_eq!(jenn.cd().unwrap().cox(), cd.cox());
+
+        // Jenn3D only supports rank 4 groups and integral edges.
+        assert!(Jenn::from_cd(&Cd::parse("x4o3o").unwrap()).is_none());
+        assert!(Jenn::from_cd(&Cd::parse("x5/2o3o3o").unwrap()).is_none());
+    }
+
+    /// Checks that invalid parameters are reported.
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            Jenn::parse("-v 1 0 0 0"),
+            Err(JennError::MissingMatrix)
+        ));
+        assert!(matches!(
+            Jenn::parse("-c 5 2 2 3"),
+            Err(JennError::MissingValues { option: "-c", .. })
+        ));
+        assert!(matches!(
+            Jenn::parse("-c 5 2 1 3 2 3"),
+            Err(JennError::InvalidEntry { .. })
+        ));
+        assert!(matches!(
+            Jenn::parse("-c 5 2 2 3 2 3 -g 0 1"),
+            Err(JennError::UnsupportedOption { .. })
+        ));
+        assert!(matches!(
+            Concrete::from_jenn("-c 6 2 2 3 2 3"),
+            Err(JennError::NotSpherical)
+        ));
+    }
+}
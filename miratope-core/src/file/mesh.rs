@@ -0,0 +1,597 @@
+//! Writes triangle meshes into files that other programs can read, like STL,
+//! Wavefront OBJ and binary glTF.
+//!
+//! The meshes here know nothing about polytopes: they're just vertices and
+//! triangles, split into named groups. Building them out of a polytope is up
+//! to whoever projects it into 3D.
+
+use std::io::{Error as IoError, ErrorKind, Result as IoResult, Write};
+
+/// Subtracts two vectors.
+fn sub(p: [f32; 3], q: [f32; 3]) -> [f32; 3] {
+    [p[0] - q[0], p[1] - q[1], p[2] - q[2]]
+}
+
+/// Returns the cross product of two vectors.
+fn cross(p: [f32; 3], q: [f32; 3]) -> [f32; 3] {
+    [
+        p[1] * q[2] - p[2] * q[1],
+        p[2] * q[0] - p[0] * q[2],
+        p[0] * q[1] - p[1] * q[0],
+    ]
+}
+
+/// Returns the dot product of two vectors.
+fn dot(p: [f32; 3], q: [f32; 3]) -> f32 {
+    p[0] * q[0] + p[1] * q[1] + p[2] * q[2]
+}
+
+/// Normalizes a vector, or returns the zero vector if it's too short.
+fn normalize(p: [f32; 3]) -> [f32; 3] {
+    let norm = dot(p, p).sqrt();
+
+    if norm < f32::EPSILON {
+        [0.0; 3]
+    } else {
+        p.map(|c| c / norm)
+    }
+}
+
+/// Returns the unit normal of a triangle, given by the right-hand rule.
+fn normal([p, q, r]: [[f32; 3]; 3]) -> [f32; 3] {
+    normalize(cross(sub(q, p), sub(r, p)))
+}
+
+/// Escapes a string so that it can be written inside of a JSON string.
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// A named set of triangles in a mesh.
+#[derive(Clone, Debug, Default)]
+pub struct MeshGroup {
+    /// The name of the group.
+    pub name: String,
+
+    /// The vertex indices of the triangles.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl MeshGroup {
+    /// Initializes a new group with no triangles.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            triangles: Vec::new(),
+        }
+    }
+}
+
+/// A triangle mesh, with its triangles split into named groups.
+#[derive(Clone, Debug, Default)]
+pub struct TriangleMesh {
+    /// The coordinates of the vertices.
+    pub vertices: Vec<[f32; 3]>,
+
+    /// The groups of triangles in the mesh.
+    pub groups: Vec<MeshGroup>,
+}
+
+impl TriangleMesh {
+    /// Initializes a new mesh with no vertices or triangles.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the number of triangles in the mesh.
+    pub fn triangle_count(&self) -> usize {
+        self.groups.iter().map(|group| group.triangles.len()).sum()
+    }
+
+    /// Returns whether the mesh has no triangles, in which case there's
+    /// nothing to write.
+    pub fn is_empty(&self) -> bool {
+        self.triangle_count() == 0
+    }
+
+    /// Adds a new vertex, and returns its index.
+    pub fn push_vertex(&mut self, v: [f32; 3]) -> u32 {
+        self.vertices.push(v);
+        (self.vertices.len() - 1) as u32
+    }
+
+    /// Adds a triangle to the group with a given index, turned so that it
+    /// faces away from the origin.
+    pub fn push_outward(&mut self, group: usize, mut triangle: [u32; 3]) {
+        let [p, q, r] = triangle.map(|v| self.vertices[v as usize]);
+        if dot(cross(sub(q, p), sub(r, p)), p) < 0.0 {
+            triangle.swap(1, 2);
+        }
+
+        self.groups[group].triangles.push(triangle);
+    }
+
+    /// Adds a closed cylinder between two points.
+    pub fn push_cylinder(
+        &mut self,
+        group: &mut MeshGroup,
+        p: [f32; 3],
+        q: [f32; 3],
+        radius: f32,
+        sides: usize,
+    ) {
+        let axis = normalize(sub(q, p));
+        if axis == [0.0; 3] {
+            return;
+        }
+
+        // Two unit vectors perpendicular to the axis and to each other. We
+        // cross the axis with the coordinate axis that's least parallel to it.
+        let least = (0..3)
+            .min_by(|&i, &j| axis[i].abs().partial_cmp(&axis[j].abs()).unwrap())
+            .unwrap();
+        let mut e = [0.0; 3];
+        e[least] = 1.0;
+        let u = normalize(cross(axis, e));
+        let v = cross(axis, u);
+
+        // The vertices around both ends of the cylinder.
+        let ring = |center: [f32; 3], i: usize| {
+            let angle = std::f32::consts::TAU * i as f32 / sides as f32;
+            let (sin, cos) = angle.sin_cos();
+            [0, 1, 2].map(|k| center[k] + radius * (cos * u[k] + sin * v[k]))
+        };
+
+        let start = self.vertices.len() as u32;
+        for i in 0..sides {
+            self.push_vertex(ring(p, i));
+            self.push_vertex(ring(q, i));
+        }
+        let bottom = |i: usize| start + 2 * (i % sides) as u32;
+        let top = |i: usize| bottom(i) + 1;
+
+        // The sides.
+        for i in 0..sides {
+            group.triangles.push([bottom(i), bottom(i + 1), top(i)]);
+            group.triangles.push([top(i), bottom(i + 1), top(i + 1)]);
+        }
+
+        // The caps.
+        for i in 1..sides - 1 {
+            group.triangles.push([bottom(0), bottom(i + 1), bottom(i)]);
+            group.triangles.push([top(0), top(i), top(i + 1)]);
+        }
+    }
+
+    /// Adds a sphere around a point, made out of `sides` meridians and half as
+    /// many parallels.
+    pub fn push_sphere(
+        &mut self,
+        group: &mut MeshGroup,
+        center: [f32; 3],
+        radius: f32,
+        sides: usize,
+    ) {
+        let parallels = (sides / 2).max(2);
+
+        let south = self.push_vertex([center[0], center[1], center[2] - radius]);
+        let start = self.vertices.len() as u32;
+        for j in 1..parallels {
+            let polar = std::f32::consts::PI * j as f32 / parallels as f32;
+            let (sin_polar, cos_polar) = polar.sin_cos();
+
+            for i in 0..sides {
+                let angle = std::f32::consts::TAU * i as f32 / sides as f32;
+                let (sin, cos) = angle.sin_cos();
+
+                self.push_vertex([
+                    center[0] + radius * sin_polar * cos,
+                    center[1] + radius * sin_polar * sin,
+                    center[2] - radius * cos_polar,
+                ]);
+            }
+        }
+        let north = self.push_vertex([center[0], center[1], center[2] + radius]);
+
+        // The index of the ith vertex in the jth parallel.
+        let idx = |j: usize, i: usize| start + (j * sides + i % sides) as u32;
+
+        for i in 0..sides {
+            group.triangles.push([south, idx(0, i + 1), idx(0, i)]);
+            group
+                .triangles
+                .push([north, idx(parallels - 2, i), idx(parallels - 2, i + 1)]);
+
+            for j in 0..parallels - 2 {
+                group
+                    .triangles
+                    .push([idx(j, i), idx(j, i + 1), idx(j + 1, i)]);
+                group
+                    .triangles
+                    .push([idx(j + 1, i), idx(j, i + 1), idx(j + 1, i + 1)]);
+            }
+        }
+    }
+
+    /// Returns an iterator over all triangles, as triples of coordinates.
+    fn triangles(&self) -> impl Iterator<Item = [[f32; 3]; 3]> + '_ {
+        self.groups.iter().flat_map(move |group| {
+            group
+                .triangles
+                .iter()
+                .map(move |t| t.map(|v| self.vertices[v as usize]))
+        })
+    }
+
+    /// Returns the groups that have any triangles.
+    fn nonempty_groups(&self) -> impl Iterator<Item = &MeshGroup> {
+        self.groups
+            .iter()
+            .filter(|group| !group.triangles.is_empty())
+    }
+
+    /// Returns an error if the mesh is empty. Most programs can't open empty
+    /// meshes, and the glTF format doesn't even allow them.
+    fn check_nonempty(&self) -> IoResult<()> {
+        if self.is_empty() {
+            Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "the mesh has no triangles",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes the mesh into a binary STL file.
+    pub fn write_binary_stl<W: Write>(&self, name: &str, w: &mut W) -> IoResult<()> {
+        self.check_nonempty()?;
+
+        // The header can hold anything but the word "solid" at its start.
+        let mut header = [b' '; 80];
+        let text = format!("Miratope export: {}", name);
+        let len = text.len().min(80);
+        header[..len].copy_from_slice(&text.as_bytes()[..len]);
+        w.write_all(&header)?;
+
+        w.write_all(&(self.triangle_count() as u32).to_le_bytes())?;
+        for triangle in self.triangles() {
+            for v in std::iter::once(normal(triangle)).chain(triangle) {
+                for c in v {
+                    w.write_all(&c.to_le_bytes())?;
+                }
+            }
+
+            // The attribute byte count.
+            w.write_all(&[0, 0])?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the mesh into an ASCII STL file.
+    pub fn write_ascii_stl<W: Write>(&self, name: &str, w: &mut W) -> IoResult<()> {
+        self.check_nonempty()?;
+        let name = name.replace(char::is_whitespace, "_");
+
+        writeln!(w, "solid {}", name)?;
+        for triangle in self.triangles() {
+            let [nx, ny, nz] = normal(triangle);
+
+            writeln!(w, "  facet normal {} {} {}", nx, ny, nz)?;
+            writeln!(w, "    outer loop")?;
+            for [x, y, z] in triangle {
+                writeln!(w, "      vertex {} {} {}", x, y, z)?;
+            }
+            writeln!(w, "    endloop")?;
+            writeln!(w, "  endfacet")?;
+        }
+        writeln!(w, "endsolid {}", name)
+    }
+
+    /// Writes the mesh into a Wavefront OBJ file, with a group for each group
+    /// of the mesh.
+    pub fn write_obj<W: Write>(&self, name: &str, w: &mut W) -> IoResult<()> {
+        self.check_nonempty()?;
+
+        writeln!(w, "# Exported from Miratope")?;
+        writeln!(w, "o {}", name.replace(char::is_whitespace, "_"))?;
+
+        for [x, y, z] in &self.vertices {
+            writeln!(w, "v {} {} {}", x, y, z)?;
+        }
+
+        for group in self.nonempty_groups() {
+            writeln!(w, "g {}", group.name)?;
+
+            // OBJ indices start at 1.
+            for [a, b, c] in &group.triangles {
+                writeln!(w, "f {} {} {}", a + 1, b + 1, c + 1)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the mesh into a binary glTF file, with a node for each group of
+    /// the mesh. All nodes share the same vertex buffer.
+    pub fn write_glb<W: Write>(&self, name: &str, w: &mut W) -> IoResult<()> {
+        // Buffer views and accessors can't be empty.
+        self.check_nonempty()?;
+
+        // The binary buffer holds the vertices, followed by the indices of
+        // each group.
+        let mut buffer = Vec::new();
+        for v in &self.vertices {
+            for c in v {
+                buffer.extend(c.to_le_bytes());
+            }
+        }
+        let positions_len = buffer.len();
+
+        let mut min = [f32::INFINITY; 3];
+        let mut max = [f32::NEG_INFINITY; 3];
+        for v in &self.vertices {
+            for k in 0..3 {
+                min[k] = min[k].min(v[k]);
+                max[k] = max[k].max(v[k]);
+            }
+        }
+
+        // The accessors for the indices of the groups, the nodes, and the
+        // meshes.
+        let mut accessors = vec![format!(
+            "{{\"bufferView\":0,\"componentType\":5126,\"count\":{},\"type\":\"VEC3\",\
+            \"min\":[{},{},{}],\"max\":[{},{},{}]}}",
+            self.vertices.len(),
+            min[0],
+            min[1],
+            min[2],
+            max[0],
+            max[1],
+            max[2]
+        )];
+        let mut nodes = Vec::new();
+        let mut meshes = Vec::new();
+
+        for (idx, group) in self.nonempty_groups().enumerate() {
+            accessors.push(format!(
+                "{{\"bufferView\":1,\"byteOffset\":{},\"componentType\":5125,\"count\":{},\
+                \"type\":\"SCALAR\"}}",
+                buffer.len() - positions_len,
+                group.triangles.len() * 3
+            ));
+            nodes.push(format!(
+                "{{\"name\":\"{}\",\"mesh\":{}}}",
+                json_escape(&group.name),
+                idx
+            ));
+            meshes.push(format!(
+                "{{\"name\":\"{}\",\"primitives\":[{{\"attributes\":{{\"POSITION\":0}},\
+                \"indices\":{},\"mode\":4}}]}}",
+                json_escape(&group.name),
+                idx + 1
+            ));
+
+            for triangle in &group.triangles {
+                for v in triangle {
+                    buffer.extend(v.to_le_bytes());
+                }
+            }
+        }
+
+        let json = format!(
+            "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"Miratope\"}},\
+            \"scene\":0,\"scenes\":[{{\"name\":\"{}\",\"nodes\":[{}]}}],\
+            \"nodes\":[{}],\"meshes\":[{}],\"accessors\":[{}],\
+            \"bufferViews\":[{{\"buffer\":0,\"byteLength\":{},\"target\":34962}},\
+            {{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34963}}],\
+            \"buffers\":[{{\"byteLength\":{}}}]}}",
+            json_escape(name),
+            (0..nodes.len())
+                .map(|idx| idx.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            nodes.join(","),
+            meshes.join(","),
+            accessors.join(","),
+            positions_len,
+            positions_len,
+            buffer.len() - positions_len,
+            buffer.len()
+        );
+
+        // Both chunks must have a length that's a multiple of 4. Everything
+        // in the buffer has 4 bytes already.
+        let mut json = json.into_bytes();
+        while json.len() % 4 != 0 {
+            json.push(b' ');
+        }
+
+        let total_len = 12 + 8 + json.len() + 8 + buffer.len();
+
+        // The header.
+        w.write_all(b"glTF")?;
+        w.write_all(&2u32.to_le_bytes())?;
+        w.write_all(&(total_len as u32).to_le_bytes())?;
+
+        // The JSON chunk.
+        w.write_all(&(json.len() as u32).to_le_bytes())?;
+        w.write_all(b"JSON")?;
+        w.write_all(&json)?;
+
+        // The binary chunk.
+        w.write_all(&(buffer.len() as u32).to_le_bytes())?;
+        w.write_all(b"BIN\0")?;
+        w.write_all(&buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square pyramid, with its base and sides in different groups.
+    fn pyramid() -> TriangleMesh {
+        let mut mesh = TriangleMesh::new();
+        for v in [
+            [1.0, 1.0, 0.0],
+            [-1.0, 1.0, 0.0],
+            [-1.0, -1.0, 0.0],
+            [1.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ] {
+            mesh.push_vertex(v);
+        }
+
+        mesh.groups.push(MeshGroup {
+            name: "base".to_string(),
+            triangles: vec![[0, 2, 1], [0, 3, 2]],
+        });
+        mesh.groups.push(MeshGroup {
+            name: "sides".to_string(),
+            triangles: vec![[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]],
+        });
+        mesh.groups.push(MeshGroup::new("empty".to_string()));
+        mesh
+    }
+
+    /// Reads a little-endian `u32` at some position.
+    fn read_u32(bytes: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+    }
+
+    /// Reads a little-endian `f32` at some position.
+    fn read_f32(bytes: &[u8], pos: usize) -> f32 {
+        f32::from_bits(read_u32(bytes, pos))
+    }
+
+    /// Checks the size and layout of a binary STL file.
+    #[test]
+    fn binary_stl() {
+        let mut bytes = Vec::new();
+        pyramid().write_binary_stl("pyramid", &mut bytes).unwrap();
+
+        // An 80 byte header, the triangle count, and 50 bytes per triangle.
+        assert_eq!(bytes.len(), 80 + 4 + 6 * 50);
+        assert!(bytes.starts_with(b"Miratope export: pyramid"));
+        assert_eq!(read_u32(&bytes, 80), 6);
+
+        // The first triangle is the first one on the base, facing down.
+        let triangle: Vec<_> = (0..12).map(|k| read_f32(&bytes, 84 + 4 * k)).collect();
+        assert_eq!(triangle[..6], [0.0, 0.0, -1.0, 1.0, 1.0, 0.0]);
+        assert_eq!(bytes[84 + 48..84 + 50], [0, 0]);
+    }
+
+    /// Checks the layout of an ASCII STL file.
+    #[test]
+    fn ascii_stl() {
+        let mut bytes = Vec::new();
+        pyramid()
+            .write_ascii_stl("square pyramid", &mut bytes)
+            .unwrap();
+        let src = String::from_utf8(bytes).unwrap();
+
+        assert_eq!(src.lines().next(), Some("solid square_pyramid"));
+        assert_eq!(src.lines().last(), Some("endsolid square_pyramid"));
+        assert_eq!(src.matches("facet normal").count(), 6);
+        assert_eq!(src.matches("vertex").count(), 18);
+    }
+
+    /// Checks the layout of an OBJ file.
+    #[test]
+    fn obj() {
+        let mut bytes = Vec::new();
+        pyramid().write_obj("pyramid", &mut bytes).unwrap();
+        let src = String::from_utf8(bytes).unwrap();
+
+        assert_eq!(src.lines().filter(|line| line.starts_with("v ")).count(), 5);
+        assert_eq!(src.lines().filter(|line| line.starts_with("f ")).count(), 6);
+
+        // Empty groups are skipped, and indices start at 1.
+        let groups: Vec<_> = src.lines().filter(|line| line.starts_with("g ")).collect();
+        assert_eq!(groups, ["g base", "g sides"]);
+        assert!(src.lines().any(|line| line == "f 1 3 2"));
+    }
+
+    /// Checks the size and layout of a binary glTF file.
+    #[test]
+    fn glb() {
+        let mut bytes = Vec::new();
+        pyramid().write_glb("pyramid", &mut bytes).unwrap();
+
+        // The header.
+        assert_eq!(&bytes[..4], b"glTF");
+        assert_eq!(read_u32(&bytes, 4), 2);
+        assert_eq!(read_u32(&bytes, 8) as usize, bytes.len());
+
+        // The JSON chunk, padded to a multiple of 4.
+        let json_len = read_u32(&bytes, 12) as usize;
+        assert_eq!(json_len % 4, 0);
+        assert_eq!(&bytes[16..20], b"JSON");
+        let json = std::str::from_utf8(&bytes[20..20 + json_len]).unwrap();
+        assert!(json.contains("\"byteLength\":60,"));
+        assert!(json.contains("\"byteOffset\":60,\"byteLength\":72,"));
+        assert!(json.contains("\"nodes\":[0,1]"));
+        assert!(!json.contains("empty"));
+
+        // The binary chunk, with 5 vertices and 6 triangles.
+        let bin = 20 + json_len;
+        assert_eq!(read_u32(&bytes, bin), 5 * 12 + 6 * 12);
+        assert_eq!(&bytes[bin + 4..bin + 8], b"BIN\0");
+        assert_eq!(bytes.len(), bin + 8 + 5 * 12 + 6 * 12);
+        assert_eq!(read_f32(&bytes, bin + 8 + 4 * 12 + 8), 1.0);
+        assert_eq!(read_u32(&bytes, bin + 8 + 5 * 12 + 4), 2);
+    }
+
+    /// Empty meshes aren't written in any format.
+    #[test]
+    fn empty() {
+        let mut mesh = TriangleMesh::new();
+        mesh.push_vertex([0.0; 3]);
+        mesh.groups.push(MeshGroup::new("empty".to_string()));
+
+        let mut bytes = Vec::new();
+        assert!(mesh.write_binary_stl("empty", &mut bytes).is_err());
+        assert!(mesh.write_ascii_stl("empty", &mut bytes).is_err());
+        assert!(mesh.write_obj("empty", &mut bytes).is_err());
+        assert!(mesh.write_glb("empty", &mut bytes).is_err());
+        assert!(bytes.is_empty());
+    }
+
+    /// Cylinders and spheres are closed, so that they can be printed.
+    #[test]
+    fn wireframe() {
+        let mut mesh = TriangleMesh::new();
+        let mut group = MeshGroup::new("wireframe".to_string());
+        mesh.push_cylinder(&mut group, [0.0; 3], [1.0, 0.0, 0.0], 0.1, 6);
+        mesh.push_sphere(&mut group, [0.0; 3], 0.1, 6);
+
+        // A closed triangle mesh has three halves of edges per triangle, each
+        // of which is paired with the opposite half.
+        let mut halves = std::collections::HashSet::new();
+        for [a, b, c] in &group.triangles {
+            for edge in [(a, b), (b, c), (c, a)] {
+                assert!(halves.insert(edge));
+            }
+        }
+        for &(a, b) in &halves {
+            assert!(halves.contains(&(b, a)));
+        }
+
+        // The cylinder has 2 rings of 6 vertices, the sphere 2 poles and 2
+        // parallels of 6 vertices.
+        assert_eq!(mesh.vertices.len(), 12 + 14);
+    }
+}
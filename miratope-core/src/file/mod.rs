@@ -1,6 +1,8 @@
 //! Reading from and writing to files in various different formats.
 
 pub mod ggb;
+pub mod jenn;
+pub mod mesh;
 pub mod off;
 
 use self::{
@@ -13,7 +15,12 @@ use off::OffParseError;
 use zip::result::ZipError;
 
 pub use std::io::Error as IoError;
-use std::{fs::File, string::FromUtf8Error, fmt::Display};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader},
+    string::FromUtf8Error,
+};
 
 /// Any error encountered while trying to load a polytope.
 #[derive(Debug)]
@@ -93,11 +100,26 @@ pub type FileResult<'a, T> = Result<T, FileError<'a>>;
 
 /// A trait for polytopes that can be read from an OFF file or a GGB file.
 pub trait FromFile: Sized {
+    /// Reads an OFF file into a new struct of type `Self`. The file is read a
+    /// chunk at a time, so it doesn't need to fit in memory.
+    fn from_off_reader<R: BufRead>(reader: R) -> OffParseResult<Self>;
+
+    /// Reads an OFF file into a new struct of type `Self`, checking that it
+    /// describes a valid polytope. Any error is reported together with the
+    /// line in the OFF file that caused it.
+    fn from_off_reader_checked<R: BufRead>(reader: R) -> OffParseResult<Self>;
+
     /// Converts an OFF file into a new struct of type `Self`.
-    ///
-    /// # Todo
-    /// Maybe don't load the entire file at once?
-    fn from_off(src: &str) -> OffParseResult<Self>;
+    fn from_off(src: &str) -> OffParseResult<Self> {
+        Self::from_off_reader(src.as_bytes())
+    }
+
+    /// Converts an OFF file into a new struct of type `Self`, checking that it
+    /// describes a valid polytope. Any error is reported together with the
+    /// line in the OFF file that caused it.
+    fn from_off_checked(src: &str) -> OffParseResult<Self> {
+        Self::from_off_reader_checked(src.as_bytes())
+    }
 
     /// Attempts to read a GGB file. If succesful, outputs a polytope in at most
     /// 3D.
@@ -105,6 +127,12 @@ pub trait FromFile: Sized {
 
     /// Loads a polytope from a file path.
     fn from_path<U: AsRef<std::path::Path>>(fp: &U) -> FileResult<'_, Self> {
+        Self::from_path_with(fp, false)
+    }
+
+    /// Loads a polytope from a file path. If `checked` is set, OFF files are
+    /// checked to describe valid polytopes.
+    fn from_path_with<U: AsRef<std::path::Path>>(fp: &U, checked: bool) -> FileResult<'_, Self> {
         use std::{ffi::OsStr, fs};
 
         let ext = fp
@@ -115,10 +143,15 @@ pub trait FromFile: Sized {
 
         match ext {
             // Reads the file as an OFF file.
-            "off" => match String::from_utf8(fs::read(fp)?) {
-                Ok(src) => Ok(Self::from_off(&src)?),
-                Err(err) => Err(err.into()),
-            },
+            "off" => {
+                let reader = BufReader::new(File::open(fp)?);
+
+                Ok(if checked {
+                    Self::from_off_reader_checked(reader)?
+                } else {
+                    Self::from_off_reader(reader)?
+                })
+            }
 
             // Reads the file as a GGB file.
             "ggb" => Ok(Self::from_ggb(fs::File::open(fp)?)?),
@@ -130,8 +163,12 @@ pub trait FromFile: Sized {
 }
 
 impl FromFile for Concrete {
-    fn from_off(src: &str) -> OffParseResult<Self> {
-        OffReader::new(src).build()
+    fn from_off_reader<R: BufRead>(reader: R) -> OffParseResult<Self> {
+        OffReader::new(reader).build()
+    }
+
+    fn from_off_reader_checked<R: BufRead>(reader: R) -> OffParseResult<Self> {
+        OffReader::new(reader).build_checked(Default::default())
     }
 
     /// Attempts to read a GGB file. If succesful, outputs a polytope in at most
@@ -174,6 +211,11 @@ impl Position {
         self.row += 1;
         self.column = 0;
     }
+
+    /// Returns the line number of the position, starting from 1.
+    pub fn line(&self) -> u32 {
+        self.row + 1
+    }
 }
 
 impl Display for Position {
@@ -1,17 +1,32 @@
 //! The code that opens an OFF file and parses it into a polytope.
 //! Also the code that writes an OFF file of a polytope.
-
-use std::{collections::{HashMap, HashSet}, fmt::Display, io::Error as IoError, path::Path, str::FromStr};
+//!
+//! Every vertex and every element must be defined in a line of its own, as
+//! both Miratope and Stella4D write them. The only thing that may follow its
+//! values is an RGB or RGBA colour, which is ignored.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    io::{BufRead, Error as IoError, ErrorKind as IoErrorKind},
+    path::Path,
+    str::FromStr,
+};
 
 use super::Position;
 
 use crate::{
-    abs::{AbstractBuilder, Ranked, SubelementList, Subelements},
+    abs::{
+        AbstractBuilder, AbstractError, ElementMap, IncidenceType, Ranked, SubelementList,
+        Subelements, ValidityOptions,
+    },
     conc::{cycle::CycleList, Concrete},
     geometry::Point,
+    name::Name,
     Polytope, COMPONENTS, ELEMENT_NAMES,
 };
 
+use rayon::prelude::*;
 use vec_like::VecLike;
 
 /// The header for OFF files created with Miratope.
@@ -21,6 +36,24 @@ const HEADER: &str = concat!(
     " (https://github.com/galoomba1/miratope-rs)"
 );
 
+/// The colours given to the element types when writing colours into an OFF
+/// file, as RGB values between 0 and 1. If there's more element types than
+/// colours, they're reused.
+const PALETTE: [[f32; 3]; 12] = [
+    [0.9, 0.2, 0.2],
+    [0.95, 0.8, 0.2],
+    [0.2, 0.4, 0.9],
+    [0.2, 0.75, 0.3],
+    [0.95, 0.5, 0.1],
+    [0.6, 0.3, 0.8],
+    [0.2, 0.8, 0.8],
+    [0.9, 0.4, 0.7],
+    [0.6, 0.85, 0.2],
+    [0.55, 0.35, 0.2],
+    [0.5, 0.5, 0.5],
+    [0.95, 0.95, 0.95],
+];
+
 /// Any error encountered while parsing an OFF file.
 #[derive(Clone, Copy, Debug)]
 pub enum OffParseError {
@@ -30,6 +63,15 @@ pub enum OffParseError {
     /// The OFF file ended unexpectedly.
     UnexpectedEnding(Position),
 
+    /// A line ended before all of the values in it were read.
+    LineEnding(Position),
+
+    /// A line had more values than expected, which didn't form a colour.
+    TrailingValues(Position),
+
+    /// The OFF file couldn't be read, or it isn't valid UTF-8.
+    Io(Position, IoErrorKind),
+
     /// Could not parse a number.
     Parsing(Position),
 
@@ -38,6 +80,16 @@ pub enum OffParseError {
 
     /// Didn't find the OFF magic word.
     MagicWord(Position),
+
+    /// The OFF file doesn't describe a valid polytope.
+    Invalid {
+        /// The error found in the polytope.
+        error: AbstractError,
+
+        /// The line in the OFF file where the element at fault was defined,
+        /// if there is one.
+        line: Option<u32>,
+    },
 }
 
 impl Display for OffParseError {
@@ -45,9 +97,16 @@ impl Display for OffParseError {
         match self {
             Self::Empty => write!(f, "file is empty."),
             Self::UnexpectedEnding(pos) => write!(f, "file ended unexpectedly at {}", pos),
+            Self::LineEnding(pos) => write!(f, "line ended unexpectedly at {}", pos),
+            Self::TrailingValues(pos) => write!(f, "unexpected values at {}", pos),
+            Self::Io(pos, kind) => write!(f, "could not read file at {}: {}", pos, kind),
             Self::Parsing(pos) => write!(f, "could not parse number at {}", pos),
             Self::Rank(pos) => write!(f, "could not read rank at {}", pos),
             Self::MagicWord(pos) => write!(f, "no \"OFF\" detected at {}", pos),
+            Self::Invalid { error, line } => match line {
+                Some(line) => write!(f, "invalid polytope at line {}: {}", line, error),
+                None => write!(f, "invalid polytope: {}", error),
+            },
         }
     }
 }
@@ -65,16 +124,123 @@ fn element_name(rank: usize) -> String {
     }
 }
 
-/// The result of trying to read the next token from an OFF file.
-enum OffNext<'a> {
-    /// We've read a token from the OFF file. We don't directly store a
-    /// [`Token`], since at this point in the code we don't know the starting
-    /// position.
-    Token(&'a str),
+/// The number of lines that are read from an OFF file at once, before being
+/// parsed in parallel. This bounds the amount of the file that's kept in
+/// memory at any time.
+const CHUNK_SIZE: usize = 1 << 14;
+
+/// A line of an OFF file, with its comment removed. Every vertex and every
+/// element is defined in a single line, so lines can be parsed in parallel.
+struct Line {
+    /// The contents of the line before the comment, if any.
+    text: String,
+
+    /// The row of the line in the file.
+    row: u32,
+}
+
+impl Line {
+    /// Returns the line number, starting from 1.
+    fn line(&self) -> u32 {
+        self.row + 1
+    }
+
+    /// Returns the position in the file of a slice of the line.
+    fn position(&self, slice: &str) -> Position {
+        let offset = slice.as_ptr() as usize - self.text.as_ptr() as usize;
+
+        Position {
+            row: self.row,
+            column: self.text[..offset].chars().count() as u32,
+        }
+    }
+
+    /// Returns the error for a line that ended before all of its values were
+    /// read.
+    fn ending(&self) -> OffParseError {
+        let text = self.text.trim_end();
+        OffParseError::LineEnding(self.position(&text[text.len()..]))
+    }
+
+    /// Returns an iterator over the tokens of the line.
+    fn tokens(&self) -> impl Iterator<Item = Token<'_>> {
+        self.text
+            .split_whitespace()
+            .map(move |slice| Token { slice, line: self })
+    }
+
+    /// Reads the next token from an iterator over the tokens of the line, and
+    /// parses it.
+    fn parse_next<'a, T: FromStr, I: Iterator<Item = Token<'a>>>(
+        &self,
+        tokens: &mut I,
+    ) -> OffParseResult<T> {
+        tokens.next().ok_or_else(|| self.ending())?.parse()
+    }
+
+    /// Checks that the tokens left in the line, if any, form an RGB or RGBA
+    /// colour, i.e. that there's three or four of them and they're all
+    /// numbers. Anything else most likely means that the line holds more
+    /// than a single vertex or element, or that it's missing a line break.
+    fn color<'a, I: Iterator<Item = Token<'a>>>(&self, tokens: I) -> OffParseResult<()> {
+        let rest: Vec<_> = tokens.collect();
+        let is_color = matches!(rest.len(), 0 | 3 | 4)
+            && rest.iter().all(|token| token.parse::<f64>().is_ok());
+
+        match rest.first() {
+            Some(token) if !is_color => Err(OffParseError::TrailingValues(token.pos())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parses the coordinates of a vertex from the line. They may only be
+    /// followed by a colour, which is ignored.
+    fn vertex(&self, dim: usize) -> OffParseResult<Point<f64>> {
+        let mut tokens = self.tokens();
+        let mut v = Vec::with_capacity(dim);
 
-    /// We either read a single character from a comment, or a single whitespace
-    /// character. Either way, we don't want it.
-    Garbage,
+        for _ in 0..dim {
+            v.push(self.parse_next(&mut tokens)?);
+        }
+
+        self.color(tokens)?;
+        Ok(v.into())
+    }
+
+    /// Parses the indices of the subelements of an element from the line,
+    /// preceded by their number. They may only be followed by a colour, which
+    /// is ignored.
+    fn indices(&self) -> OffParseResult<Vec<usize>> {
+        let mut tokens = self.tokens();
+        let count = self.parse_next(&mut tokens)?;
+        let mut indices = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            indices.push(self.parse_next(&mut tokens)?);
+        }
+
+        self.color(tokens)?;
+        Ok(indices)
+    }
+
+    /// Parses the subelements of the element with a given rank and index
+    /// from the line, and checks that they're all less than `num_subs`.
+    fn subelements(&self, rank: usize, idx: usize, num_subs: usize) -> OffParseResult<Subelements> {
+        let subs = self.indices()?;
+
+        if let Some(&index) = subs.iter().find(|&&sub| sub >= num_subs) {
+            return Err(OffParseError::Invalid {
+                error: AbstractError::Index {
+                    el: (rank, idx),
+                    incidence_type: IncidenceType::Subelement,
+                    index,
+                },
+                line: Some(self.line()),
+            });
+        }
+
+        Ok(subs.into())
+    }
 }
 
 /// Represents a token, i.e. any value of importance, in an OFF file.
@@ -82,16 +248,22 @@ struct Token<'a> {
     /// The string slice containing our value of importance.
     slice: &'a str,
 
-    /// The starting position of the token.
-    pos: Position,
+    /// The line the token was found in.
+    line: &'a Line,
 }
 
 impl<'a> Token<'a> {
+    /// Returns the starting position of the token. This is only computed
+    /// when an error is found.
+    fn pos(&self) -> Position {
+        self.line.position(self.slice)
+    }
+
     /// Attempts to parse the token.
     fn parse<T: FromStr>(&self) -> OffParseResult<T> {
         self.slice
             .parse()
-            .map_err(|_| OffParseError::Parsing(self.pos))
+            .map_err(|_| OffParseError::Parsing(self.pos()))
     }
 
     /// Reads the rank from a token of the form `(-?\d+)?OFF`. If the rank is
@@ -100,166 +272,198 @@ impl<'a> Token<'a> {
         let rank = self
             .slice
             .strip_suffix("OFF")
-            .ok_or(OffParseError::MagicWord(self.pos))?;
+            .ok_or_else(|| OffParseError::MagicWord(self.pos()))?;
 
         if rank.is_empty() {
             Ok(4)
         } else {
             match rank.parse::<isize>() {
                 Ok(r) => Ok((r + 1) as usize),
-                Err(_) => Err(OffParseError::Rank(self.pos)),
+                Err(_) => Err(OffParseError::Rank(self.pos())),
             }
         }
     }
 }
 
-/// An iterator over the tokens in an OFF file. It excludes whitespace and
-/// comments. It also keeps track of position.
-struct TokenIter<'a> {
-    /// A reference to the source OFF file.
-    src: &'a str,
+/// An iterator over the lines of an OFF file that contain anything other than
+/// whitespace and comments. Only a single line is read from the underlying
+/// reader at a time.
+struct LineIter<R: BufRead> {
+    /// The underlying reader.
+    reader: R,
 
-    /// The inner iterator over the characters.
-    iter: std::str::CharIndices<'a>,
+    /// The first line of the file, if it was read ahead of time.
+    first: Option<String>,
 
-    /// Whether we're currently reading a comment.
-    comment: bool,
-
-    /// The row and column in the file.
-    position: Position,
+    /// The row of the next line to be read.
+    row: u32,
 }
 
-impl<'a> TokenIter<'a> {
-    /// Returns an iterator over the OFF file, with all whitespace and comments
-    /// removed.
-    fn new(src: &'a str) -> Self {
+impl<R: BufRead> LineIter<R> {
+    /// Initializes an iterator over the lines of a reader.
+    fn new(reader: R) -> Self {
         Self {
-            src,
-            iter: src.char_indices(),
-            comment: false,
-            position: Default::default(),
-        }
-    }
-
-    /// Attempts to get the next token from the file. Returns `None` if the
-    /// inner iterator has been exhausted.
-    fn try_next(&mut self) -> Option<OffNext<'a>> {
-        let (mut idx, mut c) = self.iter.next()?;
-        let init_idx = idx;
-        let mut end_idx = init_idx;
-
-        loop {
-            match c {
-                // The start of a comment.
-                '#' => {
-                    self.comment = true;
-                    self.position.next();
-                }
+            reader,
+            first: None,
+            row: 0,
+        }
+    }
 
-                // End lines also end comments.
-                '\n' => {
-                    self.comment = false;
-                    self.position.next_line();
-                }
+    /// Reads the next line from the reader, including any comment. Returns
+    /// `None` if the reader has been exhausted.
+    fn read_line(&mut self) -> OffParseResult<Option<String>> {
+        if let Some(first) = self.first.take() {
+            self.row += 1;
+            return Ok(Some(first));
+        }
 
-                // We just advance the position otherwise.
-                _ => self.position.next(),
+        let mut text = String::new();
+        match self.reader.read_line(&mut text) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.row += 1;
+                Ok(Some(text))
             }
+            Err(err) => Err(OffParseError::Io(self.end(), err.kind())),
+        }
+    }
 
-            // If we're in the middle of a comment, or we found a whitespace,
-            // then whatever token we were reading has ended.
-            if self.comment || c.is_whitespace() {
-                break;
-            }
+    /// Returns the first line of the file without its line break, reading it
+    /// ahead of time if needed. Returns `None` if it can't be read, or if
+    /// we're past it.
+    fn first_line(&mut self) -> Option<&str> {
+        if self.row != 0 {
+            return None;
+        }
 
-            // Advances the iterator.
-            end_idx = idx;
-            if let Some((new_idx, new_c)) = self.iter.next() {
-                idx = new_idx;
-                c = new_c;
-            } else {
-                // We do this so that it seems like we read something at the end.
-                idx += 1;
-                break;
-            }
+        if self.first.is_none() {
+            let mut text = String::new();
+            self.reader.read_line(&mut text).ok()?;
+            self.first = Some(text);
         }
 
-        // If we immediately broke out of the loop, this means we just read a
-        // single character in a comment or a whitespace. That is, garbage.
-        Some(if init_idx == idx {
-            OffNext::Garbage
-        } else {
-            OffNext::Token(&self.src[init_idx..=end_idx])
-        })
+        self.first
+            .as_deref()
+            .map(|text| text.trim_end_matches(&['\n', '\r'][..]))
     }
 
-    /// Reads and parses the next token from the OFF file.
-    pub fn parse_next<U: FromStr>(&mut self) -> OffParseResult<U> {
-        self.next()
-            .ok_or(OffParseError::UnexpectedEnding(self.position))?
-            .parse()
+    /// Returns the position right after the last line that was read.
+    fn end(&self) -> Position {
+        Position {
+            row: self.row,
+            column: 0,
+        }
     }
-}
 
-impl<'a> Iterator for TokenIter<'a> {
-    type Item = Token<'a>;
+    /// Reads the next line that contains anything other than whitespace and
+    /// comments, and removes its comment. Returns `None` if the reader has
+    /// been exhausted.
+    fn next_line(&mut self) -> OffParseResult<Option<Line>> {
+        while let Some(mut text) = self.read_line()? {
+            if let Some(idx) = text.find('#') {
+                text.truncate(idx);
+            }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            let pos = self.position;
-            if let OffNext::Token(slice) = self.try_next()? {
-                return Some(Token { slice, pos });
+            if !text.trim().is_empty() {
+                return Ok(Some(Line {
+                    text,
+                    row: self.row - 1,
+                }));
             }
         }
+
+        Ok(None)
+    }
+
+    /// Reads the next `count` lines that contain anything other than
+    /// whitespace and comments.
+    fn chunk(&mut self, count: usize) -> OffParseResult<Vec<Line>> {
+        let mut lines = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let line = self
+                .next_line()?
+                .ok_or_else(|| OffParseError::UnexpectedEnding(self.end()))?;
+            lines.push(line);
+        }
+
+        Ok(lines)
     }
 }
 
+/// Parses the tokens from an iterator as numbers, until a list has a given
+/// length.
+fn parse_into<'a, I: Iterator<Item = Token<'a>>>(
+    tokens: I,
+    len: usize,
+    list: &mut Vec<usize>,
+) -> OffParseResult<()> {
+    for token in tokens.take(len - list.len()) {
+        list.push(token.parse()?);
+    }
+
+    Ok(())
+}
+
 /// An auxiliary struct that reads through an OFF file and builds a concrete
 /// polytope out of it.
-pub struct OffReader<'a> {
-    /// An iterator over the tokens of the OFF file.
-    iter: TokenIter<'a>,
+///
+/// The file is read from any [`BufRead`] a chunk of lines at a time, so that
+/// huge files don't have to be loaded into memory at once. The lines in each
+/// chunk are parsed in parallel.
+pub struct OffReader<R: BufRead> {
+    /// An iterator over the lines of the OFF file.
+    iter: LineIter<R>,
 
     /// The underlying abstract polytope.
     abs: AbstractBuilder,
+
+    /// The line in the OFF file where each element was defined, indexed
+    /// like the elements of the polytope. Edges are assigned the line of the
+    /// first face they were found in. The minimal and maximal elements, which
+    /// aren't explicitly defined, are given no lines.
+    lines: Vec<Vec<u32>>,
 }
 
-impl<'a> OffReader<'a> {
-    /// Initializes a new reader from a source OFF file.
-    pub fn new(src: &'a str) -> Self {
+impl<R: BufRead> OffReader<R> {
+    /// Initializes a new reader from anything that reads an OFF file, like a
+    /// [`BufReader`](std::io::BufReader) over a file.
+    pub fn new(reader: R) -> Self {
         Self {
-            iter: TokenIter::new(src),
+            iter: LineIter::new(reader),
             abs: AbstractBuilder::new(),
+            lines: Vec::new(),
         }
     }
 
-    /// Returns a reference to the underlying OFF file.
-    pub fn src(&self) -> &'a str {
-        self.iter.src
-    }
-
-    /// Advances the underlying iterator.
-    fn next(&mut self) -> Option<Token<'a>> {
-        self.iter.next()
-    }
-
-    /// Reads the first token from the OFF file, returns the polytope's rank.
-    fn rank(&mut self) -> OffParseResult<usize> {
-        self.next().ok_or(OffParseError::Empty)?.rank()
-    }
-
-    /// Gets the number of elements from the OFF file from rank 1 up to rank
+    /// Reads the header of the OFF file, and returns the polytope's rank
+    /// together with the number of elements from rank 1 up to rank
     /// max(3, dim - 1). For the purposes of the OFF format, the 2-elements of
     /// a polygon are taken to be its components.
     ///
-    /// This function ought to only be called when the rank is at least 2.
-    fn el_nums(&mut self, rank: usize) -> OffParseResult<Vec<usize>> {
-        debug_assert!(rank >= 2);
-        let mut el_nums = Vec::with_capacity(rank - 1);
+    /// The element numbers may be split over several lines. Anything after
+    /// them in their last line is ignored.
+    fn header(&mut self) -> OffParseResult<(usize, Vec<usize>)> {
+        let first = self.iter.next_line()?.ok_or(OffParseError::Empty)?;
+        let mut tokens = first.tokens();
+
+        // The line isn't empty, so it has some token.
+        let rank = tokens.next().unwrap().rank()?;
+        if rank < 3 {
+            return Ok((rank, Vec::new()));
+        }
 
         // Reads entries one by one.
-        for _ in 1..rank {
-            el_nums.push(self.iter.parse_next()?);
+        let len = rank - 1;
+        let mut el_nums = Vec::with_capacity(len + 1);
+        parse_into(tokens, len, &mut el_nums)?;
+
+        while el_nums.len() < len {
+            let line = self
+                .iter
+                .next_line()?
+                .ok_or_else(|| OffParseError::UnexpectedEnding(self.iter.end()))?;
+            parse_into(line.tokens(), len, &mut el_nums)?;
         }
 
         // A polygon always has as many vertices as edges.
@@ -270,88 +474,106 @@ impl<'a> OffReader<'a> {
         // 2-elements go before 1-elements, we're undoing that.
         el_nums.swap(1, 2);
 
-        Ok(el_nums)
+        Ok((rank, el_nums))
     }
 
     /// Parses all vertex coordinates from the OFF file.
-    fn parse_vertices(
-        &mut self,
-        count: usize,
-        dim: usize,
-    ) -> OffParseResult<Vec<Point<f64>>> {
-        // Reads all vertices.
+    fn parse_vertices(&mut self, count: usize, dim: usize) -> OffParseResult<Vec<Point<f64>>> {
         let mut vertices = Vec::with_capacity(count);
+        let mut lines = Vec::with_capacity(count);
 
-        // Add each vertex to the vector.
-        for _ in 0..count {
-            let mut v = Vec::with_capacity(dim);
+        while vertices.len() < count {
+            let chunk = self.iter.chunk(CHUNK_SIZE.min(count - vertices.len()))?;
+            let parsed: Vec<_> = chunk.par_iter().map(|line| line.vertex(dim)).collect();
 
-            for _ in 0..dim {
-                v.push(self.iter.parse_next()?);
+            for (line, vertex) in chunk.iter().zip(parsed) {
+                vertices.push(vertex?);
+                lines.push(line.line());
             }
-
-            vertices.push(v.into());
         }
 
+        self.lines.push(lines);
+
         Ok(vertices)
     }
 
     /// Reads the faces from the OFF file and gets the edges and faces from
     /// them. Since the OFF file doesn't store edges explicitly, this is harder
     /// than reading general elements.
+    ///
+    /// The faces are parsed in parallel, but the edges have to be found one
+    /// face at a time, in order.
     fn parse_edges_and_faces(
         &mut self,
         rank: usize,
         num_edges: usize,
         num_faces: usize,
     ) -> OffParseResult<(SubelementList, SubelementList)> {
+        let num_vertices = self.lines[0].len();
         let mut edges = SubelementList::with_capacity(num_edges);
         let mut faces = SubelementList::with_capacity(num_faces);
         let mut hash_edges = HashMap::new();
-
-        // Add each face to the element list.
-        for _ in 0..num_faces {
-            let face_sub_num = self.iter.parse_next()?;
-            let mut face = Subelements::new();
-            let mut face_verts = Vec::with_capacity(face_sub_num + 1);
-
-            // Reads all vertices of the face.
-            for _ in 0..face_sub_num {
-                face_verts.push(self.iter.parse_next()?);
-            }
-
-            // We add the first vertex to the end for simplicity.
-            face_verts.push(face_verts[0]);
-
-            // Gets all edges of the face.
-            for i in 0..face_sub_num {
-                let mut v0 = face_verts[i];
-                let mut v1 = face_verts[i + 1];
-
-                if v0 > v1 {
-                    std::mem::swap(&mut v0, &mut v1);
+        let mut edge_lines = Vec::with_capacity(num_edges);
+        let mut face_lines = Vec::with_capacity(num_faces);
+        let mut read = 0;
+
+        while read < num_faces {
+            let chunk = self.iter.chunk(CHUNK_SIZE.min(num_faces - read))?;
+            let parsed: Vec<_> = chunk.par_iter().map(Line::indices).collect();
+            read += chunk.len();
+
+            // Add each face to the element list.
+            for (i, (line, face_verts)) in chunk.iter().zip(parsed).enumerate() {
+                let mut face_verts = face_verts?;
+                let line = line.line();
+                let face_sub_num = face_verts.len();
+                let mut face = Subelements::with_capacity(face_sub_num);
+
+                // The edges through an invalid vertex would be invalid, so we
+                // blame the face instead.
+                if let Some(&v) = face_verts.iter().find(|&&v| v >= num_vertices) {
+                    return Err(OffParseError::Invalid {
+                        error: AbstractError::Index {
+                            el: (3, read - chunk.len() + i),
+                            incidence_type: IncidenceType::Subelement,
+                            index: v,
+                        },
+                        line: Some(line),
+                    });
                 }
 
-                let edge: Subelements = vec![v0, v1].into();
-
-                if let Some(idx) = hash_edges.get(&edge) {
-                    face.push(*idx);
-                } else {
-                    hash_edges.insert(edge.clone(), edges.len());
-                    face.push(edges.len());
-                    edges.push(edge);
+                // We add the first vertex to the end for simplicity.
+                if let Some(&v) = face_verts.first() {
+                    face_verts.push(v);
                 }
-            }
 
-            // If these are truly faces and not just components, we add them.
-            // Hopefully the compiler can optimize this better, I'm lazy.
-            if rank != 3 {
-                faces.push(face);
-            }
+                // Gets all edges of the face.
+                for i in 0..face_sub_num {
+                    let mut v0 = face_verts[i];
+                    let mut v1 = face_verts[i + 1];
+
+                    if v0 > v1 {
+                        std::mem::swap(&mut v0, &mut v1);
+                    }
+
+                    let edge: Subelements = vec![v0, v1].into();
+
+                    if let Some(idx) = hash_edges.get(&edge) {
+                        face.push(*idx);
+                    } else {
+                        hash_edges.insert(edge.clone(), edges.len());
+                        face.push(edges.len());
+                        edges.push(edge);
+                        edge_lines.push(line);
+                    }
+                }
 
-            // Goes to the end of the line in order to ignore things like colour info.
-            if self.iter.position.column != 0 {
-                self.iter.comment = true;
+                // If these are truly faces and not just components, we add them.
+                // Hopefully the compiler can optimize this better, I'm lazy.
+                if rank != 3 {
+                    faces.push(face);
+                    face_lines.push(line);
+                }
             }
         }
 
@@ -366,48 +588,89 @@ impl<'a> OffReader<'a> {
             println!("WARNING: Edge count doesn't match expected edge count!");
         }
 
+        self.lines.push(edge_lines);
+        self.lines.push(face_lines);
         Ok((edges, faces))
     }
 
     /// Parses the next set of d-elements from the OFF file.
     fn parse_els(&mut self, num_el: usize) -> OffParseResult<SubelementList> {
+        let rank = self.lines.len() + 1;
+        let num_subs = self.lines[rank - 2].len();
         let mut els_subs = SubelementList::with_capacity(num_el);
+        let mut lines = Vec::with_capacity(num_el);
 
         // Adds every d-element to the element list.
-        for _ in 0..num_el {
-            let el_sub_num = self.iter.parse_next()?;
-            let mut subs = Subelements::with_capacity(el_sub_num);
-
-            // Reads all sub-elements of the d-element.
-            for _ in 0..el_sub_num {
-                subs.push(self.iter.parse_next()?);
-            }
-
-            els_subs.push(subs);
-
-            // Goes to the end of the line in order to ignore things like colour info.
-            if self.iter.position.column != 0 {
-                self.iter.comment = true;
+        while els_subs.len() < num_el {
+            let read = els_subs.len();
+            let chunk = self.iter.chunk(CHUNK_SIZE.min(num_el - read))?;
+            let parsed: Vec<_> = chunk
+                .par_iter()
+                .enumerate()
+                .map(|(idx, line)| line.subelements(rank, read + idx, num_subs))
+                .collect();
+
+            for (line, subs) in chunk.iter().zip(parsed) {
+                els_subs.push(subs?);
+                lines.push(line.line());
             }
         }
 
+        self.lines.push(lines);
         Ok(els_subs)
     }
 
-    /*
-    /// Returns the [`Name`] stored in the OFF file, if any.
-    fn name(&self) -> Option<Name<Con>> {
-        self.src()
-            .lines()
-            .next()
-            .map(Concrete::name_from_src)
-            .flatten()
-    }*/
+    /// Returns the line in the OFF file where an element was defined, if
+    /// there is one.
+    fn line(&self, rank: usize, idx: usize) -> Option<u32> {
+        self.lines.get(rank.checked_sub(1)?)?.get(idx).copied()
+    }
+
+    /// Returns the line in the OFF file that's most relevant to an error in
+    /// the polytope.
+    fn error_line(&self, error: &AbstractError) -> Option<u32> {
+        match *error {
+            AbstractError::Bounded { .. } => None,
+            AbstractError::Index { el, .. }
+            | AbstractError::Consistency { el, .. }
+            | AbstractError::Ranked { el, .. } => self.line(el.0, el.1),
+            AbstractError::Dyadic { section, .. } | AbstractError::Connected(section) => self
+                .line(section.hi_rank, section.hi_idx)
+                .or_else(|| self.line(section.lo_rank, section.lo_idx)),
+        }
+    }
+
+    /// Returns the [`Name`] stored in the OFF file, if any. This reads the
+    /// first line of the file ahead of time, so it must be called before the
+    /// polytope is built.
+    pub fn name(&mut self) -> Option<Name> {
+        self.iter.first_line().and_then(Concrete::name_from_src)
+    }
 
     /// Builds a concrete polytope from the OFF reader.
-    pub fn build(mut self) -> OffParseResult<Concrete> {
-        // Reads the rank of the polytope.
-        let rank = self.rank()?;
+    ///
+    /// Other than making sure that every index refers to an existing element,
+    /// this doesn't check that the OFF file actually describes a valid
+    /// polytope. Use [`Self::build_checked`] for that. Methods called on an
+    /// invalid polytope won't index out of bounds, but their results might
+    /// not make sense.
+    pub fn build(self) -> OffParseResult<Concrete> {
+        self.read(None)
+    }
+
+    /// Builds a concrete polytope from the OFF reader, and checks that it's
+    /// [valid](crate::abs::Ranks::is_valid_with), together with any of the
+    /// optional checks that are set. Any error in the polytope is returned
+    /// together with the line of the OFF file that produced it.
+    pub fn build_checked(self, options: ValidityOptions) -> OffParseResult<Concrete> {
+        self.read(Some(options))
+    }
+
+    /// Reads the OFF file and builds a concrete polytope out of it. If `check`
+    /// is set, the polytope is validated beforehand with the given options.
+    fn read(mut self, check: Option<ValidityOptions>) -> OffParseResult<Concrete> {
+        // Reads the rank of the polytope and the element numbers.
+        let (rank, num_elems) = self.header()?;
 
         // Deals with dumb degenerate cases.
         match rank {
@@ -417,8 +680,7 @@ impl<'a> OffReader<'a> {
             _ => {}
         }
 
-        // Reads the element numbers and vertices.
-        let num_elems = self.el_nums(rank)?;
+        // Reads the vertices.
         let vertices = self.parse_vertices(num_elems[0], rank - 1)?;
 
         // Adds nullitope and vertices.
@@ -444,22 +706,34 @@ impl<'a> OffReader<'a> {
             self.abs.push_max();
         }
 
+        // Checks the polytope if necessary.
+        if let Some(options) = check {
+            if let Err(error) = self.abs.ranks().is_valid_with(options) {
+                return Err(OffParseError::Invalid {
+                    line: self.error_line(&error),
+                    error,
+                });
+            }
+        }
+
         // Builds the concrete polytope.
 
-        // Safety: TODO this isn't actually safe. We need to do some checking.
+        // Safety: every subelement index has been checked to refer to an
+        // existing element of the previous rank. Unless `check` is set, the
+        // polytope might be invalid in other ways, which the caller of
+        // `build` has opted into.
         Ok(Concrete::new(vertices, unsafe { self.abs.build() }))
     }
 }
 
-/*
 impl Concrete {
     /// Gets the name from the first line of an OFF file.
-    fn name_from_src(first_line: &str) -> Option<Name<Con>> {
+    fn name_from_src(first_line: &str) -> Option<Name> {
         let mut fl_iter = first_line.char_indices();
 
         if let Some((_, '#')) = fl_iter.next() {
             let (idx, _) = fl_iter.next()?;
-            if let Ok(new_name) = ron::from_str(&first_line[idx..]) {
+            if let Ok(new_name) = Name::from_ron(&first_line[idx..]) {
                 return Some(new_name);
             }
         }
@@ -469,26 +743,53 @@ impl Concrete {
 
     /// Gets the name from an OFF file, assuming it's stored in RON in the first
     /// line of the file.
-    pub fn name_from_off<T: AsRef<Path>>(path: T) -> Option<Name<Con>> {
-        use std::io::{BufRead, BufReader};
+    pub fn name_from_off<T: AsRef<Path>>(path: T) -> Option<Name> {
+        use std::{
+            fs,
+            io::{BufRead, BufReader},
+        };
 
         let file = BufReader::new(fs::File::open(path).ok()?);
         let first_line = file.lines().next()?.ok()?;
 
         Self::name_from_src(&first_line)
     }
-}*/
+}
 
 /// A set of options to be used when saving the OFF file.
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct OffOptions {
     /// Whether the OFF file should have comments specifying each face type.
     pub comments: bool,
+
+    /// The name of the polytope, to be stored in the first line of the file.
+    pub name: Option<Name>,
+
+    /// Whether faces and higher elements should be followed by RGB colours,
+    /// one for each element type. This is the dialect written and read by
+    /// Stella4D, whose 4OFF files otherwise match ours.
+    pub colors: bool,
 }
 
 impl Default for OffOptions {
     fn default() -> Self {
-        OffOptions { comments: true }
+        OffOptions {
+            comments: true,
+            name: None,
+            colors: false,
+        }
+    }
+}
+
+impl OffOptions {
+    /// The options for writing an OFF file that Stella4D can read, with the
+    /// elements coloured by type.
+    pub fn stella(name: Option<Name>) -> Self {
+        Self {
+            name,
+            colors: true,
+            ..Default::default()
+        }
     }
 }
 
@@ -543,16 +844,22 @@ pub struct OffWriter<'a> {
 
     /// Options for the text output.
     options: OffOptions,
+
+    /// The type of every element, if colours are written.
+    types: Option<ElementMap<usize>>,
 }
 
 impl<'a> OffWriter<'a> {
     /// Initializes a new OFF writer from a polytope, with a given set of
     /// options.
     pub fn new(poly: &'a Concrete, options: OffOptions) -> Self {
+        let types = (options.colors && poly.rank() > 3).then(|| poly.types_of_elements());
+
         Self {
             off: String::new(),
             poly,
             options,
+            types,
         }
     }
 
@@ -586,6 +893,16 @@ impl<'a> OffWriter<'a> {
         self.push_str(data.to_string())
     }
 
+    /// Writes the colour of an element at the end of its line, if colours are
+    /// written.
+    fn write_color(&mut self, rank: usize, idx: usize) {
+        if let Some(types) = &self.types {
+            let color = PALETTE[types[(rank, idx)] % PALETTE.len()];
+            let color = format!("  {} {} {}", color[0], color[1], color[2]);
+            self.push_str(color);
+        }
+    }
+
     /// Writes the OFF format header.
     fn write_rank(&mut self) {
         let rank = self.rank();
@@ -737,6 +1054,7 @@ impl<'a> OffWriter<'a> {
                     self.push(' ');
                     self.push_to_str(v);
                 }
+                self.write_color(3, idx);
                 self.push('\n');
             }
         }
@@ -754,7 +1072,7 @@ impl<'a> OffWriter<'a> {
         }
 
         // Adds the elements' indices.
-        for el in &self.poly[rank] {
+        for (idx, el) in self.poly[rank].iter().enumerate() {
             let subs = &el.subs;
             self.push_to_str(subs.len());
 
@@ -763,6 +1081,7 @@ impl<'a> OffWriter<'a> {
                 self.push_to_str(sub);
             }
 
+            self.write_color(rank, idx);
             self.push('\n');
         }
     }
@@ -772,10 +1091,11 @@ impl<'a> OffWriter<'a> {
         let rank = self.poly.rank();
 
         // Serialized name.
-        /* self.off.push_str("# ");
-        self.off
-            .push_str(&ron::to_string(&self.polytope.name).unwrap_or_default());
-        self.off.push('\n'); */
+        if let Some(name) = self.options.name.as_ref().and_then(|name| name.to_ron().ok()) {
+            self.push_str("# ");
+            self.push_str(&name);
+            self.push('\n');
+        }
 
         // Blatant advertising.
         if self.comments() {
@@ -878,6 +1198,9 @@ mod tests {
         let poly = Concrete::from_off(src).expect("OFF file could not be loaded.");
         test(&poly, element_counts.clone());
 
+        // Checks that the polytope is valid.
+        Concrete::from_off_checked(src).expect("OFF file is not valid.");
+
         // Checks that the polytope can be reloaded correctly.
         const ERR: &str = "OFF file could not be reloaded.";
         test(
@@ -973,4 +1296,203 @@ mod tests {
     fn parse() {
         unwrap_off("OFF\n10 foo bar")
     }
+
+    /// A vertex with too few coordinates should fail at the end of its line.
+    #[test]
+    #[should_panic(expected = "LineEnding(Position { row: 3, column: 3 })")]
+    fn line_ending() {
+        unwrap_off("OFF\n3 1 0\n0 0 0\n1 0 \n0 1 0\n3 0 1 2")
+    }
+
+    /// A file that isn't valid UTF-8 should fail.
+    #[test]
+    #[should_panic(expected = "Io(Position { row: 2, column: 0 }, InvalidData)")]
+    fn invalid_utf8() {
+        Concrete::from_off_reader(&b"OFF\n3 1 0\n\xff\xfe"[..]).unwrap();
+    }
+
+    /// The element numbers may be split over several lines.
+    #[test]
+    fn split_header() {
+        let src = "OFF 4\n# faces\n4\n6\n\
+            1 1 1\n1 -1 -1\n-1 1 -1\n-1 -1 1\n\
+            3 0 1 2\n3 0 3 1\n3 0 2 3\n3 1 3 2";
+        test(&Concrete::from_off_checked(src).unwrap(), [1, 4, 6, 4, 1]);
+    }
+
+    /// Files with more lines than fit in a chunk can be read through any
+    /// buffered reader.
+    #[test]
+    fn streaming() {
+        use std::io::BufReader;
+
+        let poly = Concrete::polygon(100).duoprism(&Concrete::polygon(200));
+        let off = poly.to_off(Default::default()).unwrap();
+        let reader = BufReader::with_capacity(64, off.as_bytes());
+
+        test(
+            &Concrete::from_off_reader(reader).unwrap(),
+            [1, 20000, 40000, 20300, 300, 1],
+        );
+    }
+
+    /// Attempts to parse an OFF file with checking, returns the error and the
+    /// line it was found in.
+    fn invalid_off(src: &str) -> (AbstractError, Option<u32>) {
+        match Concrete::from_off_checked(src) {
+            Err(OffParseError::Invalid { error, line }) => (error, line),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("invalid OFF file was loaded"),
+        }
+    }
+
+    /// A face with a non-existent vertex should fail on its line.
+    #[test]
+    fn invalid_vertex() {
+        let (error, line) = invalid_off("OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3");
+        assert!(matches!(
+            error,
+            AbstractError::Index {
+                el: (3, 0),
+                index: 3,
+                ..
+            }
+        ));
+        assert_eq!(line, Some(6));
+
+        // Unchecked loading should also fail.
+        assert!(Concrete::from_off("OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3").is_err());
+
+        // The components of a polygon are blamed by their index.
+        let (error, line) = invalid_off("2OFF\n4 2\n0 0\n1 0\n1 1\n0 1\n2 0 1\n2 2 4");
+        assert!(matches!(
+            error,
+            AbstractError::Index {
+                el: (3, 1),
+                index: 4,
+                ..
+            }
+        ));
+        assert_eq!(line, Some(8));
+    }
+
+    /// A cell with a non-existent face should fail on its line.
+    #[test]
+    fn invalid_face() {
+        let (error, line) = invalid_off(
+            "4OFF\n4 1 0 1\n0 0 0 0\n1 0 0 0\n0 1 0 0\n0 0 1 0\n3 0 1 2\n1 1",
+        );
+        assert!(matches!(
+            error,
+            AbstractError::Index {
+                el: (4, 0),
+                index: 1,
+                ..
+            }
+        ));
+        assert_eq!(line, Some(8));
+    }
+
+    /// Two triangles sharing an edge don't form a dyadic polytope.
+    #[test]
+    fn non_dyadic() {
+        let (error, line) = invalid_off(
+            "OFF\n4 2 0\n0 0 0\n1 0 0\n0 1 0\n0 0 1\n# faces\n3 0 1 2\n3 1 2 3",
+        );
+        assert!(matches!(error, AbstractError::Dyadic { .. }));
+        assert!(matches!(line, Some(8) | Some(9)));
+    }
+
+    /// Compounds are valid, even if they're not strongly connected.
+    #[test]
+    fn compound() {
+        let src = include_str!("so.off");
+        Concrete::from_off_checked(src).unwrap();
+        assert!(matches!(
+            OffReader::new(src.as_bytes()).build_checked(ValidityOptions::strongly_connected()),
+            Err(OffParseError::Invalid {
+                error: AbstractError::Connected(_),
+                line: None,
+            })
+        ));
+    }
+
+    /// Names are stored in the first line of the OFF file.
+    #[test]
+    fn name() {
+        let name = Name::polygon(3).prism();
+        let off = Concrete::polygon(3)
+            .prism()
+            .to_off(OffOptions {
+                name: Some(name.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(OffReader::new(off.as_bytes()).name(), Some(name));
+        test(&Concrete::from_off(&off).unwrap(), [1, 6, 9, 5, 1]);
+
+        // Files without names, or with other comments, have no names.
+        assert_eq!(OffReader::new(include_str!("tet.off").as_bytes()).name(), None);
+        assert_eq!(OffReader::new("# foo\nOFF".as_bytes()).name(), None);
+    }
+
+    /// Stella4D's files have colours after faces, cells and possibly vertices.
+    #[test]
+    fn stella() {
+        let tesseract = Concrete::hypercube(5);
+        let off = tesseract.to_off(OffOptions::stella(None)).unwrap();
+
+        // All faces and cells have the same type, so they get the first colour.
+        assert_eq!(off.matches("  0.9 0.2 0.2").count(), 24 + 8);
+        test(&Concrete::from_off_checked(&off).unwrap(), [1, 16, 32, 24, 8, 1]);
+
+        // Prisms have two types of faces.
+        let off = Concrete::polygon(5).prism().to_off(OffOptions::stella(None)).unwrap();
+        assert_eq!(off.matches("  0.9 0.2 0.2").count(), 5);
+        assert_eq!(off.matches("  0.95 0.8 0.2").count(), 2);
+
+        // Vertex and face colours are ignored when reading.
+        let src = "OFF\n4 4 6\n\
+            1 1 1 255 0 0\n1 -1 -1 0 255 0\n-1 1 -1 0 0 255\n-1 -1 1 255 255 0\n\
+            3 0 1 2 0.5 0.5 0.5 1\n3 0 3 1 0.5 0.5 0.5 1\n3 0 2 3 1 0 0\n3 1 3 2 0 1 0";
+        test(&Concrete::from_off_checked(src).unwrap(), [1, 4, 6, 4, 1]);
+    }
+
+    /// Lines with several elements, or elements split over several lines,
+    /// are rejected.
+    #[test]
+    fn one_per_line() {
+        const VERTICES: &str = "OFF\n4 4 6\n1 1 1\n1 -1 -1\n-1 1 -1\n-1 -1 1\n";
+
+        let src = format!(
+            "{}3 0 1 2 3 0 3 1 3 0 2 3\n3 0 3 1\n3 0 2 3\n3 1 3 2",
+            VERTICES
+        );
+        assert!(matches!(
+            Concrete::from_off(&src),
+            Err(OffParseError::TrailingValues(Position { row: 6, column: 8 }))
+        ));
+
+        // The second face could be a colour, but then the file ends early.
+        let src = format!("{}3 0 1 2 3 0 3 1\n3 0 2 3 3 1 3 2", VERTICES);
+        assert!(matches!(
+            Concrete::from_off(&src),
+            Err(OffParseError::UnexpectedEnding(_))
+        ));
+
+        let src = format!("{}3 0 1\n2\n3 0 3 1\n3 0 2 3\n3 1 3 2", VERTICES);
+        assert!(matches!(
+            Concrete::from_off(&src),
+            Err(OffParseError::LineEnding(Position { row: 6, .. }))
+        ));
+
+        // Vertices with an extra coordinate are rejected too.
+        let src = "OFF\n4 4 6\n1 1 1 1\n1 -1 -1\n-1 1 -1\n-1 -1 1\n\
+            3 0 1 2\n3 0 3 1\n3 0 2 3\n3 1 3 2";
+        assert!(matches!(
+            Concrete::from_off(src),
+            Err(OffParseError::TrailingValues(Position { row: 2, column: 6 }))
+        ));
+    }
 }
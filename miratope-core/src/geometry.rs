@@ -24,6 +24,7 @@ use crate::{
 
 use approx::{abs_diff_eq, abs_diff_ne};
 use nalgebra::{allocator::Allocator, DefaultAllocator, Dim, Dynamic, OMatrix, U1};
+use serde::{Deserialize, Serialize};
 use vec_like::VecLike;
 
 /// A hypersphere with a certain center and radius.
@@ -98,7 +99,7 @@ impl<T: Float> Hypersphere<T> {
 ///
 /// TODO: Use asserts to guarantee that the basis is an orthogonal basis of unit
 /// vectors.
-#[derive(Clone,)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Subspace<T: Float> {
     /// An orthogonal basis for the subspace, defined by unit vectors.
     pub basis: Vec<Vector<T>>,
@@ -358,6 +359,78 @@ impl<'a, T: Float> Segment<'a, T> {
     }
 }
 
+/// A rotation of *n*-dimensional space about the origin, stored as an
+/// orthogonal matrix. Rotations are built up by composing rotations in planes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rotation<T: Float>(Matrix<T>);
+
+impl<T: Float> Rotation<T> {
+    /// Returns the identity rotation in a given number of dimensions.
+    pub fn identity(dim: usize) -> Self {
+        Self(Matrix::identity(dim, dim))
+    }
+
+    /// Returns the number of dimensions of the space the rotation acts on.
+    pub fn dim(&self) -> usize {
+        self.0.nrows()
+    }
+
+    /// Returns the orthogonal matrix of the rotation.
+    pub fn matrix(&self) -> &Matrix<T> {
+        &self.0
+    }
+
+    /// Returns whether this is the identity rotation.
+    pub fn is_identity(&self) -> bool {
+        self.0 == Matrix::identity(self.dim(), self.dim())
+    }
+
+    /// Composes the rotation with a rotation by some angle in the plane
+    /// spanned by the `i`-th and `j`-th coordinate axes, which takes the
+    /// first axis towards the second. The new rotation is applied after the
+    /// current one.
+    pub fn rotate(&mut self, i: usize, j: usize, angle: T) {
+        let (sin, cos) = angle.fsin_cos();
+
+        for k in 0..self.dim() {
+            let x = self.0[(i, k)];
+            let y = self.0[(j, k)];
+            self.0[(i, k)] = x * cos - y * sin;
+            self.0[(j, k)] = x * sin + y * cos;
+        }
+    }
+
+    /// Composes the rotation with a rotation by some angle in the plane
+    /// spanned by two vectors, which takes the first vector towards the
+    /// second. The new rotation is applied after the current one. Returns
+    /// `false` and does nothing if the vectors don't span a plane.
+    pub fn rotate_in(&mut self, u: &Vector<T>, v: &Vector<T>, angle: T) -> bool {
+        // We get an orthonormal basis of the plane.
+        let e0 = match u.try_normalize(T::EPS) {
+            Some(e0) => e0,
+            None => return false,
+        };
+        let e1 = match (v - &e0 * v.dot(&e0)).try_normalize(T::EPS) {
+            Some(e1) => e1,
+            None => return false,
+        };
+
+        // The rotation fixes the orthogonal complement of the plane.
+        let (sin, cos) = angle.fsin_cos();
+        let rotation = Matrix::identity(self.dim(), self.dim())
+            + (&e0 * e0.transpose() + &e1 * e1.transpose()) * (cos - T::ONE)
+            + (&e1 * e0.transpose() - &e0 * e1.transpose()) * sin;
+
+        self.0 = rotation * &self.0;
+        true
+    }
+
+    /// Applies the rotation to a point.
+    pub fn apply(&self, p: &Point<T>) -> Point<T> {
+        &self.0 * p
+    }
+}
+
 /// A matrix ordered by fuzzy lexicographic ordering. That is, lexicographic
 /// ordering where two entries that differ by less than an epsilon are
 /// considered equal.
@@ -491,6 +564,49 @@ mod tests {
         assert_abs_diff_eq!((p - q).norm(), 0.0, epsilon = f32::EPS)
     }
 
+    #[test]
+    /// Composes rotations in coordinate planes and in arbitrary planes.
+    pub fn rotation() {
+        use std::f32::consts::FRAC_PI_2;
+
+        let mut rotation = Rotation::identity(4);
+        assert!(rotation.is_identity());
+
+        // A quarter turn in the xw plane takes x to w, then a quarter turn in
+        // the wy plane takes w to y.
+        rotation.rotate(0, 3, FRAC_PI_2);
+        assert_eq(
+            rotation.apply(&dvector![1.0, 0.0, 0.0, 0.0]),
+            dvector![0.0, 0.0, 0.0, 1.0],
+        );
+        rotation.rotate(3, 1, FRAC_PI_2);
+        assert_eq(
+            rotation.apply(&dvector![1.0, 0.0, 0.0, 0.0]),
+            dvector![0.0, 1.0, 0.0, 0.0],
+        );
+        assert_eq(
+            rotation.apply(&dvector![0.0, 0.0, 1.0, 0.0]),
+            dvector![0.0, 0.0, 1.0, 0.0],
+        );
+
+        // Rotations in planes spanned by non-orthogonal vectors.
+        let mut rotation = Rotation::identity(3);
+        assert!(rotation.rotate_in(
+            &dvector![2.0, 0.0, 0.0],
+            &dvector![1.0, 1.0, 0.0],
+            FRAC_PI_2
+        ));
+        assert_eq(
+            rotation.apply(&dvector![1.0, 0.0, 1.0]),
+            dvector![0.0, 1.0, 1.0],
+        );
+        assert!(!rotation.rotate_in(
+            &dvector![1.0, 1.0, 0.0],
+            &dvector![2.0, 2.0, 0.0],
+            1.0
+        ));
+    }
+
     #[test]
     /// Reciprocates points about spheres.
     pub fn reciprocate() {
@@ -0,0 +1,257 @@
+//! Finds the planes in which elements of a group act as rotations, which are
+//! used to build the [Coxeter plane](https://en.wikipedia.org/wiki/Coxeter_element#Coxeter_plane)
+//! projections of polytopes.
+
+use std::cmp::Ordering;
+
+use crate::{
+    float::Float,
+    geometry::{Matrix, Point, Vector},
+};
+
+use super::Group;
+
+use approx::abs_diff_eq;
+
+/// The greatest order of a group element we look for. Any elements of higher
+/// order are ignored.
+const MAX_ORDER: u32 = 1024;
+
+/// Returns the order of an orthogonal matrix, that is, the least positive
+/// power of it that equals the identity. Returns `None` if the order is larger
+/// than [`MAX_ORDER`], or if the matrix has infinite order.
+pub fn order(mat: &Matrix<f64>) -> Option<u32> {
+    let dim = mat.nrows();
+    let identity = Matrix::identity(dim, dim);
+    let mut power = mat.clone();
+
+    for k in 1..=MAX_ORDER {
+        if abs_diff_eq!(power, identity, epsilon = f64::EPS) {
+            return Some(k);
+        }
+
+        power = mat * power;
+    }
+
+    None
+}
+
+/// Returns an orthonormal basis for a plane in which an orthogonal matrix acts
+/// as a rotation by 2π / `k`, if it exists. The matrix rotates the first vector
+/// of the basis towards the second one.
+pub fn rotation_plane(mat: &Matrix<f64>, k: u32) -> Option<[Vector<f64>; 2]> {
+    if k < 3 {
+        return None;
+    }
+
+    // Such a plane is spanned by the eigenvectors for the eigenvalues
+    // exp(±2πi / k), which are the kernel of the following matrix.
+    let dim = mat.nrows();
+    let cos = (f64::TAU / k as f64).fcos();
+    let poly = mat * mat - mat * (2.0 * cos) + Matrix::identity(dim, dim);
+
+    let svd = poly.svd(false, true);
+    let (idx, &min) = svd
+        .singular_values
+        .iter()
+        .enumerate()
+        .min_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap_or(Ordering::Equal))?;
+    if min > f64::EPS {
+        return None;
+    }
+
+    // The matrix maps u into cos(θ) u + sin(θ) v.
+    let u: Vector<f64> = svd.v_t?.row(idx).transpose().normalize();
+    let v = (mat * &u - &u * cos).normalize();
+    Some([u, v])
+}
+
+/// A plane in which some element of a group of orthogonal matrices acts as a
+/// rotation by 2π / k, where k is the order of the element.
+///
+/// If the element is a Coxeter element, projecting a polytope onto this plane
+/// gives its Coxeter plane projection, in which its Petrie polygon becomes a
+/// regular polygon. Other elements give other symmetric projections.
+#[derive(Clone, Debug)]
+pub struct CoxeterPlane {
+    /// The group element.
+    pub element: Matrix<f64>,
+
+    /// The order of the group element.
+    pub order: u32,
+
+    /// An orthonormal basis for the plane. The element rotates the first vector
+    /// towards the second one.
+    pub basis: [Vector<f64>; 2],
+}
+
+impl CoxeterPlane {
+    /// Returns the plane of a group element, if it exists.
+    pub fn new(element: Matrix<f64>) -> Option<Self> {
+        let order = order(&element)?;
+        let basis = rotation_plane(&element, order)?;
+
+        Some(Self {
+            element,
+            order,
+            basis,
+        })
+    }
+
+    /// Finds the plane of a Coxeter element of a group. We take the element
+    /// with the largest order among those with a plane, which for an
+    /// irreducible Coxeter group has the order of its Coxeter number, and is
+    /// always a Coxeter element.
+    pub fn from_group<I: Iterator<Item = Matrix<f64>>>(group: Group<I>) -> Option<Self> {
+        let mut best = None;
+        let mut best_order = 0;
+
+        for element in group {
+            if let Some(order) = order(&element).filter(|&order| order > best_order) {
+                if let Some(basis) = rotation_plane(&element, order) {
+                    best_order = order;
+                    best = Some(Self {
+                        element,
+                        order,
+                        basis,
+                    });
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Finds the plane of some group element with a given order, if it exists.
+    pub fn with_order<I: Iterator<Item = Matrix<f64>>>(
+        mut group: Group<I>,
+        order: u32,
+    ) -> Option<Self> {
+        group.find_map(|element| {
+            (self::order(&element) == Some(order))
+                .then(|| rotation_plane(&element, order))
+                .flatten()
+                .map(|basis| Self {
+                    element,
+                    order,
+                    basis,
+                })
+        })
+    }
+
+    /// Returns the number of dimensions of the space the plane lives in.
+    pub fn dim(&self) -> usize {
+        self.element.nrows()
+    }
+
+    /// Projects a point onto the plane, and returns its coordinates in its
+    /// basis.
+    pub fn project(&self, p: &Point<f64>) -> [f64; 2] {
+        [self.basis[0].dot(p), self.basis[1].dot(p)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        conc::Concrete,
+        cox::{cd::Cd, Cox},
+        Polytope,
+    };
+
+    use approx::assert_abs_diff_eq;
+
+    /// Returns the distinct distances from the center of the plane of the
+    /// projections of the vertices of a polytope, along with the number of
+    /// vertices at each of them.
+    fn rings(poly: &Concrete, plane: &CoxeterPlane) -> Vec<(f64, usize)> {
+        let mut radii: Vec<_> = poly
+            .vertices
+            .iter()
+            .map(|v| {
+                let [x, y] = plane.project(v);
+                x.hypot(y)
+            })
+            .collect();
+        radii.sort_by(|x, y| x.partial_cmp(y).unwrap());
+
+        let mut rings: Vec<(f64, usize)> = Vec::new();
+        for r in radii {
+            match rings.last_mut() {
+                Some((last, count)) if abs_diff_eq!(*last, r, epsilon = 1e-6) => *count += 1,
+                _ => rings.push((r, 1)),
+            }
+        }
+
+        rings
+    }
+
+    /// Checks that the Coxeter elements of some groups have the order of their
+    /// Coxeter numbers, and that we find them from the entire groups.
+    #[test]
+    fn coxeter_number() {
+        for (cox, h) in [
+            (Cox::a(4), 5),
+            (Cox::b(4), 8),
+            (Cox::d(4), 6),
+            (Cox::h(3), 10),
+        ] {
+            assert_eq!(cox.coxeter_plane().unwrap().order, h);
+            assert_eq!(
+                CoxeterPlane::from_group(cox.group().unwrap())
+                    .unwrap()
+                    .order,
+                h
+            );
+        }
+
+        assert_eq!(Cox::h(4).coxeter_plane().unwrap().order, 30);
+        assert_eq!(Cox::e(8).coxeter_plane().unwrap().order, 30);
+    }
+
+    /// Checks that the Petrie polygons of some Wythoffian polytopes become
+    /// regular polygons in their Coxeter planes.
+    #[test]
+    fn petrie_polygon() {
+        let plane = Cox::a(4).coxeter_plane().unwrap();
+        let simplex = Concrete::from_cd(&Cd::parse("x3o3o3o").unwrap()).unwrap();
+        assert_eq!(rings(&simplex, &plane).len(), 1);
+
+        let plane = Cox::b(4).coxeter_plane().unwrap();
+        let tesseract = Concrete::from_cd(&Cd::parse("x4o3o3o").unwrap()).unwrap();
+        let rings = rings(&tesseract, &plane);
+        assert_eq!(rings.iter().map(|ring| ring.1).collect::<Vec<_>>(), [8, 8]);
+    }
+
+    /// Checks that the Coxeter planes can be found from the symmetry group of
+    /// a polytope, as can planes for elements of other orders.
+    #[test]
+    fn symmetry_group() {
+        let mut tesseract = Concrete::hypercube(5);
+        let group = tesseract.get_symmetry_group().unwrap().0.cache();
+
+        let plane = CoxeterPlane::from_group(group.clone()).unwrap();
+        assert_eq!(plane.order, 8);
+        assert_eq!(rings(&tesseract, &plane).len(), 2);
+
+        let plane = CoxeterPlane::with_order(group.clone(), 6).unwrap();
+        assert_eq!(plane.order, 6);
+        assert!(CoxeterPlane::with_order(group, 5).is_none());
+    }
+
+    /// Checks that the basis is oriented along the rotation.
+    #[test]
+    fn orientation() {
+        let plane = Cox::h(3).coxeter_plane().unwrap();
+        let [u, v] = &plane.basis;
+        let angle = f64::TAU / plane.order as f64;
+
+        assert_abs_diff_eq!(u.dot(v), 0.0, epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            &plane.element * u,
+            u * angle.cos() + v * angle.sin(),
+            epsilon = 1e-9
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! Contains methods to generate many symmetry groups.
 
+pub mod coxeter_plane;
 pub mod cyclic;
 pub mod gen_iter;
 pub mod group_item;
@@ -26,10 +26,12 @@
 pub mod abs;
 pub mod conc;
 pub mod cox;
+pub mod expr;
 pub mod file;
 pub mod float;
 pub mod geometry;
 pub mod group;
+pub mod name;
 
 use std::{collections::HashSet, error::Error, iter, ops::IndexMut};
 
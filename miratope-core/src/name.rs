@@ -0,0 +1,553 @@
+//! Contains the [`Name`] type, which describes how a polytope was built, and
+//! the code to convert these names into English.
+//!
+//! Names can be serialized, which allows us to store them in the OFF files we
+//! save, in [RON](https://github.com/ron-rs/ron) format. Every name is stored
+//! together with the version of the format it was saved in, so that names
+//! saved by older versions of Miratope can still be read.
+
+use std::fmt::Display;
+
+use crate::conc::element_types::EL_SUFFIXES;
+
+use serde::{Deserialize, Serialize};
+
+/// The version of the format in which names are serialized. Names without a
+/// version were saved by older versions of Miratope, which gave points rank 0
+/// instead of 1.
+pub const NAME_VERSION: u32 = 1;
+
+/// The name of a polytope, stored as an abstract syntax tree. Every variant
+/// corresponds to some way of building or describing a polytope.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Name {
+    /// A nullitope.
+    Nullitope,
+
+    /// A point.
+    Point,
+
+    /// A dyad.
+    Dyad,
+
+    /// A simplex of a given rank.
+    Simplex(usize),
+
+    /// A hypercube of a given rank.
+    Hypercube(usize),
+
+    /// An orthoplex of a given rank.
+    Orthoplex(usize),
+
+    /// A polytope of a given rank, described only by its number of facets.
+    /// For instance, a pentagon or an octahedron.
+    Generic {
+        /// The number of facets of the polytope.
+        facet_count: usize,
+
+        /// The rank of the polytope.
+        rank: usize,
+    },
+
+    /// A regular star polygon.
+    StarPolygon {
+        /// The number of sides of the polygon.
+        n: usize,
+
+        /// The turning number of the polygon.
+        d: usize,
+    },
+
+    /// A pyramid over a base.
+    Pyramid(Box<Name>),
+
+    /// A prism over a base.
+    Prism(Box<Name>),
+
+    /// A tegum over a base.
+    Tegum(Box<Name>),
+
+    /// An antiprism over a base.
+    Antiprism(Box<Name>),
+
+    /// The dual of a polytope.
+    Dual(Box<Name>),
+
+    /// The Petrial of a polytope.
+    Petrial(Box<Name>),
+
+    /// The small version of a polytope, as in the small stellated
+    /// dodecahedron.
+    Small(Box<Name>),
+
+    /// The great version of a polytope, as in the great icosahedron.
+    Great(Box<Name>),
+
+    /// The stellation of a polytope, as in the great stellated dodecahedron.
+    Stellated(Box<Name>),
+
+    /// A pyramid product of a list of polytopes.
+    Duopyramid(Vec<Name>),
+
+    /// A prism product of a list of polytopes.
+    Duoprism(Vec<Name>),
+
+    /// A tegum product of a list of polytopes.
+    Duotegum(Vec<Name>),
+
+    /// A comb product of a list of polytopes.
+    Duocomb(Vec<Name>),
+
+    /// A compound of some polytopes, each with a given multiplicity.
+    Compound(Vec<(usize, Name)>),
+
+    /// Any name that doesn't fit the other variants, like the name of a file.
+    Custom(String),
+}
+
+impl Default for Name {
+    fn default() -> Self {
+        Self::Custom(String::from("default"))
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self::Custom(name)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self::Custom(name.to_owned())
+    }
+}
+
+/// A name, together with the version of the format it was serialized in.
+#[derive(Serialize, Deserialize)]
+struct Versioned<T> {
+    /// The version of the format, see [`NAME_VERSION`].
+    version: u32,
+
+    /// The name itself.
+    name: T,
+}
+
+/// A name as serialized by older versions of Miratope, before names had a
+/// version. Ranks were counted from 0 for points. Fields that are no longer
+/// used, like the centers of regular polytopes, are ignored.
+#[derive(Deserialize)]
+enum LegacyName {
+    Nullitope,
+    Point,
+    Dyad,
+    Pyramid(Box<LegacyName>),
+    Prism(Box<LegacyName>),
+    Tegum(Box<LegacyName>),
+    Dual { base: Box<LegacyName> },
+    Simplex { rank: usize },
+    Hyperblock { rank: usize },
+    Orthoplex { rank: usize },
+    Generic { facet_count: usize, rank: usize },
+    Small(Box<LegacyName>),
+    Great(Box<LegacyName>),
+    Stellated(Box<LegacyName>),
+    Compound(Vec<(usize, LegacyName)>),
+}
+
+impl From<LegacyName> for Name {
+    fn from(name: LegacyName) -> Self {
+        let boxed = |base: Box<LegacyName>| Box::new(Name::from(*base));
+
+        match name {
+            LegacyName::Nullitope => Self::Nullitope,
+            LegacyName::Point => Self::Point,
+            LegacyName::Dyad => Self::Dyad,
+            LegacyName::Pyramid(base) => Self::Pyramid(boxed(base)),
+            LegacyName::Prism(base) => Self::Prism(boxed(base)),
+            LegacyName::Tegum(base) => Self::Tegum(boxed(base)),
+            LegacyName::Dual { base, .. } => Self::Dual(boxed(base)),
+            LegacyName::Simplex { rank, .. } => Self::Simplex(rank + 1),
+            LegacyName::Hyperblock { rank, .. } => Self::Hypercube(rank + 1),
+            LegacyName::Orthoplex { rank, .. } => Self::Orthoplex(rank + 1),
+            LegacyName::Generic { facet_count, rank } => Self::Generic {
+                facet_count,
+                rank: rank + 1,
+            },
+            LegacyName::Small(base) => Self::Small(boxed(base)),
+            LegacyName::Great(base) => Self::Great(boxed(base)),
+            LegacyName::Stellated(base) => Self::Stellated(boxed(base)),
+            LegacyName::Compound(components) => Self::Compound(
+                components
+                    .into_iter()
+                    .map(|(count, name)| (count, name.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Returns the Greek numerical prefix for a number, or a numeral with a
+/// hyphen if we don't have one.
+fn greek_prefix(n: usize) -> String {
+    const PREFIXES: [&str; 13] = [
+        "", "mono", "di", "tri", "tetra", "penta", "hexa", "hepta", "octa", "ennea", "deca",
+        "hendeca", "dodeca",
+    ];
+
+    match n {
+        1..=12 => String::from(PREFIXES[n]),
+        16 => String::from("hexadeca"),
+        20 => String::from("icosa"),
+        _ => format!("{}-", n),
+    }
+}
+
+/// Converts the last word of a noun phrase into its adjective form, so that
+/// "cube" becomes "cubic" and "triangular prism" becomes "triangular
+/// prismatic".
+fn adjective(noun: &str) -> String {
+    /// Pairs of noun endings and their adjective endings, tried in order.
+    const ENDINGS: [(&str, &str); 16] = [
+        ("triangle", "triangular"),
+        ("square", "square"),
+        ("point", "point"),
+        ("cube", "cubic"),
+        ("tesseract", "tesseractic"),
+        ("plex", "plicial"),
+        ("gon", "gonal"),
+        ("hedron", "hedral"),
+        ("on", "ic"),
+        ("antiprism", "antiprismatic"),
+        ("prism", "prismatic"),
+        ("pyramid", "pyramidal"),
+        ("tegum", "tegmatic"),
+        ("comb", "combic"),
+        ("dyad", "dyadic"),
+        ("tope", "topic"),
+    ];
+
+    for (ending, adj_ending) in ENDINGS.iter() {
+        if let Some(stem) = noun.strip_suffix(ending) {
+            return format!("{}{}", stem, adj_ending);
+        }
+    }
+
+    String::from(noun)
+}
+
+/// Converts a noun into its plural form, so that "cube" becomes "cubes" and
+/// "octahedron" becomes "octahedra".
+fn plural(noun: &str) -> String {
+    if let Some(stem) = noun.strip_suffix("on") {
+        format!("{}a", stem)
+    } else if let Some(stem) = noun.strip_suffix("ex") {
+        format!("{}ices", stem)
+    } else if let Some(stem) = noun.strip_suffix("um") {
+        format!("{}a", stem)
+    } else {
+        format!("{}s", noun)
+    }
+}
+
+impl Name {
+    /// The name of a polygon with a given number of sides.
+    pub fn polygon(n: usize) -> Self {
+        Self::Generic {
+            facet_count: n,
+            rank: 3,
+        }
+    }
+
+    /// The name of a regular polygon with a given number of sides and turning
+    /// number.
+    pub fn star_polygon(n: usize, d: usize) -> Self {
+        if d > 1 {
+            Self::StarPolygon { n, d }
+        } else {
+            Self::polygon(n)
+        }
+    }
+
+    /// The name of a pyramid over a base.
+    pub fn pyramid(self) -> Self {
+        Self::Pyramid(Box::new(self))
+    }
+
+    /// The name of a prism over a base.
+    pub fn prism(self) -> Self {
+        Self::Prism(Box::new(self))
+    }
+
+    /// The name of a tegum over a base.
+    pub fn tegum(self) -> Self {
+        Self::Tegum(Box::new(self))
+    }
+
+    /// The name of an antiprism over a base.
+    pub fn antiprism(self) -> Self {
+        Self::Antiprism(Box::new(self))
+    }
+
+    /// The name of the dual of a polytope.
+    pub fn dual(self) -> Self {
+        Self::Dual(Box::new(self))
+    }
+
+    /// The name of the Petrial of a polytope.
+    pub fn petrial(self) -> Self {
+        Self::Petrial(Box::new(self))
+    }
+
+    /// The name of the small version of a polytope.
+    pub fn small(self) -> Self {
+        Self::Small(Box::new(self))
+    }
+
+    /// The name of the great version of a polytope.
+    pub fn great(self) -> Self {
+        Self::Great(Box::new(self))
+    }
+
+    /// The name of the stellation of a polytope.
+    pub fn stellated(self) -> Self {
+        Self::Stellated(Box::new(self))
+    }
+
+    /// Returns the name of a polytope of a given rank with a given number of
+    /// facets in English.
+    fn generic_noun(facet_count: usize, rank: usize) -> String {
+        match rank {
+            0 => String::from("nullitope"),
+            1 => String::from("point"),
+            2 => String::from("dyad"),
+            3 => match facet_count {
+                3 => String::from("triangle"),
+                4 => String::from("square"),
+                n => format!("{}gon", greek_prefix(n)),
+            },
+            _ => match EL_SUFFIXES.get(rank) {
+                Some(suffix) => format!("{}{}", greek_prefix(facet_count), suffix),
+                None => format!("{}-facet {}-polytope", facet_count, rank - 1),
+            },
+        }
+    }
+
+    /// Returns the name of a product of a list of polytopes in English.
+    fn product_noun(bases: &[Name], product: &str) -> String {
+        match bases.split_first() {
+            // All bases are the same, as in a triangular duoprism.
+            Some((first, rest)) if rest.iter().all(|base| base == first) => {
+                format!("{} {}", first.adjective(), product)
+            }
+
+            // All bases are written down, as in a cube-octahedron duoprism.
+            _ => format!(
+                "{} {}",
+                bases.iter().map(Name::noun).collect::<Vec<_>>().join("-"),
+                product
+            ),
+        }
+    }
+
+    /// Returns the name of the polytope in English, as a noun.
+    pub fn noun(&self) -> String {
+        match self {
+            Self::Nullitope => Self::generic_noun(0, 0),
+            Self::Point => Self::generic_noun(1, 1),
+            Self::Dyad => Self::generic_noun(2, 2),
+            Self::Simplex(rank) => Self::generic_noun(*rank, *rank),
+            Self::Hypercube(rank) => match rank {
+                0..=3 => Self::generic_noun(2 * rank.saturating_sub(1), *rank),
+                4 => String::from("cube"),
+                5 => String::from("tesseract"),
+                _ => format!("{}-cube", rank - 1),
+            },
+            Self::Orthoplex(rank) => match rank {
+                0..=3 => Self::generic_noun(2 * rank.saturating_sub(1), *rank),
+                4 => String::from("octahedron"),
+                5 => String::from("hexadecachoron"),
+                _ => format!("{}-orthoplex", rank - 1),
+            },
+            Self::Generic { facet_count, rank } => Self::generic_noun(*facet_count, *rank),
+            Self::StarPolygon { n, d } => format!("{}/{}-gon", n, d),
+            Self::Pyramid(base) => format!("{} pyramid", base.adjective()),
+            Self::Prism(base) => format!("{} prism", base.adjective()),
+            Self::Tegum(base) => format!("{} tegum", base.adjective()),
+            Self::Antiprism(base) => format!("{} antiprism", base.adjective()),
+            Self::Dual(base) => format!("dual {}", base.noun()),
+            Self::Petrial(base) => format!("Petrial {}", base.noun()),
+            Self::Small(base) => format!("small {}", base.noun()),
+            Self::Great(base) => format!("great {}", base.noun()),
+            Self::Stellated(base) => format!("stellated {}", base.noun()),
+            Self::Duopyramid(bases) => Self::product_noun(bases, "duopyramid"),
+            Self::Duoprism(bases) => Self::product_noun(bases, "duoprism"),
+            Self::Duotegum(bases) => Self::product_noun(bases, "duotegum"),
+            Self::Duocomb(bases) => Self::product_noun(bases, "duocomb"),
+            Self::Compound(components) => format!(
+                "compound of {}",
+                components
+                    .iter()
+                    .map(|(count, name)| if *count == 1 {
+                        name.noun()
+                    } else {
+                        format!("{} {}", count, plural(&name.noun()))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
+            Self::Custom(name) => name.clone(),
+        }
+    }
+
+    /// Returns the name of the polytope in English, as an adjective. For
+    /// instance, the adjective for a triangle is "triangular", as in
+    /// "triangular prism".
+    pub fn adjective(&self) -> String {
+        match self {
+            // Adjectives for compounds and custom names would be confusing.
+            Self::Compound(_) | Self::Custom(_) => self.noun(),
+            _ => adjective(&self.noun()),
+        }
+    }
+
+    /// Serializes the name in RON, together with the current
+    /// [`NAME_VERSION`].
+    pub fn to_ron(&self) -> ron::Result<String> {
+        ron::to_string(&Versioned {
+            version: NAME_VERSION,
+            name: self,
+        })
+    }
+
+    /// Deserializes a name from RON. Names without a version are read as
+    /// they were saved by older versions of Miratope.
+    pub fn from_ron(src: &str) -> ron::Result<Self> {
+        use serde::de::Error;
+
+        match ron::from_str::<Versioned<Self>>(src) {
+            Ok(Versioned { version, name }) if version <= NAME_VERSION => Ok(name),
+            Ok(Versioned { version, .. }) => Err(ron::Error::custom(format!(
+                "unsupported name version {}",
+                version
+            ))),
+            Err(err) => ron::from_str::<LegacyName>(src)
+                .map(Into::into)
+                .map_err(|_| err),
+        }
+    }
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.noun())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that a name has the expected English name.
+    fn test_name(name: Name, english: &str) {
+        assert_eq!(name.to_string(), english);
+    }
+
+    /// Names of polygons and regular polytopes.
+    #[test]
+    fn regular() {
+        test_name(Name::polygon(3), "triangle");
+        test_name(Name::polygon(4), "square");
+        test_name(Name::polygon(7), "heptagon");
+        test_name(Name::polygon(13), "13-gon");
+        test_name(Name::Hypercube(3), "square");
+        test_name(Name::Hypercube(4), "cube");
+        test_name(Name::Hypercube(7), "6-cube");
+        test_name(Name::Simplex(5), "pentachoron");
+        test_name(Name::Orthoplex(4), "octahedron");
+        test_name(
+            Name::Generic {
+                facet_count: 12,
+                rank: 4,
+            },
+            "dodecahedron",
+        );
+    }
+
+    /// Names of operations on a single polytope.
+    #[test]
+    fn operations() {
+        test_name(Name::polygon(3).prism(), "triangular prism");
+        test_name(Name::Hypercube(3).pyramid(), "square pyramid");
+        test_name(Name::polygon(5).antiprism(), "pentagonal antiprism");
+        test_name(Name::Hypercube(4).tegum(), "cubic tegum");
+        test_name(Name::Orthoplex(4).prism(), "octahedral prism");
+        test_name(
+            Name::polygon(3).prism().prism(),
+            "triangular prismatic prism",
+        );
+        test_name(Name::Hypercube(4).dual(), "dual cube");
+        test_name(Name::Simplex(5).prism(), "pentachoric prism");
+        test_name(Name::star_polygon(5, 2).prism(), "5/2-gonal prism");
+    }
+
+    /// Names of products and compounds.
+    #[test]
+    fn products() {
+        test_name(
+            Name::Duoprism(vec![Name::Hypercube(4), Name::Orthoplex(4)]),
+            "cube-octahedron duoprism",
+        );
+        test_name(
+            Name::Duotegum(vec![Name::polygon(3), Name::polygon(3)]),
+            "triangular duotegum",
+        );
+        test_name(
+            Name::Compound(vec![(2, Name::Simplex(4)), (1, Name::Hypercube(4))]),
+            "compound of 2 tetrahedra and cube",
+        );
+    }
+
+    /// Names survive being serialized and deserialized.
+    #[test]
+    fn ron() {
+        let name = Name::Compound(vec![
+            (
+                3,
+                Name::Duoprism(vec![Name::polygon(5).pyramid(), Name::Custom("foo".into())]),
+            ),
+            (1, Name::Hypercube(4).dual()),
+        ]);
+
+        let ron = name.to_ron().unwrap();
+        assert!(!ron.contains('\n'));
+        assert_eq!(Name::from_ron(&ron).unwrap(), name);
+    }
+
+    /// Names from older versions of Miratope have their ranks shifted.
+    #[test]
+    fn legacy() {
+        let test_legacy = |ron: &str, english: &str| {
+            assert_eq!(Name::from_ron(ron).unwrap().to_string(), english);
+        };
+
+        test_legacy("Generic(facet_count:12,rank:3)", "dodecahedron");
+        test_legacy("Generic(facet_count:5,rank:2)", "pentagon");
+        test_legacy(
+            "Simplex(regular:(Yes(center:(data:[0,0,0],nrows:3,ncols:()))),rank:3)",
+            "tetrahedron",
+        );
+        test_legacy(
+            "Hyperblock(regular:(Yes(center:([0,0,0],3,()))),rank:3)",
+            "cube",
+        );
+        test_legacy(
+            "Small(Stellated(Generic(facet_count:12,rank:3)))",
+            "small stellated dodecahedron",
+        );
+        test_legacy("Prism(Generic(facet_count:3,rank:2))", "triangular prism");
+
+        // Names from newer versions can't be read.
+        assert!(Name::from_ron("(version:1000,name:Point)").is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Exports the mesh of a polytope into files that other programs can read, so
+//! that it can be 3D printed or rendered elsewhere.
+//!
+//! The mesh is the same one that's shown on screen: polytopes in more than
+//! three dimensions are projected down, and faces are triangulated, including
+//! the extra vertices needed for star polygons. Edges can also be exported as
+//! cylinders, and vertices as spheres, so that wireframes can be printed.
+
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Error as IoError, ErrorKind, Result as IoResult, Write},
+    path::Path,
+};
+
+use crate::{
+    mesh::{vertex_coords, with_faces, Triangulation},
+    ui::camera::ProjectionType,
+    Concrete, Float,
+};
+
+use miratope_core::{
+    abs::Ranked,
+    file::mesh::{MeshGroup, TriangleMesh},
+    geometry::Rotation,
+};
+use vec_like::VecLike;
+
+/// The file formats a mesh can be exported into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A binary STL file.
+    BinaryStl,
+
+    /// An ASCII STL file.
+    AsciiStl,
+
+    /// A Wavefront OBJ file, with a group for each face type.
+    Obj,
+
+    /// A binary glTF file, with a node for each face type.
+    Glb,
+}
+
+impl ExportFormat {
+    /// All of the export formats, in the order they're shown.
+    pub const ALL: [Self; 4] = [Self::BinaryStl, Self::AsciiStl, Self::Obj, Self::Glb];
+
+    /// The extension of the files in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::BinaryStl | Self::AsciiStl => "stl",
+            Self::Obj => "obj",
+            Self::Glb => "glb",
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::BinaryStl => "STL (binary)",
+            Self::AsciiStl => "STL (ASCII)",
+            Self::Obj => "Wavefront OBJ",
+            Self::Glb => "glTF (binary)",
+        })
+    }
+}
+
+/// The options for exporting edges and vertices as solids.
+#[derive(Clone, Copy, Debug)]
+pub struct Wireframe {
+    /// The radius of the cylinders around the edges.
+    pub edge_radius: f32,
+
+    /// The radius of the spheres around the vertices. If this is zero, no
+    /// spheres are exported.
+    pub vertex_radius: f32,
+
+    /// The number of sides of the cylinders and spheres.
+    pub sides: usize,
+}
+
+impl Default for Wireframe {
+    fn default() -> Self {
+        Self {
+            edge_radius: 0.02,
+            vertex_radius: 0.03,
+            sides: 12,
+        }
+    }
+}
+
+/// The options for exporting a mesh.
+#[derive(Clone, Copy, Debug)]
+pub struct ExportOptions {
+    /// The format of the file.
+    pub format: ExportFormat,
+
+    /// Whether the faces are exported.
+    pub faces: bool,
+
+    /// Whether the edges and vertices are exported as solids, and how.
+    pub wireframe: Option<Wireframe>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::BinaryStl,
+            faces: true,
+            wireframe: None,
+        }
+    }
+}
+
+/// Builds the mesh of a polytope, rotated and projected into 3D in the same
+/// way as on screen.
+pub fn export_mesh(
+    poly: &Concrete,
+    projection_type: &ProjectionType,
+    rotation: &Rotation<Float>,
+    options: &ExportOptions,
+) -> TriangleMesh {
+    let mut mesh = TriangleMesh::new();
+
+    if poly.vertex_count() == 0 {
+        return mesh;
+    }
+
+    if options.faces {
+        push_faces(&mut mesh, poly, projection_type, rotation);
+    }
+
+    if let Some(wireframe) = options.wireframe {
+        push_wireframe(&mut mesh, poly, projection_type, rotation, &wireframe);
+    }
+
+    mesh
+}
+
+/// Adds the triangulated faces of a polytope to a mesh, with a group for each
+/// face type.
+fn push_faces(
+    mesh: &mut TriangleMesh,
+    poly: &Concrete,
+    projection_type: &ProjectionType,
+    rotation: &Rotation<Float>,
+) {
+    let faces = with_faces(poly);
+    let triangulation = Triangulation::new(&faces);
+    if triangulation.triangles.is_empty() {
+        return;
+    }
+
+    let offset = mesh.vertices.len() as u32;
+    mesh.vertices.extend(vertex_coords(
+        poly,
+        poly.vertices
+            .iter()
+            .chain(triangulation.extra_vertices.iter()),
+        projection_type,
+        rotation,
+    ));
+
+    // The face types, and the number of edges of each.
+    let types = faces.types_of_elements();
+    let type_count = types[3].iter().copied().max().map_or(0, |t| t + 1);
+    let mut sides = vec![0; type_count];
+    for (face, &t) in faces[3].iter().zip(&types[3]) {
+        sides[t] = face.subs.len();
+    }
+
+    let first_group = mesh.groups.len();
+    mesh.groups.extend(
+        sides
+            .into_iter()
+            .enumerate()
+            .map(|(t, sides)| MeshGroup::new(format!("face_type_{}_{}gon", t, sides))),
+    );
+
+    for (triangle, &face) in triangulation
+        .triangles
+        .chunks_exact(3)
+        .zip(&triangulation.faces)
+    {
+        // The triangles from the triangulation have no consistent
+        // orientation, so we turn them away from the origin. This gives the
+        // right result for any polyhedron that's star-shaped around it, which
+        // is the case for most polytopes.
+        let triangle = [triangle[0], triangle[1], triangle[2]].map(|v| v + offset);
+        mesh.push_outward(first_group + types[3][face], triangle);
+    }
+}
+
+/// Adds the edges of a polytope to a mesh as cylinders, and its vertices as
+/// spheres.
+fn push_wireframe(
+    mesh: &mut TriangleMesh,
+    poly: &Concrete,
+    projection_type: &ProjectionType,
+    rotation: &Rotation<Float>,
+    wireframe: &Wireframe,
+) {
+    let vertices = vertex_coords(poly, poly.vertices.iter(), projection_type, rotation);
+    let sides = wireframe.sides.max(3);
+
+    if let Some(edges) = poly.get_element_list(2) {
+        let mut group = MeshGroup::new("edges".to_string());
+
+        for edge in edges {
+            let (p, q) = (vertices[edge.subs[0]], vertices[edge.subs[1]]);
+            mesh.push_cylinder(&mut group, p, q, wireframe.edge_radius, sides);
+        }
+
+        mesh.groups.push(group);
+    }
+
+    if wireframe.vertex_radius > 0.0 {
+        let mut group = MeshGroup::new("vertices".to_string());
+
+        for &v in &vertices {
+            mesh.push_sphere(&mut group, v, wireframe.vertex_radius, sides);
+        }
+
+        mesh.groups.push(group);
+    }
+}
+
+/// Writes a mesh into a file in a given format.
+pub fn write<W: Write>(
+    mesh: &TriangleMesh,
+    format: ExportFormat,
+    name: &str,
+    w: &mut W,
+) -> IoResult<()> {
+    match format {
+        ExportFormat::BinaryStl => mesh.write_binary_stl(name, w),
+        ExportFormat::AsciiStl => mesh.write_ascii_stl(name, w),
+        ExportFormat::Obj => mesh.write_obj(name, w),
+        ExportFormat::Glb => mesh.write_glb(name, w),
+    }
+}
+
+/// Exports the mesh of a polytope into a file.
+pub fn export<P: AsRef<Path>>(
+    poly: &Concrete,
+    name: &str,
+    projection_type: &ProjectionType,
+    rotation: &Rotation<Float>,
+    options: &ExportOptions,
+    path: P,
+) -> IoResult<()> {
+    let mesh = export_mesh(poly, projection_type, rotation, options);
+
+    // We don't even create the file if there's nothing to write to it.
+    if mesh.is_empty() {
+        return Err(IoError::new(
+            ErrorKind::InvalidInput,
+            "there's nothing to export",
+        ));
+    }
+
+    let mut file = BufWriter::new(File::create(path)?);
+    write(&mesh, options.format, name, &mut file)?;
+    file.flush()
+}
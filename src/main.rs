@@ -15,16 +15,19 @@ use bevy::prelude::*;
 use bevy::reflect::TypeUuid;
 use bevy::render::{camera::PerspectiveProjection, pipeline::PipelineDescriptor};
 use bevy_egui::EguiPlugin;
-use miratope_core::file::FromFile;
+use miratope_core::{file::FromFile, geometry::Rotation};
 use no_cull_pipeline::PbrNoBackfaceBundle;
 
 use ui::{
     camera::{CameraInputEvent, ProjectionType},
+    coloring::FaceColoring,
+    selection::SelectionHighlight,
     MiratopePlugins,
 };
 
 use crate::mesh::Renderable;
 
+mod export;
 mod mesh;
 mod no_cull_pipeline;
 mod ui;
@@ -92,7 +95,7 @@ fn setup(
         no_cull_pipeline::build_no_cull_pipeline(&mut shaders),
     );
 
-    // Selected object (unused as of yet).
+    // Selected element material.
     materials.set_untracked(
         WIREFRAME_SELECTED_MATERIAL,
         Color::rgb_u8(126, 192, 255).into(),
@@ -113,24 +116,44 @@ fn setup(
     let mut cam = Default::default();
     CameraInputEvent::reset(&mut cam_anchor, &mut cam);
 
+    // The view starts out unrotated, and the faces uncolored.
+    let rotation = Rotation::identity(0);
+    let (mesh, mesh_vertices) = poly.mesh(
+        &ProjectionType::Perspective,
+        &rotation,
+        &FaceColoring::default(),
+    );
+    let wireframe = poly.wireframe(&mesh_vertices.projector, &rotation);
+
     commands
         .spawn()
         // Mesh
         .insert_bundle(PbrNoBackfaceBundle {
-            mesh: meshes.add(poly.mesh(ProjectionType::Perspective)),
+            mesh: meshes.add(mesh),
             material: mesh_material,
             ..Default::default()
         })
         // Wireframe
         .with_children(|cb| {
             cb.spawn().insert_bundle(PbrNoBackfaceBundle {
-                mesh: meshes.add(poly.wireframe(ProjectionType::Perspective)),
+                mesh: meshes.add(wireframe),
                 material: wf_material,
                 ..Default::default()
             });
         })
         // Polytope
-        .insert(poly);
+        .insert(poly)
+        .insert(mesh_vertices);
+
+    // Highlight for the selected element
+    commands
+        .spawn()
+        .insert_bundle(PbrNoBackfaceBundle {
+            mesh: meshes.add(mesh::empty_mesh()),
+            material: WIREFRAME_SELECTED_MATERIAL.typed(),
+            ..Default::default()
+        })
+        .insert(SelectionHighlight);
 
     // Camera anchor
     commands
@@ -2,8 +2,11 @@
 
 use std::collections::HashMap;
 
-use crate::ui::camera::ProjectionType;
-use crate::{Concrete, Float, Point, EPS};
+use crate::ui::{
+    camera::ProjectionType,
+    coloring::{ColorScheme, FaceColoring},
+};
+use crate::{Concrete, Float, Hypersphere, Point, Vector, EPS};
 
 use bevy::{
     prelude::Mesh,
@@ -14,7 +17,8 @@ use miratope_core::conc::cycle::CycleList;
 use miratope_core::{
     abs::{ElementList, Ranked},
     conc::ConcretePolytope,
-    geometry::{Subspace, Vector},
+    geometry::{Rotation, Subspace},
+    Polytope,
 };
 
 use vec_like::*;
@@ -91,19 +95,23 @@ pub fn path(cycles: &CycleList, vertices: &[Point]) -> Option<Path> {
 /// Represents a triangulation of the faces of a [`Concrete`]. It stores the
 /// vertex indices that make up the triangulation of the polytope, as well as
 /// the extra vertices that may be needed to represent it.
-struct Triangulation {
+pub(crate) struct Triangulation {
     /// Extra vertices that might be needed for the triangulation.
-    extra_vertices: Vec<Point>,
+    pub extra_vertices: Vec<Point>,
 
     /// Indices of the vertices that make up the triangles.
-    triangles: Vec<u32>,
+    pub triangles: Vec<u32>,
+
+    /// The index of the face each triangle belongs to.
+    pub faces: Vec<usize>,
 }
 
 impl Triangulation {
     /// Creates a new triangulation from a polytope.
-    fn new(polytope: &Concrete) -> Self {
+    pub fn new(polytope: &Concrete) -> Self {
         let mut extra_vertices = Vec::new();
         let mut triangles = Vec::new();
+        let mut face_indices = Vec::new();
         let empty_els = ElementList::new();
 
         // Either returns a reference to the element list of a given rank, or
@@ -116,7 +124,7 @@ impl Triangulation {
         let concrete_vertex_len = polytope.vertices.len() as u32;
 
         // We render each face separately.
-        for face in faces {
+        for (face_idx, face) in faces.iter().enumerate() {
             // We tesselate this path.
             let cycles = CycleList::from_edges(face.subs.iter().map(|&i| &edges[i].subs));
             if let Some(path) = path(&cycles, &polytope.vertices) {
@@ -181,12 +189,14 @@ impl Triangulation {
                 {
                     triangles.push(new_idx);
                 }
+                face_indices.resize(triangles.len() / 3, face_idx);
             }
         }
 
         Self {
             extra_vertices,
             triangles,
+            faces: face_indices,
         }
     }
 }
@@ -209,89 +219,536 @@ fn normals(vertices: &[[f32; 3]]) -> Vec<[f32; 3]> {
 }
 
 /// Returns an empty mesh.
-fn empty_mesh() -> Mesh {
+pub(crate) fn empty_mesh() -> Mesh {
     let mut mesh = Mesh::new(PrimitiveTopology::LineList);
     mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0; 3]]);
     mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0; 3]]);
     mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]]);
+    mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]]);
     mesh.set_indices(Some(Indices::U16(Vec::new())));
 
     mesh
 }
 
-/// Gets the coordinates of the vertices, after projecting down into 3D.
-fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
-    poly: &Concrete,
-    vertices: I,
-    projection_type: ProjectionType,
-) -> Vec<[f32; 3]> {
-    let dim = poly.dim_or();
+/// Returns the polytope whose faces get triangulated. Polygons are turned into
+/// ditopes, so that their body is rendered as a face.
+pub(crate) fn with_faces(poly: &Concrete) -> Concrete {
+    let mut poly = poly.clone();
+
+    if poly.rank() == 3 {
+        poly = poly.ditope();
+        poly.untangle_faces();
+    }
+
+    poly
+}
+
+/// The distance at which the eye is placed beyond a facet to build its Schlegel
+/// diagram, as a proportion of the distance from the facet to the center of
+/// the polytope. The eye is placed closer whenever it would otherwise lie
+/// beyond some other facet.
+const SCHLEGEL_HEIGHT: Float = 1.0;
+
+/// In a stereographic projection, vertices at the pole get sent to infinity. We
+/// bound the denominator of the projection by this value, so that they're
+/// instead sent very far away.
+const STEREOGRAPHIC_LIMIT: Float = 1e-3;
+
+/// A projection into 3D, with everything that depends on the polytope worked
+/// out in advance, so that it can be quickly applied to every vertex whenever
+/// the view is rotated.
+pub enum Projector {
+    /// Takes the first three coordinates.
+    Orthogonal,
+
+    /// Scales the first three coordinates by the distances from the other
+    /// coordinates to some projection planes.
+    Perspective {
+        /// The distance from the origin to the projection planes.
+        dist: f32,
+    },
+
+    /// Projects from an eye beyond a facet onto the facet's hyperplane.
+    Schlegel {
+        /// The point we project from.
+        eye: Point,
+
+        /// The unit normal of the facet, pointing away from the polytope.
+        normal: Vector,
+
+        /// The distance from the eye to the facet's hyperplane.
+        height: Float,
+
+        /// An orthonormal basis of the facet's hyperplane.
+        basis: Vec<Vector>,
+    },
+
+    /// Projects a hypersphere stereographically onto the hyperplane through
+    /// its center perpendicular to some axis, from the point of the
+    /// hypersphere opposite to the axis.
+    Stereographic {
+        /// The hypersphere we project from.
+        sphere: Hypersphere,
+
+        /// The unit vector pointing away from the point we project from.
+        axis: Vector,
+
+        /// An orthonormal basis of the hyperplane.
+        basis: Vec<Vector>,
+    },
+
+    /// Takes the coordinates in some orthonormal basis. Basis vectors may also
+    /// be zero, in which case the corresponding coordinate is zero.
+    Basis(Vec<Vector>),
+}
+
+impl Default for Projector {
+    fn default() -> Self {
+        Self::Orthogonal
+    }
+}
+
+/// Returns the first three vectors of the orthonormal basis of the hyperplane
+/// perpendicular to a unit vector that's closest to the coordinate axes.
+fn hyperplane_basis(normal: &Vector) -> Vec<Vector> {
+    let dim = normal.len();
+    let mut subspace = Subspace::new(Point::zeros(dim));
+    subspace.add(normal);
+
+    for i in 0..dim {
+        if subspace.rank() > 3 {
+            break;
+        }
+
+        subspace.add(&Point::from_fn(dim, |j, _| if i == j { 1.0 } else { 0.0 }));
+    }
+
+    subspace.basis.split_off(1)
+}
+
+/// Returns the centroid of the vertices of a facet of a polytope, if it
+/// exists, together with the indices of these vertices.
+fn facet_centroid(poly: &Concrete, idx: usize) -> Option<(Point, Vec<usize>)> {
+    let vertices = poly.abs.element_vertices(poly.rank() - 1, idx)?;
+    let centroid =
+        vertices.iter().map(|&v| &poly.vertices[v]).sum::<Point>() / vertices.len() as Float;
+
+    Some((centroid, vertices))
+}
+
+/// Returns the first three coordinates of a vector in a basis, scaled by some
+/// factor. Any missing coordinates are set to zero.
+fn basis_coords(basis: &[Vector], v: &Vector, scale: Float) -> [f32; 3] {
+    let mut coords = [0.0; 3];
+    for (c, b) in coords.iter_mut().zip(basis) {
+        *c = (b.dot(v) * scale) as f32;
+    }
+    coords
+}
+
+impl Projector {
+    /// Works out the projection of a polytope with a given projection type.
+    /// Any projections that can't be done are replaced by the default ones.
+    pub fn new(poly: &Concrete, projection_type: &ProjectionType) -> Self {
+        let dim = poly.dim_or();
+
+        match projection_type {
+            ProjectionType::Subspace { basis } => {
+                if basis.iter().all(|v| v.len() == dim) {
+                    Self::basis(basis)
+                } else {
+                    Self::Orthogonal
+                }
+            }
+
+            // If the polytope is at most 3D, we just embed it into 3D space.
+            _ if dim <= 3 => Self::Orthogonal,
+            ProjectionType::Orthogonal => Self::Orthogonal,
+            ProjectionType::Perspective => Self::perspective(poly),
+            ProjectionType::Schlegel { facet } => {
+                Self::schlegel(poly, *facet).unwrap_or_else(|| Self::perspective(poly))
+            }
+            ProjectionType::Stereographic => Self::stereographic(poly),
+        }
+    }
+
+    /// Projects the polytope from a point.
+    fn perspective(poly: &Concrete) -> Self {
+        // Distance from the projection planes. We take it to be larger than
+        // the distance from any vertex to the origin, so that it doesn't
+        // change as the view is rotated.
+        let radius = poly.vertices.iter().map(|v| v.norm()).fold(0.0, Float::max);
+
+        Self::Perspective {
+            dist: radius as f32 + 1.0,
+        }
+    }
+
+    /// Builds the Schlegel diagram through some facet of the polytope. Returns
+    /// `None` if the facet doesn't exist, or if it doesn't span a hyperplane
+    /// leaving the center of the polytope out.
+    fn schlegel(poly: &Concrete, facet: usize) -> Option<Self> {
+        let center = poly.gravicenter()?;
+
+        // Returns the unit normal of the hyperplane of a facet, pointing away
+        // from the center, together with the position of the hyperplane along
+        // it, and the centroid of the facet.
+        let hyperplane = |idx| {
+            let (centroid, vertices) = facet_centroid(poly, idx)?;
+            let subspace = Subspace::from_points(vertices.iter().map(|&v| &poly.vertices[v]));
+            if !subspace.is_hyperplane() {
+                return None;
+            }
+
+            let normal = -subspace.normal(&center)?;
+            let pos = normal.dot(&subspace.offset);
+            Some((normal, pos, centroid))
+        };
+
+        let (normal, pos, centroid) = hyperplane(facet)?;
+
+        // We move the eye away from the facet until it's about to lie beyond
+        // some other facet.
+        let mut height = SCHLEGEL_HEIGHT * (pos - normal.dot(&center));
+        for idx in (0..poly.facet_count()).filter(|&idx| idx != facet) {
+            if let Some((other_normal, other_pos, _)) = hyperplane(idx) {
+                let rate = other_normal.dot(&normal);
+
+                if rate > EPS {
+                    let dist = (other_pos - other_normal.dot(&centroid)) / rate;
+                    if dist > EPS {
+                        height = height.min(dist / 2.0);
+                    }
+                }
+            }
+        }
+
+        Some(Self::Schlegel {
+            eye: &centroid + &normal * height,
+            basis: hyperplane_basis(&normal),
+            normal,
+            height,
+        })
+    }
+
+    /// Projects the polytope stereographically from its circumsphere, so that
+    /// its first facet ends up in the middle whenever possible. If the polytope
+    /// has no circumsphere, we instead use the smallest hypersphere centered at
+    /// its gravicenter that contains all of its vertices.
+    fn stereographic(poly: &Concrete) -> Self {
+        let sphere = poly.circumsphere().unwrap_or_else(|| {
+            // The polytope has vertices, as its dimension is at least 4.
+            let center = poly.gravicenter().unwrap();
+            let radius = poly
+                .vertices
+                .iter()
+                .map(|v| (v - &center).norm())
+                .fold(0.0, Float::max);
+
+            Hypersphere::with_radius(center, radius)
+        });
+
+        // There's nothing to project from if all vertices coincide.
+        let radius = sphere.radius();
+        if radius < EPS {
+            return Self::Orthogonal;
+        }
+
+        // We project from the point opposite to the first facet. If there's a
+        // vertex there, as in a simplex, we try the first edge instead, and as
+        // a last resort, the last axis.
+        let dim = poly.dim_or();
+        let edge = poly
+            .get_element_list(2)
+            .and_then(|edges| edges.get(0))
+            .map(|edge| (&poly.vertices[edge.subs[0]] + &poly.vertices[edge.subs[1]]) / 2.0);
+
+        let axis = [facet_centroid(poly, 0).map(|(centroid, _)| centroid), edge]
+            .iter()
+            .flatten()
+            .filter_map(|p| (p - &sphere.center).try_normalize(EPS))
+            .find(|axis| {
+                poly.vertices
+                    .iter()
+                    .all(|v| 1.0 + axis.dot(&(v - &sphere.center)) / radius > STEREOGRAPHIC_LIMIT)
+            })
+            .unwrap_or_else(|| Vector::from_fn(dim, |i, _| if i == dim - 1 { 1.0 } else { 0.0 }));
+
+        Self::Stereographic {
+            sphere,
+            basis: hyperplane_basis(&axis),
+            axis,
+        }
+    }
 
-    // Returns the ith coordinate of p, or 0 if it doesn't exist.
-    let coord = |p: &Point, i: usize| p.get(i).copied().unwrap_or_default();
+    /// Takes the coordinates in the orthonormal basis spanning the same
+    /// subspaces as some vectors.
+    fn basis(vectors: &[Vector]) -> Self {
+        let dim = vectors.first().map_or(0, |v| v.len());
+        let mut subspace = Subspace::new(Point::zeros(dim));
 
-    // If the polytope is at most 3D, we just embed it into 3D space.
-    if projection_type.is_orthogonal() || dim <= 3 {
-        vertices.map(|p| [0, 1, 2].map(|i| coord(p, i) as f32)).collect()
+        Self::Basis(
+            vectors
+                .iter()
+                .map(|v| {
+                    subspace
+                        .add(v)
+                        .cloned()
+                        .unwrap_or_else(|| Vector::zeros(dim))
+                })
+                .collect(),
+        )
     }
-    // Else, we project it down.
-    else {
-        // Distance from the projection planes.
-        let mut direction = Vector::zeros(dim);
-        direction[3] = 1.0;
 
-        let (min, max) = poly.minmax(direction).unwrap();
-        let dist = (min as f32 - 1.0).abs().max(max as f32 + 1.0).abs();
+    /// Rotates the projections from a point or a hypersphere along with the
+    /// view, which is much faster than working them out again. Returns `None`
+    /// for any other projection, as they're applied after rotating the view
+    /// and don't depend on it.
+    fn rotated(&self, rotation: &Rotation<Float>) -> Option<Self> {
+        match self {
+            Self::Schlegel {
+                eye,
+                normal,
+                height,
+                ..
+            } => {
+                let normal = rotation.apply(normal);
+
+                Some(Self::Schlegel {
+                    eye: rotation.apply(eye),
+                    basis: hyperplane_basis(&normal),
+                    normal,
+                    height: *height,
+                })
+            }
 
+            Self::Stereographic { sphere, axis, .. } => {
+                let axis = rotation.apply(axis);
+
+                Some(Self::Stereographic {
+                    sphere: Hypersphere::with_squared_radius(
+                        rotation.apply(&sphere.center),
+                        sphere.squared_radius,
+                    ),
+                    basis: hyperplane_basis(&axis),
+                    axis,
+                })
+            }
+
+            _ => None,
+        }
+    }
+
+    /// Gets the coordinates of some vertices of a polytope, after rotating the
+    /// view and projecting down into 3D. The rotation is ignored if it's for a
+    /// different number of dimensions.
+    pub fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
+        &self,
+        poly: &Concrete,
+        vertices: I,
+        rotation: &Rotation<Float>,
+    ) -> Vec<[f32; 3]> {
+        let rotation =
+            (rotation.dim() == poly.dim_or() && !rotation.is_identity()).then(|| rotation);
+        let rotated = rotation.and_then(|rotation| self.rotated(rotation));
+        let projector = rotated.as_ref().unwrap_or(self);
+
+        // Rotates the vertices before projecting them.
         vertices
-            .map(|p| {
-                // We scale the first three coordinates accordingly.
-                let factor: f32 = p.iter().skip(3).map(|&x| x as f32 + dist).product();
-                [0, 1, 2].map(|i| coord(p, i) as f32 / factor)
+            .map(|p| match rotation {
+                Some(rotation) => projector.project(&rotation.apply(p)),
+                None => projector.project(p),
             })
             .collect()
     }
+
+    /// Projects a point into 3D.
+    fn project(&self, p: &Point) -> [f32; 3] {
+        // Returns the ith coordinate of p, or 0 if it doesn't exist.
+        let coord = |i: usize| p.get(i).copied().unwrap_or_default();
+
+        match self {
+            Self::Orthogonal => [0, 1, 2].map(|i| coord(i) as f32),
+
+            Self::Perspective { dist } => {
+                // We scale the first three coordinates accordingly.
+                let factor: f32 = p.iter().skip(3).map(|&x| x as f32 + dist).product();
+                [0, 1, 2].map(|i| coord(i) as f32 / factor)
+            }
+
+            Self::Schlegel {
+                eye,
+                normal,
+                height,
+                basis,
+            } => {
+                // Points beyond the facet's hyperplane only occur in
+                // nonconvex polytopes, and are projected orthogonally onto it.
+                let v = p - eye;
+                let depth = (-normal.dot(&v)).max(*height);
+                basis_coords(basis, &v, height / depth)
+            }
+
+            Self::Stereographic {
+                sphere,
+                axis,
+                basis,
+            } => {
+                let radius = sphere.radius();
+                let q = (p - &sphere.center) / radius;
+                let scale = radius / (1.0 + axis.dot(&q)).max(STEREOGRAPHIC_LIMIT);
+                basis_coords(basis, &q, scale)
+            }
+
+            Self::Basis(basis) => basis_coords(basis, p, 1.0),
+        }
+    }
+}
+
+/// Gets the coordinates of the vertices, after rotating the view and
+/// projecting down into 3D. The rotation is ignored if it's for a different
+/// number of dimensions.
+///
+/// This works out the projection from scratch, so whenever the polytope is
+/// on screen, the [`Projector`] in its [`MeshVertices`] should be used instead.
+pub(crate) fn vertex_coords<'a, I: Iterator<Item = &'a Point>>(
+    poly: &Concrete,
+    vertices: I,
+    projection_type: &ProjectionType,
+    rotation: &Rotation<Float>,
+) -> Vec<[f32; 3]> {
+    Projector::new(poly, projection_type).vertex_coords(poly, vertices, rotation)
+}
+
+/// Gives each face of a triangulation its own copies of its vertices, so that
+/// it can be colored separately from the faces next to it. The copies are
+/// added after the vertices of the polytope, which are left in place for the
+/// wireframe. Returns the face each vertex belongs to, if any.
+fn split_faces(triangulation: &mut Triangulation, vertices: &mut Vec<Point>) -> Vec<Option<usize>> {
+    let mut vertex_faces = vec![None; vertices.len()];
+    let mut copies = HashMap::new();
+
+    for (triangle, &face) in triangulation
+        .triangles
+        .chunks_exact_mut(3)
+        .zip(&triangulation.faces)
+    {
+        for idx in triangle {
+            let old_idx = *idx;
+            *idx = *copies.entry((face, old_idx)).or_insert_with(|| {
+                vertices.push(vertices[old_idx as usize].clone());
+                vertex_faces.push(Some(face));
+                (vertices.len() - 1) as u32
+            });
+        }
+    }
+
+    vertex_faces
+}
+
+/// The vertices a mesh was built from, before being projected into 3D, and the
+/// projection they go through. We store them so that the view can be rotated
+/// without triangulating the faces or working out the projection again.
+#[derive(Default)]
+pub struct MeshVertices {
+    /// The vertices of the polytope, followed by any extra vertices from the
+    /// triangulation of its faces.
+    pub vertices: Vec<Point>,
+
+    /// The projection of the polytope into 3D, before the view is rotated.
+    /// This has to be worked out again whenever the projection type changes.
+    pub projector: Projector,
+}
+
+/// Projects the vertices of a mesh or a wireframe of a polytope again, after
+/// the view has changed.
+pub fn reproject(
+    mesh: &mut Mesh,
+    poly: &Concrete,
+    vertices: &[Point],
+    projector: &Projector,
+    rotation: &Rotation<Float>,
+) {
+    if vertices.is_empty() {
+        return;
+    }
+
+    let vertices = projector.vertex_coords(poly, vertices.iter(), rotation);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices));
+    mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+}
+
+/// Colors the vertices of the mesh of a polytope again after the view has
+/// been rotated, if the colors depend on it.
+pub fn recolor(
+    mesh: &mut Mesh,
+    poly: &Concrete,
+    vertices: &[Point],
+    coloring: &FaceColoring,
+    rotation: &Rotation<Float>,
+) {
+    if vertices.is_empty() || coloring.scheme() != ColorScheme::Depth {
+        return;
+    }
+
+    mesh.set_attribute(
+        Mesh::ATTRIBUTE_COLOR,
+        coloring.vertex_colors(poly, vertices, &[], rotation),
+    );
 }
 
 /// A trait for a polytope for which we can build a mesh.
 pub trait Renderable: ConcretePolytope {
-    /// Builds the mesh of a polytope.
-    fn mesh(&self, projection_type: ProjectionType) -> Mesh {
+    /// Builds the mesh of a polytope with its faces colored, and returns it
+    /// together with the vertices it was built from.
+    fn mesh(
+        &self,
+        projection_type: &ProjectionType,
+        rotation: &Rotation<Float>,
+        coloring: &FaceColoring,
+    ) -> (Mesh, MeshVertices) {
         // If there's no vertices, returns an empty mesh.
         if self.vertex_count() == 0 {
-            return empty_mesh();
-        }
-
-        let mut poly = self.clone();
-        
-        if poly.rank() == 3 {
-            poly = poly.ditope();
-            poly.untangle_faces();
+            return (empty_mesh(), MeshVertices::default());
         }
 
         // Triangulates the polytope's faces, projects the vertices of both the
         // polytope and the triangulation.
-        let triangulation = Triangulation::new(poly.con());
-        let vertices = vertex_coords(
-            self.con(),
-            self.vertices()
-                .iter()
-                .chain(triangulation.extra_vertices.iter()),
-            projection_type,
-        );
+        let mut triangulation = Triangulation::new(&with_faces(self.con()));
+        let mut mesh_vertices = self.vertices().clone();
+        mesh_vertices.append(&mut triangulation.extra_vertices);
+
+        // Faces that get a single color each need their own vertices.
+        let vertex_faces = if coloring.by_face() {
+            split_faces(&mut triangulation, &mut mesh_vertices)
+        } else {
+            Vec::new()
+        };
+
+        let projector = Projector::new(self.con(), projection_type);
+        let vertices = projector.vertex_coords(self.con(), mesh_vertices.iter(), rotation);
+        let colors = coloring.vertex_colors(self.con(), &mesh_vertices, &vertex_faces, rotation);
 
         // Builds the actual mesh.
         let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
         mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 1.0]; vertices.len()]);
         mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices));
         mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+        mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, colors);
         mesh.set_indices(Some(Indices::U32(triangulation.triangles)));
 
-        mesh
+        (
+            mesh,
+            MeshVertices {
+                vertices: mesh_vertices,
+                projector,
+            },
+        )
     }
 
-    /// Builds the wireframe of a polytope.
-    fn wireframe(&self, projection_type: ProjectionType) -> Mesh {
+    /// Builds the wireframe of a polytope, with the same projection as its
+    /// mesh.
+    fn wireframe(&self, projector: &Projector, rotation: &Rotation<Float>) -> Mesh {
         let vertex_count = self.vertex_count();
 
         // If there's no vertices, returns an empty mesh.
@@ -302,7 +759,7 @@ pub trait Renderable: ConcretePolytope {
         let edge_count = self.edge_count();
 
         // We add a single vertex so that Miratope doesn't crash.
-        let vertices = vertex_coords(self.con(), self.vertices().iter(), projection_type);
+        let vertices = projector.vertex_coords(self.con(), self.vertices().iter(), rotation);
         let mut indices = Vec::with_capacity(edge_count * 2);
 
         // Adds the edges to the wireframe.
@@ -325,6 +782,7 @@ pub trait Renderable: ConcretePolytope {
         mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, normals(&vertices));
         mesh.set_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
         mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; vertex_count]);
+        mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; vertex_count]);
         mesh.set_indices(Some(Indices::U16(indices)));
 
         mesh
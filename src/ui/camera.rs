@@ -10,6 +10,9 @@ use bevy::{
 };
 use bevy_egui::{egui::CtxRef, EguiContext};
 
+use super::rotation;
+use crate::Vector;
+
 /// The plugin handling all camera input.
 pub struct InputPlugin;
 
@@ -24,13 +27,31 @@ impl Plugin for InputPlugin {
     }
 }
 
-#[derive(Clone, Copy)]
+/// The way in which polytopes are projected down into 3D.
+#[derive(Clone, PartialEq)]
 pub enum ProjectionType {
     /// We're projecting orthogonally.
     Orthogonal,
 
     /// We're projecting from a point.
     Perspective,
+
+    /// We're building a Schlegel diagram, projecting from a point right beyond
+    /// a facet onto its hyperplane.
+    Schlegel {
+        /// The index of the facet.
+        facet: usize,
+    },
+
+    /// We're projecting stereographically from the circumsphere.
+    Stereographic,
+
+    /// We're projecting orthogonally onto the subspace spanned by some
+    /// vectors, and taking coordinates along them.
+    Subspace {
+        /// The vectors spanning the subspace.
+        basis: Vec<Vector>,
+    },
 }
 
 impl Default for ProjectionType {
@@ -40,17 +61,36 @@ impl Default for ProjectionType {
 }
 
 impl ProjectionType {
-    /// Flips the projection type.
-    pub fn flip(&mut self) {
+    /// Returns the projection onto the first three coordinate axes of a space
+    /// with a given number of dimensions.
+    pub fn subspace(dim: usize) -> Self {
+        Self::Subspace {
+            basis: (0..3)
+                .map(|i| Vector::from_fn(dim, |j, _| if i == j { 1.0 } else { 0.0 }))
+                .collect(),
+        }
+    }
+
+    /// Returns the name of the projection type.
+    pub fn name(&self) -> &'static str {
         match self {
-            Self::Orthogonal => *self = Self::Perspective,
-            Self::Perspective => *self = Self::Orthogonal,
+            Self::Orthogonal => "Orthogonal",
+            Self::Perspective => "Perspective",
+            Self::Schlegel { .. } => "Schlegel diagram",
+            Self::Stereographic => "Stereographic",
+            Self::Subspace { .. } => "Orthogonal onto subspace",
         }
     }
 
-    /// Returns whether the projection type is `Orthogonal`.
-    pub fn is_orthogonal(&self) -> bool {
-        matches!(self, Self::Orthogonal)
+    /// Returns whether the projection type has any settings.
+    pub fn has_settings(&self) -> bool {
+        matches!(self, Self::Schlegel { .. } | Self::Subspace { .. })
+    }
+
+    /// Returns whether two projection types are of the same kind, regardless
+    /// of their settings.
+    pub fn same_kind(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
     }
 }
 
@@ -190,14 +230,21 @@ impl CameraInputEvent {
         (real_scale, scale)
     }
 
-    /// Processes camera events coming from the mouse buttons.
+    /// Processes camera events coming from the mouse buttons. Dragging while
+    /// holding the rotation modifier rotates the view in four or more
+    /// dimensions instead, so we ignore it.
     fn cam_events_from_mouse(
+        keyboard: &Input<KeyCode>,
         mouse_button: &Input<MouseButton>,
         mut mouse_move: EventReader<'_, '_, MouseMotion>,
         height: f32,
         real_scale: f32,
         cam_inputs: &mut EventWriter<'_, '_, Self>,
     ) {
+        if rotation::modifier_pressed(keyboard) {
+            return;
+        }
+
         if mouse_button.pressed(MouseButton::Left) || mouse_button.pressed(MouseButton::Right) {
             for MouseMotion { mut delta } in mouse_move.iter() {
                 delta.x /= height;
@@ -249,6 +296,7 @@ fn add_cam_input_events(
     // Omit any events if the UI will process them instead.
     if !ctx.wants_pointer_input() {
         CameraInputEvent::cam_events_from_mouse(
+            &keyboard,
             &mouse_button,
             mouse_move,
             height,
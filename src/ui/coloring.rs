@@ -0,0 +1,375 @@
+//! Contains the methods to color the faces of polytopes in the 3D view.
+//!
+//! Faces are either colored by some class they belong to, like their element
+//! type, in which case each class gets a color from a palette, or by their
+//! depth along the fourth axis, in which case the color of each vertex is taken
+//! from a gradient and blended over the faces.
+
+use crate::mesh::{MeshVertices, Renderable};
+use crate::{ui::camera::ProjectionType, Concrete, Float, Point, EPS};
+
+use super::rotation::{axis_name, ViewRotation};
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, Ui};
+use miratope_core::{
+    abs::Ranked,
+    conc::{element_types::EL_SUFFIXES, ConcretePolytope},
+    geometry::Rotation,
+    Polytope,
+};
+use vec_like::VecLike;
+
+/// The color of faces that aren't colored by any scheme.
+const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+
+/// The number of colors from the gradient shown in the legend when coloring by
+/// depth.
+const GRADIENT_STOPS: usize = 5;
+
+/// The plugin that updates the face colors of polytopes.
+pub struct ColoringPlugin;
+
+impl Plugin for ColoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FaceColoring>().add_system_to_stage(
+            CoreStage::PostUpdate,
+            recolor_polytopes.system().after("update_changed_polytopes"),
+        );
+    }
+}
+
+/// The ways in which the faces of a polytope can be colored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorScheme {
+    /// Every face is white.
+    None,
+
+    /// Faces of polyhedra are colored by their element type, and faces of
+    /// higher polytopes by the element type of their facets.
+    ElementType,
+
+    /// Faces are colored by the orbit of their facets under the symmetry
+    /// group.
+    FacetOrbit,
+
+    /// Faces are colored by their number of sides.
+    FaceSize,
+
+    /// Faces are colored by the depth of their vertices along the fourth
+    /// axis, after the view is rotated.
+    Depth,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl ColorScheme {
+    /// All of the color schemes, in the order they're shown.
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::ElementType,
+        Self::FacetOrbit,
+        Self::FaceSize,
+        Self::Depth,
+    ];
+
+    /// The name of the color scheme, as shown on the UI.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::ElementType => "Element type",
+            Self::FacetOrbit => "Facet orbit",
+            Self::FaceSize => "Face size",
+            Self::Depth => "Depth",
+        }
+    }
+}
+
+/// Returns the color from the palette for a given class. Consecutive classes
+/// have their hues a golden angle apart, so that any number of them are easy
+/// to tell apart.
+pub fn palette(class: usize) -> Color {
+    Color::hsl((class as f32 * 137.508) % 360.0, 0.65, 0.6)
+}
+
+/// Returns the color from the depth gradient at a given position between 0
+/// and 1, going from blue to red.
+pub fn gradient(t: Float) -> Color {
+    Color::hsl(240.0 * (1.0 - t.clamp(0.0, 1.0) as f32), 0.7, 0.55)
+}
+
+/// Converts a color into a color for the UI.
+fn egui_color(color: Color) -> egui::Color32 {
+    let [r, g, b, _] = color.as_rgba_f32();
+    egui::Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+/// An entry of the legend, shown in the right panel.
+#[derive(Clone, Debug)]
+pub struct LegendEntry {
+    /// The color of the entry.
+    pub color: Color,
+
+    /// What the color stands for.
+    pub label: String,
+}
+
+/// The resource that stores how the faces of the polytope are colored.
+#[derive(Default)]
+pub struct FaceColoring {
+    /// The color scheme.
+    scheme: ColorScheme,
+
+    /// The class of each face of the polytope returned by
+    /// [`with_faces`](crate::mesh::with_faces), as an index into the legend.
+    /// This is empty unless faces are colored by class.
+    classes: Vec<usize>,
+
+    /// The legend for the colors.
+    legend: Vec<LegendEntry>,
+
+    /// The axis along which the depth is measured.
+    depth_axis: usize,
+
+    /// The greatest distance from a vertex to the origin, by which depths
+    /// are scaled.
+    radius: Float,
+}
+
+impl FaceColoring {
+    /// Returns the color scheme.
+    pub fn scheme(&self) -> ColorScheme {
+        self.scheme
+    }
+
+    /// Returns the legend for the colors.
+    pub fn legend(&self) -> &[LegendEntry] {
+        &self.legend
+    }
+
+    /// Returns whether every face gets a single color, so that faces need
+    /// their own copies of their vertices.
+    pub fn by_face(&self) -> bool {
+        !self.classes.is_empty()
+    }
+
+    /// Returns the facet that a face of a polytope belongs to. If a face
+    /// belongs to many, we just take the first one.
+    fn facet_of(poly: &Concrete, face: usize) -> usize {
+        let mut idx = face;
+        for r in 3..poly.rank() - 1 {
+            idx = poly[(r, idx)].sups[0];
+        }
+
+        idx
+    }
+
+    /// Splits the faces into classes given by the type of their facets, and
+    /// labels each class by the number of elements of that type.
+    fn facet_classes(&mut self, faces: &Concrete, types: &[usize]) {
+        let rank = faces.rank();
+        let facet_count = faces[rank - 1].len();
+        let class_count = types.iter().copied().max().map_or(0, |t| t + 1);
+        let mut counts = vec![0; class_count];
+        let mut sides = vec![0; class_count];
+
+        for (idx, &t) in types.iter().enumerate().take(facet_count) {
+            counts[t] += 1;
+            sides[t] = faces[(rank - 1, idx)].subs.len();
+        }
+
+        let suffix = EL_SUFFIXES.get(rank - 1).copied().unwrap_or("");
+        self.legend = counts
+            .into_iter()
+            .zip(sides)
+            .enumerate()
+            .map(|(t, (count, sides))| LegendEntry {
+                color: palette(t),
+                label: format!("{} × {}-{}", count, sides, suffix),
+            })
+            .collect();
+
+        self.classes = (0..faces[3].len())
+            .map(|face| types[Self::facet_of(faces, face)])
+            .collect();
+    }
+
+    /// Splits the faces into classes given by their number of sides.
+    fn size_classes(&mut self, faces: &Concrete) {
+        let sizes: Vec<_> = faces[3].iter().map(|face| face.subs.len()).collect();
+        let mut distinct = sizes.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+
+        self.legend = distinct
+            .iter()
+            .enumerate()
+            .map(|(class, &sides)| LegendEntry {
+                color: palette(class),
+                label: format!(
+                    "{} × {}-gon",
+                    sizes.iter().filter(|&&s| s == sides).count(),
+                    sides
+                ),
+            })
+            .collect();
+
+        self.classes = sizes
+            .iter()
+            .map(|sides| distinct.binary_search(sides).unwrap())
+            .collect();
+    }
+
+    /// Sets up the gradient for the depths of the vertices of a polytope.
+    fn depth_gradient(&mut self, poly: &Concrete) {
+        let dim = poly.dim_or();
+        self.depth_axis = if dim >= 4 { 3 } else { dim.saturating_sub(1) };
+        self.radius = poly.vertices.iter().map(|v| v.norm()).fold(0.0, Float::max);
+
+        let axis = axis_name(self.depth_axis);
+        self.legend = (0..GRADIENT_STOPS)
+            .rev()
+            .map(|i| {
+                let t = i as Float / (GRADIENT_STOPS - 1) as Float;
+                LegendEntry {
+                    color: gradient(t),
+                    label: format!("{} = {:.3}", axis, self.radius * (2.0 * t - 1.0)),
+                }
+            })
+            .collect();
+    }
+
+    /// Works out the colors of the faces of a polytope, after it or the color
+    /// scheme changes.
+    pub fn update(&mut self, poly: &Concrete) {
+        self.classes.clear();
+        self.legend.clear();
+
+        let mut faces = crate::mesh::with_faces(poly);
+        if faces.rank() < 4 {
+            return;
+        }
+        faces.element_sort();
+        let rank = faces.rank();
+
+        match self.scheme {
+            ColorScheme::None => {}
+            ColorScheme::ElementType => {
+                let types = faces.types_of_elements();
+                self.facet_classes(&faces, &types[rank - 1]);
+            }
+            ColorScheme::FacetOrbit => {
+                // Falls back to the element types if the symmetry group can't
+                // be computed.
+                let orbits = faces.element_orbits_common().map(|(_, orbits)| orbits);
+                if orbits.is_none() {
+                    eprintln!("Facet orbits failed: using the element types instead.");
+                }
+
+                let types = orbits.unwrap_or_else(|| faces.types_of_elements());
+                self.facet_classes(&faces, &types[rank - 1]);
+            }
+            ColorScheme::FaceSize => self.size_classes(&faces),
+            ColorScheme::Depth => self.depth_gradient(poly),
+        }
+    }
+
+    /// Returns the colors of the vertices of a mesh, given the face each of
+    /// them belongs to, if any.
+    pub fn vertex_colors(
+        &self,
+        poly: &Concrete,
+        vertices: &[Point],
+        vertex_faces: &[Option<usize>],
+        rotation: &Rotation<Float>,
+    ) -> Vec<[f32; 4]> {
+        if self.scheme != ColorScheme::Depth {
+            return (0..vertices.len())
+                .map(|idx| {
+                    match vertex_faces
+                        .get(idx)
+                        .copied()
+                        .flatten()
+                        .and_then(|face| self.classes.get(face))
+                    {
+                        Some(&class) => palette(class).as_linear_rgba_f32(),
+                        None => WHITE,
+                    }
+                })
+                .collect();
+        }
+
+        // Depths are measured after rotating the view.
+        let rotation = (rotation.dim() == poly.dim_or()).then(|| rotation);
+        vertices
+            .iter()
+            .map(|p| {
+                let depth = match rotation {
+                    Some(rotation) => rotation.apply(p).get(self.depth_axis).copied(),
+                    None => p.get(self.depth_axis).copied(),
+                }
+                .unwrap_or(0.0);
+
+                let t = if self.radius < EPS {
+                    0.5
+                } else {
+                    (depth / self.radius + 1.0) / 2.0
+                };
+
+                gradient(t).as_linear_rgba_f32()
+            })
+            .collect()
+    }
+}
+
+/// Shows the color scheme and its legend in the right panel.
+pub fn show_face_colors(ui: &mut Ui, coloring: &mut ResMut<'_, FaceColoring>) {
+    // We only write the scheme back if it's changed, so that the polytope
+    // isn't colored again every frame.
+    let mut scheme = coloring.scheme;
+    ui.horizontal_wrapped(|ui| {
+        for new_scheme in ColorScheme::ALL {
+            ui.radio_value(&mut scheme, new_scheme, new_scheme.name());
+        }
+    });
+
+    if scheme != coloring.scheme {
+        coloring.scheme = scheme;
+    }
+
+    for entry in coloring.legend() {
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(16.0, 16.0), egui::Sense::hover());
+            ui.painter().rect_filled(rect, 2.0, egui_color(entry.color));
+            ui.label(entry.label.as_str());
+        });
+    }
+}
+
+/// The system that colors the polytope again whenever the color scheme
+/// changes. Polytopes that were just changed have already been colored.
+fn recolor_polytopes(
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    mut polies: Query<'_, '_, (&Concrete, &mut MeshVertices, &Handle<Mesh>)>,
+    changed: Query<'_, '_, (), Changed<Concrete>>,
+    projection_type: Res<'_, ProjectionType>,
+    view_rotation: Res<'_, ViewRotation>,
+    mut coloring: ResMut<'_, FaceColoring>,
+) {
+    if !coloring.is_changed() || changed.iter().next().is_some() {
+        return;
+    }
+
+    for (poly, mut vertices, mesh_handle) in polies.iter_mut() {
+        coloring.update(poly);
+
+        let (mesh, mesh_vertices) =
+            poly.mesh(&projection_type, view_rotation.rotation(), &coloring);
+        *meshes.get_mut(mesh_handle).unwrap() = mesh;
+        *vertices = mesh_vertices;
+    }
+}
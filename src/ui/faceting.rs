@@ -0,0 +1,252 @@
+//! Runs the faceting algorithm in the background, and shows its progress.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, Receiver, Sender, TryRecvError},
+    Arc, Mutex,
+};
+
+use super::{memory::Memory, window::FacetingSettings};
+use crate::{Concrete, Point};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use bevy_egui::{egui, EguiContext};
+use miratope_core::conc::faceting::{FacetingProgress, FacetingStage, GroupEnum};
+
+/// The maximum number of log messages kept in the progress window.
+const LOG_LEN: usize = 100;
+
+/// The plugin in charge of running facetings in the background.
+pub struct FacetingPlugin;
+
+impl Plugin for FacetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FacetingJob>().add_system(
+            show_faceting_job
+                .system()
+                .label("show_faceting_job")
+                .after("show_top_panel"),
+        );
+    }
+}
+
+/// An update sent by a faceting running in the background.
+enum FacetingEvent {
+    /// The faceting entered a new stage.
+    Stage(FacetingStage),
+
+    /// A message that should be logged.
+    Message(String),
+
+    /// The current state of a long computation.
+    Status(String),
+
+    /// A faceting or facet was found.
+    Output(Concrete, Option<String>),
+}
+
+/// Sends the progress of a faceting back to the main thread.
+struct ChannelProgress {
+    /// The sender for the updates.
+    sender: Sender<FacetingEvent>,
+
+    /// Whether the user has cancelled the faceting.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ChannelProgress {
+    /// Sends an update. If the receiver is gone, there's nobody to tell.
+    fn send(&self, event: FacetingEvent) {
+        self.sender.send(event).ok();
+    }
+}
+
+impl FacetingProgress for ChannelProgress {
+    fn stage(&mut self, stage: FacetingStage) {
+        self.send(FacetingEvent::Stage(stage));
+    }
+
+    fn message(&mut self, message: String) {
+        self.send(FacetingEvent::Message(message));
+    }
+
+    fn status(&mut self, status: String) {
+        self.send(FacetingEvent::Status(status));
+    }
+
+    fn output(&mut self, poly: Concrete, label: Option<String>) {
+        self.send(FacetingEvent::Output(poly, label));
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A faceting running in the background.
+struct RunningFaceting {
+    /// The task running the faceting. Dropping it would stop the faceting.
+    _task: Task<()>,
+
+    /// The receiver for the updates from the faceting.
+    receiver: Mutex<Receiver<FacetingEvent>>,
+
+    /// Whether the user has cancelled the faceting.
+    cancelled: Arc<AtomicBool>,
+
+    /// Whether the faceting has stopped, either because it completed or because
+    /// it was cancelled.
+    finished: bool,
+
+    /// The current stage of the faceting.
+    stage: Option<FacetingStage>,
+
+    /// The current state of the stage.
+    status: String,
+
+    /// The latest messages from the faceting.
+    log: Vec<String>,
+
+    /// The number of facetings and facets found so far.
+    found: usize,
+}
+
+/// The faceting running in the background, if any.
+#[derive(Default)]
+pub struct FacetingJob(Option<RunningFaceting>);
+
+impl FacetingJob {
+    /// Returns whether a faceting is currently running.
+    pub fn is_running(&self) -> bool {
+        matches!(&self.0, Some(job) if !job.finished)
+    }
+
+    /// Starts faceting a polytope in the background with the given settings.
+    pub fn start(
+        &mut self,
+        task_pool: &AsyncComputeTaskPool,
+        mut poly: Concrete,
+        vertices: Vec<Point>,
+        symmetry: GroupEnum,
+        settings: &FacetingSettings,
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut progress = ChannelProgress {
+            sender,
+            cancelled: Arc::clone(&cancelled),
+        };
+
+        let options = settings.options();
+
+        // The sender is dropped once the faceting stops, even if it panics,
+        // which lets us know that it's finished.
+        let task = task_pool.spawn(async move {
+            poly.faceting_with_progress(vertices, symmetry, &options, &mut progress);
+        });
+
+        self.0 = Some(RunningFaceting {
+            _task: task,
+            receiver: Mutex::new(receiver),
+            cancelled,
+            finished: false,
+            stage: None,
+            status: String::new(),
+            log: Vec::new(),
+            found: 0,
+        });
+    }
+}
+
+/// The system that receives the updates from the faceting running in the
+/// background, and shows its progress.
+pub fn show_faceting_job(
+    egui_ctx: Res<'_, EguiContext>,
+    mut faceting_job: ResMut<'_, FacetingJob>,
+    mut memory: ResMut<'_, Memory>,
+) {
+    let job = match &mut faceting_job.0 {
+        Some(job) => job,
+        None => return,
+    };
+
+    // Receives all pending updates. Facetings go straight into memory.
+    if !job.finished {
+        let receiver = job.receiver.get_mut().unwrap();
+        loop {
+            match receiver.try_recv() {
+                Ok(FacetingEvent::Stage(stage)) => {
+                    job.stage = Some(stage);
+                    job.status.clear();
+                }
+                Ok(FacetingEvent::Message(message)) => {
+                    if job.log.len() == LOG_LEN {
+                        job.log.remove(0);
+                    }
+                    job.log.push(message);
+                }
+                Ok(FacetingEvent::Status(status)) => job.status = status,
+                Ok(FacetingEvent::Output(poly, label)) => {
+                    memory.push((poly, label.map(Into::into)));
+                    job.found += 1;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    job.finished = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut close = false;
+
+    egui::Window::new("Faceting")
+        .collapsible(true)
+        .resizable(true)
+        .show(egui_ctx.ctx(), |ui| {
+            let stage = match job.stage {
+                Some(FacetingStage::Done) => "Faceting complete".to_string(),
+                Some(stage) if job.finished => {
+                    if job.cancelled.load(Ordering::Relaxed) {
+                        format!("Cancelled while {}", stage.to_string().to_lowercase())
+                    } else {
+                        format!("Stopped while {}", stage.to_string().to_lowercase())
+                    }
+                }
+                Some(stage) => format!("{}...", stage),
+                None => "Starting...".to_string(),
+            };
+            ui.heading(stage);
+
+            if !job.finished {
+                ui.label(&job.status);
+            }
+            ui.label(format!("{} results found", job.found));
+
+            ui.separator();
+            egui::containers::ScrollArea::from_max_height(200.).show(ui, |ui| {
+                for message in &job.log {
+                    ui.label(message);
+                }
+            });
+            ui.separator();
+
+            if job.finished {
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            } else if job.cancelled.load(Ordering::Relaxed) {
+                ui.label("Cancelling...");
+            } else if ui.button("Cancel").clicked() {
+                job.cancelled.store(true, Ordering::Relaxed);
+            }
+        });
+
+    if close {
+        faceting_job.0 = None;
+    }
+}
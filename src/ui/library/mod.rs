@@ -6,7 +6,7 @@ use std::{
     path::PathBuf,
 };
 
-use super::{config::LibPath, main_window::PolyName};
+use super::{config::LibPath, main_window::PolyName, top_panel::LoadError};
 use crate::Concrete;
 use miratope_core::file::FromFile;
 use special::*;
@@ -250,6 +250,7 @@ fn show_library(
     mut poly_name: ResMut<'_, PolyName>,
     mut library: ResMut<'_, Option<Library>>,
     lib_path: Res<'_, LibPath>,
+    mut load_error: ResMut<'_, LoadError>,
 ) {
     // Shows the polytope library.
     if let Some(library) = library.as_mut() {
@@ -263,14 +264,19 @@ fn show_library(
                         ShowResult::None => {}
 
                         // Loads a selected file.
-                        ShowResult::Load(file) => match Concrete::from_path(&file) {
+                        ShowResult::Load(file) => match Concrete::from_path_with(&file, true) {
                             Ok(q) => {
                                 *query.iter_mut().next().unwrap() = q;
                                 let path_buf = PathBuf::from(file);
-                                let file_name = path_buf.file_name().unwrap().to_str().unwrap();
-                                poly_name.0 = file_name[..file_name.len()-4].into();
+                                poly_name.0 = Concrete::name_from_off(&path_buf).unwrap_or_else(|| {
+                                    let file_name = path_buf.file_name().unwrap().to_str().unwrap();
+                                    file_name[..file_name.len()-4].into()
+                                });
                             },
-                            Err(err) => eprintln!("File open failed: {}", err),
+                            Err(err) => {
+                                eprintln!("File open failed: {}", err);
+                                load_error.0 = Some(err.to_string());
+                            }
                         },
 
                         // Loads a special polytope.
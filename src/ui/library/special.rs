@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::Concrete;
 use miratope_core::conc::ConcretePolytope;
-use miratope_core::Polytope;
+use miratope_core::{name::Name, Polytope};
 
 use super::ShowResult;
 
@@ -183,36 +183,24 @@ impl SpecialLibrary {
     }
 
     /// Loads the given special polytope from the library.
-    pub fn load(&self) -> (Concrete, String) {
+    pub fn load(&self) -> (Concrete, Name) {
         match *self {
             // Loads a regular star polygon.
             Self::Polygon(n, d) => (
                 Concrete::star_polygon_with_edge(n, d, 1.0),
-                format!(
-                    "{}{}-gon",
-                    n,
-                    if d > 1 {format!("/{}", d)} else {"".to_string()}
-                )
+                Name::star_polygon(n, d),
             ),
 
             // Loads a uniform polygonal prism.
             Self::Prism(n, d) => (
                 Concrete::uniform_prism(n, d),
-                format!(
-                    "{}{}-gonal prism",
-                    n,
-                    if d > 1 {format!("/{}", d)} else {"".to_string()}
-                )
+                Name::star_polygon(n, d).prism(),
             ),
 
             // Loads a uniform polygonal antiprism.
             Self::Antiprism(n, d) => (
                 Concrete::uniform_antiprism(n, d),
-                format!(
-                    "{}{}-gonal antiprism",
-                    n,
-                    if d > 1 {format!("/{}", d)} else {"".to_string()}
-                )
+                Name::star_polygon(n, d).antiprism(),
             ),
 
             // Loads a uniform polygonal duoprism.
@@ -228,41 +216,31 @@ impl SpecialLibrary {
                         Concrete::duoprism(&p1, &p2)
                     }
                 },
-                format!(
-                    "{}{}-{}{} duoprism",
-                    n1,
-                    if d1 > 1 {format!("/{}", d1)} else {"".to_string()},
-                    n2,
-                    if d2 > 1 {format!("/{}", d2)} else {"".to_string()}
-                )
+                Name::Duoprism(vec![Name::star_polygon(n1, d1), Name::star_polygon(n2, d2)]),
             ),
 
             // Loads a uniform polygonal antiprism prism.
             Self::AntiprismPrism(n, d) => (
                 Concrete::uniform_antiprism(n, d).prism(),
-                format!(
-                    "{}{}-gonal antiprism prism",
-                    n,
-                    if d > 1 {format!("/{}", d)} else {"".to_string()}
-                )
+                Name::star_polygon(n, d).antiprism().prism(),
             ),
 
             // Loads a simplex with a given rank.
             Self::Simplex(rank) => (
                 Concrete::simplex((rank + 1) as usize),
-                format!("{}-simplex", rank)
+                Name::Simplex((rank + 1) as usize),
             ),
 
             // Loads a hypercube with a given rank.
             Self::Hypercube(rank) => (
                 Concrete::hypercube((rank + 1) as usize),
-                format!("{}-cube", rank)
+                Name::Hypercube((rank + 1) as usize),
             ),
 
             // Loads an orthoplex with a given rank.
             Self::Orthoplex(rank) => (
                 Concrete::orthoplex((rank + 1) as usize),
-                format!("{}-orthoplex", rank)
+                Name::Orthoplex((rank + 1) as usize),
             ),
         }
     }
@@ -1,13 +1,17 @@
 //! The systems that update the main window.
 
 use super::right_panel::ElementTypesRes;
-use super::{camera::ProjectionType, top_panel::SectionState};
-use crate::mesh::Renderable;
+use super::selection::SelectionHighlight;
+use super::{
+    camera::ProjectionType, coloring::FaceColoring, rotation::ViewRotation,
+    top_panel::SectionState,
+};
+use crate::mesh::{MeshVertices, Renderable};
 use crate::Concrete;
 
 use bevy::prelude::*;
 use bevy_egui::EguiSettings;
-use miratope_core::Polytope;
+use miratope_core::{name::Name, Polytope};
 use miratope_core::abs::Ranked;
 
 /// The plugin in charge of the Miratope main window, and of drawing the
@@ -18,23 +22,28 @@ impl Plugin for MainWindowPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_to_stage(CoreStage::PreUpdate, update_visible.system())
             .add_system(update_scale_factor.system())
-            .add_system_to_stage(CoreStage::PostUpdate, update_changed_polytopes.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_changed_polytopes
+                    .system()
+                    .label("update_changed_polytopes"),
+            )
             .init_resource::<PolyName>();
     }
 }
 
-pub struct PolyName(pub String);
+pub struct PolyName(pub Name);
 
 impl Default for PolyName {
     fn default() -> PolyName {
-        PolyName("default".to_string())
+        PolyName(Name::default())
     }
 }
 
 pub fn update_visible(
     keyboard: Res<'_, Input<KeyCode>>,
     mut polies_vis: Query<'_, '_, &mut Visible, With<Concrete>>,
-    mut wfs_vis: Query<'_, '_, &mut Visible, Without<Concrete>>,
+    mut wfs_vis: Query<'_, '_, &mut Visible, (Without<Concrete>, Without<SelectionHighlight>)>,
 ) {
     if keyboard.just_pressed(KeyCode::V) {
         if let Some(mut visible) = polies_vis.iter_mut().next() {
@@ -61,7 +70,12 @@ pub fn update_scale_factor(mut egui_settings: ResMut<'_, EguiSettings>, windows:
 /// Updates polytopes after an operation.
 pub fn update_changed_polytopes(
     mut meshes: ResMut<'_, Assets<Mesh>>,
-    mut polies: Query<'_, '_, (&mut Concrete, &Handle<Mesh>, &Children), Changed<Concrete>>,
+    mut polies: Query<
+        '_,
+        '_,
+        (&mut Concrete, &mut MeshVertices, &Handle<Mesh>, &Children),
+        Changed<Concrete>,
+    >,
     wfs: Query<'_, '_, &Handle<Mesh>, Without<Concrete>>,
     mut windows: ResMut<'_, Windows>,
     mut section_state: ResMut<'_, SectionState>,
@@ -69,8 +83,12 @@ pub fn update_changed_polytopes(
     name: Res<'_, PolyName>,
 
     orthogonal: Res<'_, ProjectionType>,
+    view_rotation: Res<'_, ViewRotation>,
+    mut coloring: ResMut<'_, FaceColoring>,
 ) {
-    for (mut poly, mesh_handle, children) in polies.iter_mut() {
+    let rotation = view_rotation.rotation();
+
+    for (mut poly, mut vertices, mesh_handle, children) in polies.iter_mut() {
         poly.untangle_faces();
         if cfg!(debug_assertions) {
             poly.assert_valid();
@@ -82,12 +100,15 @@ pub fn update_changed_polytopes(
             element_types.main_updating = false;
         }
 
-        *meshes.get_mut(mesh_handle).unwrap() = poly.mesh(*orthogonal);
+        coloring.update(&poly);
+        let (mesh, mesh_vertices) = poly.mesh(&orthogonal, rotation, &coloring);
+        *meshes.get_mut(mesh_handle).unwrap() = mesh;
+        *vertices = mesh_vertices;
 
         // Updates all wireframes.
         for child in children.iter() {
             if let Ok(wf_handle) = wfs.get_component::<Handle<Mesh>>(*child) {
-                *meshes.get_mut(wf_handle).unwrap() = poly.wireframe(*orthogonal);
+                *meshes.get_mut(wf_handle).unwrap() = poly.wireframe(&vertices.projector, rotation);
             }
         }
 
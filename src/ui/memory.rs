@@ -4,15 +4,16 @@ use bevy::prelude::{Query, Res, ResMut};
 use bevy_egui::{egui, EguiContext};
 
 use crate::Concrete;
+use miratope_core::name::Name;
 
 use super::main_window::PolyName;
 
 /// Represents the memory slots to store polytopes.
 #[derive(Default)]
-pub struct Memory(pub Vec<Option<(Concrete, Option<String>)>>);
+pub struct Memory(pub Vec<Option<(Concrete, Option<Name>)>>);
 
 impl std::ops::Index<usize> for Memory {
-    type Output = Option<(Concrete, Option<String>)>;
+    type Output = Option<(Concrete, Option<Name>)>;
 
     fn index(&self, index: usize) -> &Self::Output {
         &self.0[index]
@@ -31,12 +32,12 @@ impl Memory {
     }
 
     /// Returns an iterator over the memory slots.
-    pub fn iter(&self) -> std::slice::Iter<'_, Option<(Concrete, Option<String>)>> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Option<(Concrete, Option<Name>)>> {
         self.0.iter()
     }
 
     /// Appends an element.
-    pub fn push(&mut self, a: (Concrete, Option<String>)) {
+    pub fn push(&mut self, a: (Concrete, Option<Name>)) {
         self.0.push(Some(a));
     }
 
@@ -85,15 +86,15 @@ impl Memory {
                                 ui.label(format!("{}:", idx));
                                 let name = match label {
                                     None => {
-                                        slot_label(idx)
+                                        slot_label(idx).into()
                                     }
                                     
                                     Some(name) => {
-                                        name.to_string()
+                                        name.clone()
                                     }
                                 };
 
-                                ui.label(&name);
+                                ui.label(name.to_string());
 
                                 // Clones a polytope from memory.
                                 if ui.button("Load").clicked() {
@@ -5,10 +5,14 @@ use approx::abs_diff_eq;
 use bevy_egui::egui::{self, Ui, Widget};
 
 pub mod camera;
+pub mod coloring;
 pub mod config;
+pub mod faceting;
 pub mod library;
 pub mod main_window;
 pub mod memory;
+pub mod rotation;
+pub mod selection;
 pub mod wiki;
 pub mod window;
 pub mod top_panel;
@@ -21,12 +25,16 @@ impl bevy::prelude::PluginGroup for MiratopePlugins {
     fn build(&mut self, group: &mut bevy::app::PluginGroupBuilder) {
         group
             .add(camera::InputPlugin)
+            .add(rotation::RotationPlugin)
+            .add(coloring::ColoringPlugin)
+            .add(selection::SelectionPlugin)
             .add(config::ConfigPlugin)
             .add(window::WindowPlugin)
             .add(library::LibraryPlugin)
             .add(main_window::MainWindowPlugin)
             .add(top_panel::TopPanelPlugin)
-            .add(right_panel::RightPanelPlugin);
+            .add(right_panel::RightPanelPlugin)
+            .add(faceting::FacetingPlugin);
     }
 }
 
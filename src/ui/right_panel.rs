@@ -7,10 +7,10 @@ use bevy_egui::{
     egui,
     EguiContext,
 };
-use miratope_core::{conc::{element_types::{EL_NAMES, EL_SUFFIXES}, ConcretePolytope}, Polytope, abs::Ranked, geometry::{Subspace, Point, Vector}};
+use miratope_core::{conc::{element_types::{ElementType, EL_NAMES, EL_SUFFIXES}, ConcretePolytope}, Polytope, abs::Ranked, geometry::{Subspace, Point, Vector}, name::Name};
 use vec_like::VecLike;
 
-use super::{top_panel::{SectionDirection, SectionState}, main_window::PolyName};
+use super::{top_panel::{SectionDirection, SectionState}, main_window::PolyName, coloring::{self, FaceColoring}, memory::Memory, selection::{self, Selection}};
 
 #[derive(Clone, Copy, Debug)]
 pub struct ElementTypeWithData {
@@ -28,6 +28,10 @@ pub struct ElementTypeWithData {
 
     /// The circumradius of the element, or distance from the origin if it's a vertex.
     radius: Option<f64>,
+
+    /// The order of the stabilizer of the element, if the types are orbits
+    /// under the symmetry group.
+    stabilizer: Option<usize>,
 }
 
 #[derive(Clone)]
@@ -40,7 +44,7 @@ pub struct ElementTypesRes {
     pub poly: Concrete,
 
     /// The name of the polytope.
-    pub poly_name: String,
+    pub poly_name: Name,
 
     /// The element types.
     pub types: Vec<Vec<ElementTypeWithData>>,
@@ -53,6 +57,10 @@ pub struct ElementTypesRes {
 
     /// Whether we're updating `main`.
     pub main_updating: bool,
+
+    /// Whether the element types are computed as orbits under the symmetry
+    /// group, rather than by a heuristic.
+    symmetry: bool,
 }
 
 impl Default for ElementTypesRes {
@@ -60,24 +68,33 @@ impl Default for ElementTypesRes {
         ElementTypesRes {
             active: false,
             poly: Concrete::nullitope(),
-            poly_name: "nullitope".to_string(),
+            poly_name: Name::Nullitope,
             types: Vec::new(),
             components: None,
             main: true,
             main_updating: false,
+            symmetry: false,
         }
     }
 }
 
 impl ElementTypesRes {
-    fn from_poly(&self, poly: Mut<'_, Concrete>, poly_name: String) -> ElementTypesRes {
+    fn from_poly(&self, poly: Mut<'_, Concrete>, poly_name: Name) -> ElementTypesRes {
         let mut poly = poly.clone();
         poly.element_sort();
 
-        let plain_types = poly.element_types();
+        // Falls back to the heuristic if the symmetry group can't be computed.
+        let plain_types: Vec<Vec<(ElementType, Option<usize>)>> = match self.symmetry.then(|| poly.element_orbits()).flatten() {
+            Some(orbits) => orbits.into_iter().map(|orbits| {
+                orbits.into_iter().map(|o| (o.into(), Some(o.stabilizer))).collect()
+            }).collect(),
+            None => poly.element_types().into_iter().map(|types| {
+                types.into_iter().map(|t| (t, None)).collect()
+            }).collect(),
+        };
         let mut types_with_data = Vec::new();
     
-        for (r, types) in plain_types.clone().into_iter().enumerate() {
+        for (r, types) in plain_types.into_iter().enumerate() {
             let rank = poly.rank();
             if r == rank {
                 break;
@@ -87,7 +104,7 @@ impl ElementTypesRes {
             let dual_abs = &abs.dual();
             let mut types_with_data_this_rank = Vec::new();
             
-            for t in types {
+            for (t, stabilizer) in types {
                 let idx = t.example;
     
                 let facets = abs[(r, idx)].subs.len();
@@ -110,6 +127,7 @@ impl ElementTypesRes {
                     facets,
                     fig_facets,
                     radius,
+                    stabilizer,
                 });
             }
             types_with_data.push(types_with_data_this_rank);
@@ -123,6 +141,7 @@ impl ElementTypesRes {
             components: None,
             main: true,
             main_updating: false,
+            symmetry: self.symmetry,
         }
     }
 
@@ -160,6 +179,9 @@ pub fn show_right_panel(
     mut element_types: ResMut<'_, ElementTypesRes>,
     mut section_direction: ResMut<'_, Vec<SectionDirection>>,
     section_state: Res<'_, SectionState>,
+    mut face_coloring: ResMut<'_, FaceColoring>,
+    mut selection: ResMut<'_, Selection>,
+    mut memory: ResMut<'_, Memory>,
 
 ) {
     // The right panel.
@@ -184,10 +206,36 @@ pub fn show_right_panel(
                         poly_name.0 = element_types.poly_name.clone();
                     }
                 }
+
+                ui.checkbox(&mut element_types.symmetry, "Use symmetry")
+                    .on_hover_text("Computes the element types as orbits under the symmetry group");
             });
 
             ui.separator();
 
+            // The color scheme for the faces, and its legend.
+            egui::CollapsingHeader::new("Face colors")
+                .default_open(true)
+                .show(ui, |ui| coloring::show_face_colors(ui, &mut face_coloring));
+
+            ui.separator();
+
+            // The element selected in the view.
+            egui::CollapsingHeader::new("Selection")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let poly = query.iter_mut().next();
+                    selection::show_selection(
+                        ui,
+                        &mut selection,
+                        poly.as_deref(),
+                        &poly_name,
+                        &mut memory,
+                    );
+                });
+
+            ui.separator();
+
             if element_types.active {
                 egui::containers::ScrollArea::auto_sized().show(ui, |ui| {
                     for (r, types) in element_types.types.clone().into_iter().enumerate().skip(1) {
@@ -221,7 +269,7 @@ pub fn show_right_panel(
                                             element.flatten();
                                             element.recenter();
                                             *p = element;
-                                            poly_name.0 = format!("Element of {}",element_types.poly_name.clone());
+                                            poly_name.0 = format!("Element of {}", element_types.poly_name).into();
                                         } else {
                                             eprintln!("Element failed: no element at rank {}, index {}", r, i);
                                         }
@@ -240,7 +288,7 @@ pub fn show_right_panel(
                                                 figure.flatten();
                                                 figure.recenter();
                                                 *p = figure;
-                                                poly_name.0 = format!("Figure of {}",element_types.poly_name.clone());
+                                                poly_name.0 = format!("Figure of {}", element_types.poly_name).into();
                                             }
                                             Ok(None) => eprintln!("Figure failed: no element at rank {}, index {}", r, i),
                                             Err(err) => eprintln!("Figure failed: {}", err),
@@ -269,6 +317,10 @@ pub fn show_right_panel(
                                         else {format!("radius {:.10}", radius)}
                                     );
                                 }
+
+                                if let Some(stabilizer) = t.stabilizer {
+                                    ui.label(format!("stabilizer order {}", stabilizer));
+                                }
                             });
                         }
 
@@ -303,11 +355,11 @@ pub fn show_right_panel(
                             )).clicked() {
                                 if let Some(mut p) = query.iter_mut().next() {
                                     *p = component.clone();
-                                    poly_name.0 = format!(
-                                        "{}{}",
-                                        if components.len() > 1 {"Component of "} else {""},
+                                    poly_name.0 = if components.len() > 1 {
+                                        format!("Component of {}", element_types.poly_name).into()
+                                    } else {
                                         element_types.poly_name.clone()
-                                    );
+                                    };
                                 }
                             }
                         }
@@ -0,0 +1,234 @@
+//! Contains the methods to rotate the view of polytopes in four or more
+//! dimensions.
+//!
+//! These rotations aren't applied to the polytope itself. Rather, they're
+//! applied to its vertices right before they're projected down into 3D, after
+//! which the usual camera controls take over.
+
+use std::f64::consts::TAU;
+
+use crate::mesh::{recolor, reproject, MeshVertices, Projector};
+use crate::{
+    ui::{camera::ProjectionType, coloring::FaceColoring},
+    Concrete,
+};
+
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy_egui::EguiContext;
+use miratope_core::{abs::Ranked, geometry::Rotation};
+
+/// The plugin handling the rotations of the view.
+pub struct RotationPlugin;
+
+impl Plugin for RotationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewRotation>()
+            // We register inputs after the library has been shown, so that we
+            // know whether mouse input should register.
+            .add_system(add_rotation_input.system().after("show_library"))
+            .add_system(animate_rotation.system())
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                reproject_polytopes
+                    .system()
+                    .label("reproject_polytopes")
+                    .after("update_changed_polytopes"),
+            );
+    }
+}
+
+/// Returns the name of a coordinate axis.
+pub fn axis_name(axis: usize) -> String {
+    match axis {
+        0 => "x".to_string(),
+        1 => "y".to_string(),
+        2 => "z".to_string(),
+        3 => "w".to_string(),
+        _ => format!("x{}", axis + 1),
+    }
+}
+
+/// Returns whether the modifier key that makes the mouse rotate the view,
+/// rather than the camera, is pressed.
+pub fn modifier_pressed(keyboard: &Input<KeyCode>) -> bool {
+    keyboard.pressed(KeyCode::LAlt) || keyboard.pressed(KeyCode::RAlt)
+}
+
+/// The rotation applied to the polytope before it's projected into 3D, along
+/// with the settings for rotating it.
+pub struct ViewRotation {
+    /// The rotation of the view.
+    rotation: Rotation<f64>,
+
+    /// The axis that the x, y and z axes get rotated towards when dragging
+    /// the mouse. This is always at least 3.
+    pub axis: usize,
+
+    /// Whether the view is rotated at a constant speed.
+    pub animate: bool,
+
+    /// The plane in which the view is rotated when animated, given by the
+    /// indices of two coordinate axes.
+    pub plane: (usize, usize),
+
+    /// The speed at which the view is rotated when animated, in radians per
+    /// second.
+    pub speed: f64,
+}
+
+impl Default for ViewRotation {
+    fn default() -> Self {
+        Self {
+            rotation: Rotation::identity(0),
+            axis: 3,
+            animate: false,
+            plane: (0, 3),
+            speed: 0.5,
+        }
+    }
+}
+
+impl ViewRotation {
+    /// Returns the rotation of the view.
+    pub fn rotation(&self) -> &Rotation<f64> {
+        &self.rotation
+    }
+
+    /// Returns the number of dimensions of the view.
+    pub fn dim(&self) -> usize {
+        self.rotation.dim()
+    }
+
+    /// Resets the rotation of the view, and the settings that depend on the
+    /// number of dimensions.
+    pub fn reset(&mut self, dim: usize) {
+        self.rotation = Rotation::identity(dim);
+
+        if self.axis >= dim {
+            self.axis = 3;
+        }
+        if self.plane.0 >= dim || self.plane.1 >= dim {
+            self.plane = (0, 3);
+        }
+    }
+
+    /// Rotates the view in the plane of two coordinate axes, if the view has
+    /// at least four dimensions.
+    pub fn rotate(&mut self, i: usize, j: usize, angle: f64) {
+        let dim = self.dim();
+
+        if dim >= 4 && i < dim && j < dim && i != j {
+            self.rotation.rotate(i, j, angle);
+        }
+    }
+}
+
+/// The system that rotates the view when the mouse is dragged while holding
+/// the modifier key. Dragging with the left button rotates the x and y axes
+/// towards the chosen axis, and dragging with the right button does the same
+/// for the z axis.
+fn add_rotation_input(
+    keyboard: Res<'_, Input<KeyCode>>,
+    mouse_button: Res<'_, Input<MouseButton>>,
+    mut mouse_move: EventReader<'_, '_, MouseMotion>,
+    windows: Res<'_, Windows>,
+    egui_ctx: Res<'_, EguiContext>,
+    mut view_rotation: ResMut<'_, ViewRotation>,
+) {
+    // Omit any events if the UI will process them instead.
+    if !modifier_pressed(&keyboard) || egui_ctx.ctx().wants_pointer_input() {
+        return;
+    }
+
+    let height = windows
+        .get_primary()
+        .expect("There is no primary window")
+        .physical_height() as f64;
+
+    // Dragging through the height of the window makes for a full turn.
+    let axis = view_rotation.axis;
+    for MouseMotion { delta } in mouse_move.iter() {
+        let dx = TAU * delta.x as f64 / height;
+        let dy = TAU * delta.y as f64 / height;
+
+        if mouse_button.pressed(MouseButton::Left) {
+            view_rotation.rotate(0, axis, dx);
+            view_rotation.rotate(1, axis, -dy);
+        } else if mouse_button.pressed(MouseButton::Right) {
+            view_rotation.rotate(2, axis, dx);
+        }
+    }
+}
+
+/// The system that rotates the view at a constant speed.
+fn animate_rotation(time: Res<'_, Time>, mut view_rotation: ResMut<'_, ViewRotation>) {
+    if view_rotation.animate {
+        let (i, j) = view_rotation.plane;
+        let angle = view_rotation.speed * time.delta_seconds_f64();
+        view_rotation.rotate(i, j, angle);
+    }
+}
+
+/// The system that projects the polytopes again whenever the view is rotated
+/// or the projection type changes, and colors them again if their colors depend
+/// on the view. Polytopes that were just changed have already been drawn with
+/// the new view, and their rotation is reset if their number of dimensions
+/// changed. The projection itself is only worked out again when the projection
+/// type changes, as the view rotates every frame while it's animated.
+fn reproject_polytopes(
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    mut polies: Query<
+        '_,
+        '_,
+        (
+            &Concrete,
+            ChangeTrackers<Concrete>,
+            &mut MeshVertices,
+            &Handle<Mesh>,
+            &Children,
+        ),
+    >,
+    wfs: Query<'_, '_, &Handle<Mesh>, Without<Concrete>>,
+    projection_type: Res<'_, ProjectionType>,
+    mut view_rotation: ResMut<'_, ViewRotation>,
+    coloring: Res<'_, FaceColoring>,
+) {
+    for (poly, poly_tracker, mut vertices, mesh_handle, children) in polies.iter_mut() {
+        let dim = poly.dim_or();
+        if view_rotation.dim() != dim {
+            view_rotation.reset(dim);
+        } else if !(view_rotation.is_changed() || projection_type.is_changed())
+            || poly_tracker.is_changed()
+        {
+            continue;
+        }
+
+        if projection_type.is_changed() {
+            vertices.projector = Projector::new(poly, &projection_type);
+        }
+
+        let rotation = view_rotation.rotation();
+        let mesh = meshes.get_mut(mesh_handle).unwrap();
+        reproject(
+            mesh,
+            poly,
+            &vertices.vertices,
+            &vertices.projector,
+            rotation,
+        );
+        recolor(mesh, poly, &vertices.vertices, &coloring, rotation);
+
+        // Updates all wireframes.
+        for child in children.iter() {
+            if let Ok(wf_handle) = wfs.get_component::<Handle<Mesh>>(*child) {
+                reproject(
+                    meshes.get_mut(wf_handle).unwrap(),
+                    poly,
+                    &vertices.vertices[..poly.vertex_count()],
+                    &vertices.projector,
+                    rotation,
+                );
+            }
+        }
+    }
+}
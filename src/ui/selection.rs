@@ -0,0 +1,576 @@
+//! Contains the methods to select elements of a polytope by clicking on them in
+//! the 3D view.
+//!
+//! Picking is done in screen space: the vertices of the polytope are projected
+//! down into 3D as for its mesh, and then onto the screen through the camera.
+//! Vertices and edges within a few pixels of the cursor are picked before the
+//! faces under it, unless they're hidden behind them.
+
+use super::{
+    camera::ProjectionType,
+    main_window::PolyName,
+    memory::{slot_label, Memory},
+    rotation::{self, ViewRotation},
+};
+use crate::mesh::{empty_mesh, MeshVertices, Projector, Triangulation};
+use crate::{Concrete, Float, Point};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{Camera, PerspectiveProjection},
+        mesh::Indices,
+        pipeline::PrimitiveTopology,
+    },
+};
+use bevy_egui::{egui::Ui, EguiContext};
+use miratope_core::{
+    abs::Ranked,
+    conc::{element_types::EL_SUFFIXES, ConcretePolytope},
+    geometry::Rotation,
+    Polytope,
+};
+use vec_like::VecLike;
+
+/// The greatest distance in pixels the mouse can move between being pressed
+/// and released for it to count as a click.
+const CLICK_TOLERANCE: f32 = 3.0;
+
+/// The greatest distance in pixels from the cursor to a vertex to pick it.
+const VERTEX_RADIUS: f32 = 8.0;
+
+/// The greatest distance in pixels from the cursor to an edge to pick it.
+const EDGE_RADIUS: f32 = 5.0;
+
+/// How far behind a face, as a proportion of its depth, a vertex or edge can
+/// be while still being picked over it. This lets us pick the vertices and
+/// edges of the faces in front.
+const DEPTH_TOLERANCE: f32 = 0.01;
+
+/// The proportion of the distance to the camera by which the highlight is
+/// moved towards it, so that it's drawn over the wireframe.
+const HIGHLIGHT_OFFSET: f32 = 0.005;
+
+/// The size of the cross that highlights a vertex, as a proportion of its
+/// distance to the camera.
+const CROSS_SIZE: f32 = 0.02;
+
+/// The plugin handling the selection of elements.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            // We register inputs after the library has been shown, so that we
+            // know whether mouse input should register.
+            .add_system(pick_element.system().after("show_library"))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_highlight
+                    .system()
+                    .after("update_changed_polytopes")
+                    .after("reproject_polytopes"),
+            );
+    }
+}
+
+/// Marks the entity whose mesh highlights the selected element.
+#[derive(Clone, Copy, Default)]
+pub struct SelectionHighlight;
+
+/// Returns the name of an element of a given rank, where vertices have rank 1.
+pub fn element_name(rank: usize) -> String {
+    match rank {
+        1 => "Vertex".to_string(),
+        2 => "Edge".to_string(),
+        3 => "Face".to_string(),
+        4 => "Cell".to_string(),
+        _ => format!("{}-element", rank - 1),
+    }
+}
+
+/// An element of the polytope that has been selected, together with the
+/// information about it shown in the right panel.
+#[derive(Clone, Debug)]
+pub struct SelectedElement {
+    /// The rank of the element, where vertices have rank 1.
+    pub rank: usize,
+
+    /// The index of the element.
+    pub idx: usize,
+
+    /// The index of the element type of the element.
+    pub element_type: usize,
+
+    /// The number of elements of the same type.
+    pub type_count: usize,
+
+    /// The measurements of the element, as they're shown.
+    pub measurements: Vec<String>,
+}
+
+impl SelectedElement {
+    /// Selects an element of a polytope, and takes its measurements.
+    pub fn new(poly: &Concrete, rank: usize, idx: usize) -> Self {
+        let mut poly = poly.clone();
+        poly.element_sort();
+
+        let types = poly.types_of_elements();
+        let element_type = types[(rank, idx)];
+        let type_count = types[rank].iter().filter(|&&t| t == element_type).count();
+
+        let mut measurements = Vec::new();
+        match rank {
+            1 => {
+                let vertex = &poly.vertices[idx];
+                let coords: Vec<_> = vertex.iter().map(|c| format!("{:.10}", c)).collect();
+                measurements.push(format!("coordinates ({})", coords.join(", ")));
+                measurements.push(format!("norm {:.10}", vertex.norm()));
+            }
+            2 => {
+                let edge = &poly[(2, idx)].subs;
+                let length = (&poly.vertices[edge[0]] - &poly.vertices[edge[1]]).norm();
+                measurements.push(format!("length {:.10}", length));
+            }
+            _ => {
+                if let Some(mut element) = poly.element(rank, idx) {
+                    let facets = poly[(rank, idx)].subs.len();
+                    let suffix = EL_SUFFIXES.get(rank).copied().unwrap_or("");
+                    measurements.push(format!("{}-{}", facets, suffix));
+
+                    if let Some(sphere) = element.circumsphere() {
+                        measurements.push(format!("radius {:.10}", sphere.radius()));
+                    }
+
+                    element.element_sort();
+                    if let Some(volume) = element.volume() {
+                        let name = if rank == 3 { "area" } else { "volume" };
+                        measurements.push(format!("{} {:.10}", name, volume));
+                    }
+                }
+            }
+        }
+
+        Self {
+            rank,
+            idx,
+            element_type,
+            type_count,
+            measurements,
+        }
+    }
+}
+
+/// The resource that stores the selected element, if any.
+#[derive(Default)]
+pub struct Selection(pub Option<SelectedElement>);
+
+/// Returns the distance from a point to a segment, along with the position of
+/// the closest point within the segment, between 0 and 1.
+fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> (f32, f32) {
+    let ab = b - a;
+    let len_sq = ab.length_squared();
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0)
+    };
+
+    (p.distance(a + ab * t), t)
+}
+
+/// Returns the barycentric coordinates of a point in a triangle, if it's
+/// inside of it.
+fn barycentric(p: Vec2, [a, b, c]: [Vec2; 3]) -> Option<[f32; 3]> {
+    let det = (b - a).perp_dot(c - a);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let u = (b - p).perp_dot(c - p) / det;
+    let v = (c - p).perp_dot(a - p) / det;
+    let w = 1.0 - u - v;
+    (u >= 0.0 && v >= 0.0 && w >= 0.0).then(|| [u, v, w])
+}
+
+/// Everything needed to project the vertices of a polytope onto the screen.
+struct ScreenProjection<'a> {
+    /// The polytope.
+    poly: &'a Concrete,
+
+    /// The projection of the polytope into 3D.
+    projector: &'a Projector,
+
+    /// The rotation of the view.
+    rotation: &'a Rotation<Float>,
+
+    /// The matrix that maps points in 3D into clip space.
+    clip: Mat4,
+
+    /// The size of the window.
+    size: Vec2,
+}
+
+impl<'a> ScreenProjection<'a> {
+    /// Projects some points onto the screen, and returns their position
+    /// together with their distance from the camera. Points behind the camera
+    /// are returned as `None`.
+    fn project<'b, I: Iterator<Item = &'b Point>>(&self, points: I) -> Vec<Option<(Vec2, f32)>> {
+        self.projector
+            .vertex_coords(self.poly, points, self.rotation)
+            .into_iter()
+            .map(|[x, y, z]| {
+                let clip = self.clip * Vec4::new(x, y, z, 1.0);
+                (clip.w > f32::EPSILON).then(|| {
+                    let ndc = Vec2::new(clip.x, clip.y) / clip.w;
+                    ((ndc + Vec2::ONE) / 2.0 * self.size, clip.w)
+                })
+            })
+            .collect()
+    }
+
+    /// Finds the face under the cursor that's closest to the camera, and
+    /// returns its index and depth, given the positions of the vertices of the
+    /// polytope on the screen.
+    fn pick_face(&self, cursor: Vec2, vertices: &[Option<(Vec2, f32)>]) -> Option<(usize, f32)> {
+        // Polygons don't have any faces besides themselves.
+        if self.poly.rank() < 4 {
+            return None;
+        }
+
+        let triangulation = Triangulation::new(self.poly);
+        let mut vertices = vertices.to_vec();
+        vertices.extend(self.project(triangulation.extra_vertices.iter()));
+
+        let mut best: Option<(usize, f32)> = None;
+        for (triangle, &face) in triangulation
+            .triangles
+            .chunks_exact(3)
+            .zip(&triangulation.faces)
+        {
+            let corners = match (
+                vertices[triangle[0] as usize],
+                vertices[triangle[1] as usize],
+                vertices[triangle[2] as usize],
+            ) {
+                (Some(a), Some(b), Some(c)) => [a, b, c],
+                _ => continue,
+            };
+
+            if let Some([u, v, w]) = barycentric(cursor, corners.map(|c| c.0)) {
+                let depth = u * corners[0].1 + v * corners[1].1 + w * corners[2].1;
+                if best.map_or(true, |(_, best_depth)| depth < best_depth) {
+                    best = Some((face, depth));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Finds the element under the cursor, and returns its rank and index.
+    fn pick(&self, cursor: Vec2, faces_visible: bool) -> Option<(usize, usize)> {
+        let vertices = self.project(self.poly.vertices.iter());
+        let face = faces_visible
+            .then(|| self.pick_face(cursor, &vertices))
+            .flatten();
+        let max_depth = face.map_or(f32::INFINITY, |(_, depth)| depth * (1.0 + DEPTH_TOLERANCE));
+
+        // Picks the vertex in front that's close enough to the cursor.
+        let vertex = vertices
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, v)| {
+                let (pos, depth) = (*v)?;
+                (pos.distance(cursor) <= VERTEX_RADIUS && depth <= max_depth).then(|| (idx, depth))
+            })
+            .min_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
+        if let Some((idx, _)) = vertex {
+            return Some((1, idx));
+        }
+
+        // Picks the edge in front that's close enough to the cursor.
+        let mut edge: Option<(usize, f32)> = None;
+        if let Some(edges) = self.poly.get_element_list(2) {
+            for (idx, el) in edges.iter().enumerate() {
+                let ((a, a_depth), (b, b_depth)) =
+                    match (vertices[el.subs[0]], vertices[el.subs[1]]) {
+                        (Some(a), Some(b)) => (a, b),
+                        _ => continue,
+                    };
+
+                let (dist, t) = segment_distance(cursor, a, b);
+                let depth = a_depth + (b_depth - a_depth) * t;
+                if dist <= EDGE_RADIUS
+                    && depth <= max_depth
+                    && edge.map_or(true, |(_, best_depth)| depth < best_depth)
+                {
+                    edge = Some((idx, depth));
+                }
+            }
+        }
+        if let Some((idx, _)) = edge {
+            return Some((2, idx));
+        }
+
+        face.map(|(idx, _)| (3, idx))
+    }
+}
+
+/// The system that selects the element under the cursor when the view is
+/// clicked. The selection is cleared whenever the polytope changes.
+#[allow(clippy::too_many_arguments)]
+fn pick_element(
+    keyboard: Res<'_, Input<KeyCode>>,
+    mouse_button: Res<'_, Input<MouseButton>>,
+    windows: Res<'_, Windows>,
+    egui_ctx: Res<'_, EguiContext>,
+    cameras: Query<'_, '_, (&Camera, &GlobalTransform), With<PerspectiveProjection>>,
+    polies: Query<'_, '_, (&Concrete, &MeshVertices, &GlobalTransform, &Visible)>,
+    changed: Query<'_, '_, (), Changed<Concrete>>,
+    view_rotation: Res<'_, ViewRotation>,
+    mut selection: ResMut<'_, Selection>,
+    mut press: Local<'_, Option<Vec2>>,
+) {
+    if changed.iter().next().is_some() && selection.0.is_some() {
+        selection.0 = None;
+    }
+
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let cursor = match window.cursor_position() {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    // Omit any clicks if the UI will process them instead, or if they'll
+    // rotate the view.
+    if mouse_button.just_pressed(MouseButton::Left) {
+        *press = (!egui_ctx.ctx().wants_pointer_input() && !rotation::modifier_pressed(&keyboard))
+            .then(|| cursor);
+        return;
+    }
+
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+    match press.take() {
+        Some(start) if start.distance(cursor) <= CLICK_TOLERANCE => {}
+        _ => return,
+    }
+
+    let (camera, cam_gtf) = match cameras.iter().next() {
+        Some(camera) => camera,
+        None => return,
+    };
+    let view_proj = camera.projection_matrix * cam_gtf.compute_matrix().inverse();
+
+    for (poly, vertices, poly_gtf, visible) in polies.iter() {
+        let screen = ScreenProjection {
+            poly,
+            projector: &vertices.projector,
+            rotation: view_rotation.rotation(),
+            clip: view_proj * poly_gtf.compute_matrix(),
+            size: Vec2::new(window.width(), window.height()),
+        };
+
+        selection.0 = screen
+            .pick(cursor, visible.is_visible)
+            .map(|(rank, idx)| SelectedElement::new(poly, rank, idx));
+    }
+}
+
+/// Returns the indices of the edges of an element of a polytope.
+fn element_edges(poly: &Concrete, rank: usize, idx: usize) -> Vec<usize> {
+    let mut elements = vec![idx];
+
+    for r in (3..=rank).rev() {
+        elements = elements
+            .iter()
+            .flat_map(|&i| poly[(r, i)].subs.iter().copied())
+            .collect();
+        elements.sort_unstable();
+        elements.dedup();
+    }
+
+    elements
+}
+
+/// Builds the mesh that highlights an element of a polytope. A vertex gets
+/// highlighted by a small cross, and any other element by its edges.
+fn highlight_mesh(
+    poly: &Concrete,
+    element: &SelectedElement,
+    projector: &Projector,
+    rotation: &Rotation<Float>,
+    camera: Vec3,
+) -> Mesh {
+    let (mut positions, indices): (Vec<Vec3>, Vec<u32>) = if element.rank == 1 {
+        let [x, y, z] =
+            projector.vertex_coords(poly, std::iter::once(&poly.vertices[element.idx]), rotation)
+                [0];
+        let p = Vec3::new(x, y, z);
+        let size = CROSS_SIZE * p.distance(camera);
+
+        (
+            [Vec3::X, Vec3::Y, Vec3::Z]
+                .iter()
+                .flat_map(|&axis| [p - axis * size, p + axis * size])
+                .collect(),
+            (0..6).collect(),
+        )
+    } else {
+        let edges = element_edges(poly, element.rank, element.idx);
+        let points: Vec<_> = edges
+            .iter()
+            .flat_map(|&edge| poly[(2, edge)].subs.iter().map(|&v| &poly.vertices[v]))
+            .collect();
+
+        (
+            projector
+                .vertex_coords(poly, points.into_iter(), rotation)
+                .into_iter()
+                .map(Vec3::from)
+                .collect(),
+            (0..2 * edges.len() as u32).collect(),
+        )
+    };
+
+    // Moves the highlight towards the camera.
+    for p in &mut positions {
+        *p += (camera - *p) * HIGHLIGHT_OFFSET;
+    }
+
+    let len = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.set_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0; 3]; len]);
+    mesh.set_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        positions
+            .into_iter()
+            .map(<[f32; 3]>::from)
+            .collect::<Vec<_>>(),
+    );
+    mesh.set_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0; 2]; len]);
+    mesh.set_attribute(Mesh::ATTRIBUTE_COLOR, vec![[1.0; 4]; len]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+
+    mesh
+}
+
+/// The system that updates the highlight of the selected element whenever it
+/// changes, or whenever the view or the camera changes.
+#[allow(clippy::too_many_arguments)]
+fn update_highlight(
+    mut meshes: ResMut<'_, Assets<Mesh>>,
+    polies: Query<'_, '_, (&Concrete, &MeshVertices)>,
+    highlights: Query<'_, '_, &Handle<Mesh>, With<SelectionHighlight>>,
+    cameras: Query<'_, '_, &GlobalTransform, With<PerspectiveProjection>>,
+    moved: Query<'_, '_, (), (With<PerspectiveProjection>, Changed<GlobalTransform>)>,
+    selection: Res<'_, Selection>,
+    projection_type: Res<'_, ProjectionType>,
+    view_rotation: Res<'_, ViewRotation>,
+) {
+    if !(selection.is_changed()
+        || projection_type.is_changed()
+        || view_rotation.is_changed()
+        || moved.iter().next().is_some())
+    {
+        return;
+    }
+
+    let camera = cameras
+        .iter()
+        .next()
+        .map_or(Vec3::ZERO, |gtf| gtf.translation);
+
+    let mesh = match (&selection.0, polies.iter().next()) {
+        (Some(element), Some((poly, vertices))) => highlight_mesh(
+            poly,
+            element,
+            &vertices.projector,
+            view_rotation.rotation(),
+            camera,
+        ),
+        _ => empty_mesh(),
+    };
+
+    for handle in highlights.iter() {
+        *meshes.get_mut(handle).unwrap() = mesh.clone();
+    }
+}
+
+/// Shows the selected element in the right panel, along with the buttons to
+/// extract it or its figure into memory.
+pub fn show_selection(
+    ui: &mut Ui,
+    selection: &mut ResMut<'_, Selection>,
+    poly: Option<&Concrete>,
+    poly_name: &PolyName,
+    memory: &mut ResMut<'_, Memory>,
+) {
+    let (element, poly) = match (&selection.0, poly) {
+        (Some(element), Some(poly)) => (element, poly),
+        _ => {
+            ui.label("Click on a vertex, edge or face to select it.");
+            return;
+        }
+    };
+
+    let (rank, idx) = (element.rank, element.idx);
+    ui.label(format!(
+        "{} {} (rank {})",
+        element_name(rank),
+        idx,
+        rank - 1
+    ));
+    ui.label(format!(
+        "type {}, shared by {} element{}",
+        element.element_type,
+        element.type_count,
+        if element.type_count == 1 { "" } else { "s" }
+    ));
+    for measurement in &element.measurements {
+        ui.label(measurement.as_str());
+    }
+
+    let mut clear = false;
+    ui.horizontal(|ui| {
+        // Extracts the element into memory.
+        if ui.button("Extract element").clicked() {
+            match poly.element(rank, idx) {
+                Some(mut element) => {
+                    element.flatten();
+                    element.recenter();
+                    memory.push((element, Some(format!("Element of {}", poly_name.0).into())));
+                    println!("Element saved to {}.", slot_label(memory.len() - 1));
+                }
+                None => eprintln!("Element failed: no element at rank {}, index {}", rank, idx),
+            }
+        }
+
+        // Extracts the element figure into memory.
+        if ui.button("Extract figure").clicked() {
+            match poly.element_fig(rank, idx) {
+                Ok(Some(mut figure)) => {
+                    figure.flatten();
+                    figure.recenter();
+                    memory.push((figure, Some(format!("Figure of {}", poly_name.0).into())));
+                    println!("Figure saved to {}.", slot_label(memory.len() - 1));
+                }
+                Ok(None) => eprintln!("Figure failed: no element at rank {}, index {}", rank, idx),
+                Err(err) => eprintln!("Figure failed: {}", err),
+            }
+        }
+
+        if ui.button("Clear").clicked() {
+            clear = true;
+        }
+    });
+
+    if clear {
+        selection.0 = None;
+    }
+}
@@ -1,13 +1,13 @@
 //! Contains all code related to the top bar.
 
-use std::path::PathBuf;
+use std::{ffi::OsStr, path::PathBuf};
 
-use super::{camera::ProjectionType, memory::Memory, window::{Window, *}, UnitPointWidget, main_window::PolyName};
-use crate::{Concrete, Float, Hyperplane, Point, Vector};
+use super::{camera::ProjectionType, faceting::FacetingJob, rotation::ViewRotation, memory::Memory, window::{Window, *}, UnitPointWidget, main_window::PolyName};
+use crate::{export::{self, ExportOptions}, Concrete, Float, Hyperplane, Point, Vector};
 
-use bevy::prelude::*;
+use bevy::{prelude::*, tasks::AsyncComputeTaskPool};
 use bevy_egui::{egui::{self, menu, Ui}, EguiContext};
-use miratope_core::{conc::{ConcretePolytope, faceting::GroupEnum, symmetry::Vertices}, file::FromFile, float::Float as Float2, Polytope, abs::Ranked};
+use miratope_core::{conc::{ConcretePolytope, faceting::GroupEnum, symmetry::Vertices}, file::{off::OffOptions, FromFile}, float::Float as Float2, name::Name, Polytope, abs::Ranked};
 
 /// The plugin in charge of everything on the top panel.
 pub struct TopPanelPlugin;
@@ -21,8 +21,10 @@ impl Plugin for TopPanelPlugin {
             .init_resource::<ShowMemory>()
             .init_resource::<ShowHelp>()
             .init_resource::<ExportMemory>()
+            .init_resource::<LoadError>()
             .init_non_send_resource::<FileDialogToken>()
             .add_system(file_dialog.system())
+            .add_system(show_load_error.system())
             // Windows must be the first thing shown.
             .add_system(
                 show_top_panel
@@ -41,7 +43,7 @@ pub enum SectionState {
         original_polytope: Concrete,
 
         /// The name of the polytope.
-        original_name: String,
+        original_name: Name,
 
         /// The range of the slider.
         minmax: Vec<(Float, Float)>,
@@ -90,7 +92,7 @@ impl SectionState {
 		}
     }
 
-    pub fn open(&mut self, original_polytope: Concrete, name: String, minmax: Vec<(f64, f64)>) {
+    pub fn open(&mut self, original_polytope: Concrete, name: Name, minmax: Vec<(f64, f64)>) {
         *self = SectionState::Active {
             original_polytope,
             original_name: name,
@@ -164,6 +166,11 @@ impl Default for ShowHelp {
     }
 }
 
+/// Stores the error message from the last file that failed to load, if it
+/// hasn't been dismissed yet.
+#[derive(Default)]
+pub struct LoadError(pub Option<String>);
+
 /// Stores whether we're exporting the memory and the index of the memory slot.
 pub struct ExportMemory(bool, usize);
 
@@ -186,6 +193,7 @@ impl FileDialogToken {
     fn new_file_dialog() -> rfd::FileDialog {
         rfd::FileDialog::new()
             .add_filter("OFF File", &["off"])
+            .add_filter("GeoGebra File", &["ggb"])
     }
 
     /// Returns the path given by an open file dialog.
@@ -193,9 +201,22 @@ impl FileDialogToken {
         Self::new_file_dialog().pick_file()
     }
 
-    /// Returns the path given by a save file dialog.
+    /// Returns the path given by a save file dialog. Polytopes may also be
+    /// saved as GGB files.
     fn save_file(&self, name: &str) -> Option<PathBuf> {
-        Self::new_file_dialog().set_file_name(name).save_file()
+        Self::new_file_dialog()
+            .set_file_name(name)
+            .save_file()
+    }
+
+    /// Returns the path given by a save file dialog for an exported mesh.
+    fn export_file(&self, name: &str, options: &ExportOptions) -> Option<PathBuf> {
+        let extension = options.format.extension();
+
+        rfd::FileDialog::new()
+            .add_filter(&options.format.to_string(), &[extension])
+            .set_file_name(&format!("{}.{}", name, extension))
+            .save_file()
     }
 }
 
@@ -207,8 +228,15 @@ enum FileDialogMode {
     /// We're showing a file dialog to open a file.
     Open,
 
-    /// We're showing a file dialog to save a file.
-    Save,
+    /// We're showing a file dialog to save a file. OFF files may be saved
+    /// with colored faces, as Stella4D reads them.
+    Save {
+        /// Whether to write the colors of the faces.
+        colors: bool,
+    },
+
+    /// We're showing a file dialog to export the mesh of the polytope.
+    Export(ExportOptions),
 }
 
 /// The file dialog is disabled by default.
@@ -237,7 +265,21 @@ impl FileDialogState {
     /// Changes the file dialog mode to [`FileDialogMode::Save`], and loads the
     /// name of the file.
     pub fn save(&mut self, name: String) {
-        self.mode = FileDialogMode::Save;
+        self.mode = FileDialogMode::Save { colors: false };
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::Save`] with colored
+    /// faces, and loads the name of the file.
+    pub fn save_stella(&mut self, name: String) {
+        self.mode = FileDialogMode::Save { colors: true };
+        self.name = Some(name);
+    }
+
+    /// Changes the file dialog mode to [`FileDialogMode::Export`], and loads
+    /// the name of the file.
+    pub fn export(&mut self, name: String, options: ExportOptions) {
+        self.mode = FileDialogMode::Export(options);
         self.name = Some(name);
     }
 
@@ -253,31 +295,73 @@ pub fn file_dialog(
     mut name: ResMut<'_, PolyName>,
     file_dialog_state: Res<'_, FileDialogState>,
     file_dialog: NonSend<'_, FileDialogToken>,
+    mut load_error: ResMut<'_, LoadError>,
+    projection_type: Res<'_, ProjectionType>,
+    view_rotation: Res<'_, ViewRotation>,
 ) {
     if file_dialog_state.is_changed() {
         match file_dialog_state.mode {
             // We want to save a file.
-            FileDialogMode::Save => {
+            FileDialogMode::Save { colors } => {
                 if let Some(path) = file_dialog.save_file(file_dialog_state.unwrap_name()) {
                     if let Some(p) = query.iter_mut().next() {
-                        if let Err(err) = p.con().to_path(&path, Default::default()) {
+                        let result = if path.extension() == Some(OsStr::new("ggb")) {
+                            p.con().to_ggb_path(&path).map_err(|err| err.to_string())
+                        } else {
+                            let opt = OffOptions {
+                                name: Some(name.0.clone()),
+                                colors,
+                                ..Default::default()
+                            };
+
+                            p.con().to_path(&path, opt).map_err(|err| err.to_string())
+                        };
+
+                        if let Err(err) = result {
                             eprintln!("File saving failed: {}", err);
                         }
                     }
                 }
             }
 
+            // We want to export the mesh of the polytope.
+            FileDialogMode::Export(options) => {
+                let name = file_dialog_state.unwrap_name();
+
+                if let Some(path) = file_dialog.export_file(name, &options) {
+                    if let Some(p) = query.iter_mut().next() {
+                        let result = export::export(
+                            &p,
+                            name,
+                            &projection_type,
+                            view_rotation.rotation(),
+                            &options,
+                            &path,
+                        );
+
+                        if let Err(err) = result {
+                            eprintln!("Export failed: {}", err);
+                        }
+                    }
+                }
+            }
+
             // We want to open a file.
             FileDialogMode::Open => {
                 if let Some(path) = file_dialog.pick_file() {
                     if let Some(mut p) = query.iter_mut().next() {
-                        match Concrete::from_path(&path) {
+                        match Concrete::from_path_with(&path, true) {
                             Ok(q) => {
                                 *p = q;
-                                let file_name = path.file_name().unwrap().to_str().unwrap();
-                                name.0 = file_name[..file_name.len()-4].into();
+                                name.0 = Concrete::name_from_off(&path).unwrap_or_else(|| {
+                                    let file_name = path.file_name().unwrap().to_str().unwrap();
+                                    file_name[..file_name.len()-4].into()
+                                });
+                            }
+                            Err(err) => {
+                                eprintln!("File open failed: {}", err);
+                                load_error.0 = Some(err.to_string());
                             }
-                            Err(err) => eprintln!("File open failed: {}", err),
                         }
                     }
                 }
@@ -289,6 +373,28 @@ pub fn file_dialog(
     }
 }
 
+/// The system that shows an error dialog whenever a file fails to load.
+pub fn show_load_error(egui_ctx: Res<'_, EguiContext>, mut load_error: ResMut<'_, LoadError>) {
+    let mut close = false;
+
+    if let Some(err) = &load_error.0 {
+        egui::Window::new("File open failed")
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_ctx.ctx(), |ui| {
+                ui.label(err);
+
+                if ui.button("Ok").clicked() {
+                    close = true;
+                }
+            });
+    }
+
+    if close {
+        load_error.0 = None;
+    }
+}
+
 /// Whether the hotkey to enable "advanced" options is enabled.
 pub fn advanced(keyboard: &Input<KeyCode>) -> bool {
     keyboard.pressed(KeyCode::LControl) || keyboard.pressed(KeyCode::RControl)
@@ -309,10 +415,16 @@ pub type EguiWindows<'a> = (
     ResMut<'a, CompoundWindow>), // Workaround for an argument count limit
     ResMut<'a, TruncateWindow>,
     ResMut<'a, ScaleWindow>,
+    ResMut<'a, WythoffWindow>,
+    ResMut<'a, ExpressionWindow>,
     ResMut<'a, FacetingSettings>,
 	ResMut<'a, RotateWindow>,
 	ResMut<'a, PlaneWindow>,
     ResMut<'a, WikiWindow>,
+    ResMut<'a, ExportWindow>,
+    ResMut<'a, RotationWindow>,
+    ResMut<'a, ProjectionWindow>,
+    ResMut<'a, CoxeterPlaneWindow>,
 );
 
 macro_rules! element_sort {
@@ -345,6 +457,9 @@ pub fn show_top_panel(
 
     mut visuals: ResMut<'_, egui::Visuals>,
 
+    // The faceting running in the background.
+    (mut faceting_job, task_pool): (ResMut<'_, FacetingJob>, Res<'_, AsyncComputeTaskPool>),
+
     // The different windows that can be shown.
     (
         (mut dual_window,
@@ -360,10 +475,16 @@ pub fn show_top_panel(
         mut compound_window),
         mut truncate_window,
         mut scale_window,
+        mut wythoff_window,
+        mut expression_window,
         mut faceting_settings,
 		mut rotate_window,
 		mut plane_window,
         mut wiki_window,
+        mut export_window,
+        mut rotation_window,
+        mut projection_window,
+        mut coxeter_plane_window,
     ): EguiWindows<'_>,
 ) {
     // The top bar.
@@ -379,7 +500,17 @@ pub fn show_top_panel(
 
                 // Saves a file.
                 if ui.button("Save").clicked() {
-                    file_dialog_state.save(poly_name.0.clone());
+                    file_dialog_state.save(poly_name.0.to_string());
+                }
+
+                // Saves an OFF file with colored faces for Stella4D.
+                if ui.button("Save for Stella4D").clicked() {
+                    file_dialog_state.save_stella(poly_name.0.to_string());
+                }
+
+                // Exports the mesh of the polytope.
+                if ui.button("Export mesh...").clicked() {
+                    export_window.open();
                 }
 
                 if ui.button("Export all memory slots").clicked() {
@@ -405,14 +536,14 @@ pub fn show_top_panel(
                     if let Some((poly, label)) = &memory[idx] {
                         if let Some(mut p) = query.iter_mut().next() {
                             *p = poly.clone();
-                            let name = match label {
+                            let name: Name = match label {
                                 None => {
-                                    format!("polytope {}", idx)
+                                    format!("polytope {}", idx).into()
                                 }
-                                Some(a) => a.to_string()
+                                Some(a) => a.clone()
                             };
-                            poly_name.0 = name.clone();
-                            file_dialog_state.save(name);
+                            file_dialog_state.save(name.to_string());
+                            poly_name.0 = name;
                         }
                     }
                     export_memory.1 += 1;
@@ -421,16 +552,41 @@ pub fn show_top_panel(
 
             // Configures the view.
             menu::menu(ui, "View", |ui| {
-                let mut checked = projection_type.is_orthogonal();
-
-                if ui.checkbox(&mut checked, "Orthogonal projection").clicked() {
-                    projection_type.flip();
+                let dim = query.iter_mut().next().map_or(0, |p| p.dim_or());
+
+                // Chooses how polytopes are projected into 3D. The polytopes
+                // are updated when the projection type changes.
+                for new_type in [
+                    ProjectionType::Orthogonal,
+                    ProjectionType::Perspective,
+                    ProjectionType::Schlegel { facet: 0 },
+                    ProjectionType::Stereographic,
+                    ProjectionType::subspace(dim),
+                ] {
+                    let selected = projection_type.same_kind(&new_type);
+
+                    if ui.radio(selected, new_type.name()).clicked() {
+                        if new_type.has_settings() {
+                            projection_window.open();
+                        }
 
-                    // Forces an update on all polytopes.
-                    if let Some(mut p) = query.iter_mut().next() {
-                        p.set_changed();
+                        if !selected {
+                            *projection_type = new_type;
+                        }
                     }
                 }
+
+                // Projects onto the plane of a symmetry.
+                if ui.button("Coxeter plane...").clicked() {
+                    coxeter_plane_window.open();
+                }
+
+                ui.separator();
+
+                // Rotates polytopes in four or more dimensions.
+                if ui.button("Rotation...").clicked() {
+                    rotation_window.open();
+                }
             });
 
             // Prints out properties about the loaded polytope.
@@ -522,6 +678,50 @@ pub fn show_top_panel(
 						}
                     }
                 }
+
+                // Gets if it is strongly connected.
+                if ui.button("Is strongly connected").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        match p.ranks().is_strongly_connected() {
+                            Ok(()) => println!("The polytope is strongly connected."),
+                            Err(err) => println!("The polytope is not strongly connected: {}", err),
+                        }
+                    }
+                }
+
+                // Classifies the polytope by its symmetry.
+                if ui.button("Classify").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        match p.classify() {
+                            Some(classification) => println!("The polytope is {}.", classification),
+                            None => println!("Classification failed: the symmetry group couldn't be computed."),
+                        }
+                    }
+                }
+
+                // Computes the combinatorial automorphism group.
+                if ui.button("Automorphism group").clicked() {
+                    if let Some(p) = query.iter_mut().next() {
+                        let group = p.abs.automorphism_group();
+                        println!(
+                            "The automorphism group has order {}, with {} flag orbits.",
+                            group.order(),
+                            group.flag_orbit_count()
+                        );
+
+                        if group.is_regular() {
+                            println!("The polytope is abstractly regular.");
+                        } else if group.is_chiral() {
+                            println!("The polytope is chiral.");
+                        } else if let Some(class) = group.two_orbit_class() {
+                            let class: Vec<_> = class.iter().map(ToString::to_string).collect();
+                            println!(
+                                "The polytope is a two-orbit polytope of class 2_{{{}}}.",
+                                class.join(",")
+                            );
+                        }
+                    }
+                }
             });
 
             menu::menu(ui, "Transform", |ui| {
@@ -590,7 +790,7 @@ pub fn show_top_panel(
                     if ui.button("Dual").clicked() {
                         match p.try_dual_mut() {
                             Ok(_) => {
-                                poly_name.0 = format!("Dual of {}", poly_name.0);
+                                poly_name.0 = poly_name.0.clone().dual();
                                 println!("Dual succeeded.")
                             },
                             Err(err) => eprintln!("Dual failed: {}", err),
@@ -604,7 +804,7 @@ pub fn show_top_panel(
                 if ui.button("Petrial").clicked() {
                     if let Some(mut p) = query.iter_mut().next() {
                         if p.petrial_mut() {
-                            poly_name.0 = format!("Petrial of {}", poly_name.0);
+                            poly_name.0 = poly_name.0.clone().petrial();
                             println!("Petrial succeeded.");
                         } else {
                             eprintln!("Petrial failed.");
@@ -620,7 +820,7 @@ pub fn show_top_panel(
                         match p.petrie_polygon_with(flag) {
                             Some(q) => {
                                 *p = q;
-                                poly_name.0 = format!("Petrie polygon of {}", poly_name.0);
+                                poly_name.0 = format!("Petrie polygon of {}", poly_name.0).into();
                                 println!("Petrie polygon succeeded.")
                             }
                             None => eprintln!("Petrie polygon failed."),
@@ -638,7 +838,7 @@ pub fn show_top_panel(
                 } else if let Some(mut p) = query.iter_mut().next() {
                     if ui.button("Pyramid").clicked() {
                         *p = p.pyramid();
-                        poly_name.0 = format!("Pyramid of {}", poly_name.0);
+                        poly_name.0 = poly_name.0.clone().pyramid();
                     }
                 }
 
@@ -650,7 +850,7 @@ pub fn show_top_panel(
                 } else if let Some(mut p) = query.iter_mut().next() {
                     if ui.button("Prism").clicked() {
                         *p = p.prism();
-                        poly_name.0 = format!("Prism of {}", poly_name.0);
+                        poly_name.0 = poly_name.0.clone().prism();
                     }
                 }
 
@@ -662,7 +862,7 @@ pub fn show_top_panel(
                 } else if let Some(mut p) = query.iter_mut().next() {
                     if ui.button("Tegum").clicked() {
                         *p = p.tegum();
-                        poly_name.0 = format!("Tegum of {}", poly_name.0);
+                        poly_name.0 = poly_name.0.clone().tegum();
                     }
                 }
 
@@ -676,7 +876,7 @@ pub fn show_top_panel(
                         match p.try_antiprism() {
                             Ok(q) => {
                                 *p = q;
-                                poly_name.0 = format!("Antiprism of {}", poly_name.0);
+                                poly_name.0 = poly_name.0.clone().antiprism();
                             },
                             Err(err) => eprintln!("Antiprism failed: {}", err),
                         }
@@ -687,7 +887,7 @@ pub fn show_top_panel(
                 if ui.button("Ditope").clicked() {
                     if let Some(mut p) = query.iter_mut().next() {
                         p.ditope_mut();
-                        poly_name.0 = format!("Ditope of {}", poly_name.0);
+                        poly_name.0 = format!("Ditope of {}", poly_name.0).into();
                         println!("Ditope succeeded!");
                     }
                 }
@@ -696,7 +896,7 @@ pub fn show_top_panel(
                 if ui.button("Hosotope").clicked() {
                     if let Some(mut p) = query.iter_mut().next() {
                         p.hosotope_mut();
-                        poly_name.0 = format!("Hosotope of {}", poly_name.0);
+                        poly_name.0 = format!("Hosotope of {}", poly_name.0).into();
                         println!("Hosotope succeeded!");
                     }
                 }
@@ -738,9 +938,47 @@ pub fn show_top_panel(
                 if ui.button("Truncate...").clicked() {
                     truncate_window.open();
                 }
+
+                // Opens the window to build a polytope from a Coxeter diagram.
+                if ui.button("Wythoffian...").clicked() {
+                    wythoff_window.open();
+                }
+
+                // Opens the window to build a polytope from an expression.
+                if ui.button("Expression...").clicked() {
+                    expression_window.open();
+                }
 				
                 ui.separator();
 
+                // Converts the active polytope into the convex hull of its
+                // vertices.
+                if ui.button("Convex hull").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        *p = p.hull();
+                        poly_name.0 = format!("Convex hull of {}", poly_name.0).into();
+                        println!("Convex hull succeeded!");
+                    }
+                }
+
+                // Alternates the active polytope, if its vertices can be
+                // split into two classes with no edges within them.
+                if ui.button("Alternate").clicked() {
+                    if let Some(mut p) = query.iter_mut().next() {
+                        match p.alternation_vertices() {
+                            Some(vertices) => match p.alternate(&vertices) {
+                                Some(alternate) => {
+                                    *p = alternate;
+                                    poly_name.0 = format!("Alternated {}", poly_name.0).into();
+                                    println!("Alternation succeeded!");
+                                }
+                                None => println!("Alternation failed: the result isn't a valid polytope."),
+                            },
+                            None => println!("Alternation failed: the polytope has odd cycles of edges."),
+                        }
+                    }
+                }
+
                 if ui.button("Identify coplanar facets").clicked() {
                     if let Some(mut p) = query.iter_mut().next() {
                         *p = p.fuse_facets();
@@ -791,13 +1029,16 @@ pub fn show_top_panel(
             }
 
             menu::menu(ui, "Faceting", |ui| {
-                if ui.button("Enumerate facetings").clicked() {
+                // Only one faceting can run at a time.
+                if ui.add(egui::Button::new("Enumerate facetings").enabled(!faceting_job.is_running())).clicked() {
                     if let Some(p) = query.iter_mut().next() {
                         let mut vertices_thing = (Vertices(vec![]), vec![]);
                         if let GroupEnum2::FromSlot(slot) = faceting_settings.group {
                             vertices_thing = Vertices(p.vertices.clone()).copy_by_symmetry(slot.to_poly(&mut memory, &p).unwrap().clone().get_symmetry_group().unwrap().0);
                         }
-                        let facetings = p.clone().faceting(
+                        faceting_job.start(
+                            &task_pool,
+                            p.clone(),
                             match faceting_settings.group {
                                 GroupEnum2::Chiral(_) => p.vertices.clone(),
                                 GroupEnum2::FromSlot(_) => vertices_thing.0.0
@@ -806,27 +1047,8 @@ pub fn show_top_panel(
                                 GroupEnum2::Chiral(chiral) => GroupEnum::Chiral(chiral),
                                 GroupEnum2::FromSlot(_) => GroupEnum::VertexMap(vertices_thing.1)
                             },
-                            faceting_settings.any_single_edge_length,
-                            if faceting_settings.do_min_edge_length {Some(faceting_settings.min_edge_length)} else {None}, 
-                            if faceting_settings.do_max_edge_length {Some(faceting_settings.max_edge_length)} else {None}, 
-                            if faceting_settings.do_min_inradius {Some(faceting_settings.min_inradius)} else {None}, 
-                            if faceting_settings.do_max_inradius {Some(faceting_settings.max_inradius)} else {None}, 
-                            faceting_settings.exclude_hemis,
-                            faceting_settings.only_below_vertex,
-                            if faceting_settings.max_facet_types == 0 {None} else {Some(faceting_settings.max_facet_types)},
-                            if faceting_settings.max_per_hyperplane == 0 {None} else {Some(faceting_settings.max_per_hyperplane)},
-                            faceting_settings.uniform,
-                            faceting_settings.compounds,
-                            faceting_settings.mark_fissary,
-                            faceting_settings.label_facets,
-                            faceting_settings.save,
-                            faceting_settings.save_facets,
-                            faceting_settings.save_to_file,
-                            faceting_settings.file_path.clone(),
+                            &faceting_settings,
                         );
-                        for faceting in facetings {
-                            memory.push(faceting);
-                        }
                     }
                 }
                 
@@ -858,13 +1080,13 @@ pub fn show_top_panel(
                     ui.label("V: toggle faces\nB: toggle wireframe");
                     ui.separator();
                     ui.heading("Camera");
-                    ui.label("WSADRF: move\nQE: roll\nX: reset\nMouse wheel: zoom\nHold Ctrl: move faster\nHold Shift: move slower");
+                    ui.label("WSADRF: move\nQE: roll\nX: reset\nMouse wheel: zoom\nHold Ctrl: move faster\nHold Shift: move slower\nClick: select a vertex, edge or face");
                     ui.separator();
                     ui.heading("UI");
                     ui.label("Hold Ctrl: extra options in some menus\nHold Shift: move number sliders slower");
                     ui.separator();
                     ui.heading("Right panel");
-                    ui.label("Generate: computes the element types of the loaded polytope\nLoad: loads the polytope whose element types are being listed");
+                    ui.label("Generate: computes the element types of the loaded polytope\nLoad: loads the polytope whose element types are being listed\nExtract element, Extract figure: saves the selected element or its figure to memory");
                     ui.separator();
                     ui.heading("Wiki");
                     ui.label("Use the checkboxes to choose which fields to generate\nPress Generate to fill in the fields\nPress Ok to generate a copyable page");
@@ -1084,7 +1306,7 @@ fn show_views(
 				*p = r;
             }
 
-            poly_name.0 = format!("Slice of {}", original_name);
+            poly_name.0 = format!("Slice of {}", original_name).into();
         }
     }
 }
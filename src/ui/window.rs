@@ -3,17 +3,21 @@
 //! All windows are l&mut &mut oaded in parallel, before the top panel and the library are
 //! shown on screen.
 
-use std::{marker::PhantomData, cmp::min, collections::HashMap};
+use std::{marker::PhantomData, cmp::min, collections::HashMap, fs, path::PathBuf, sync::atomic::{AtomicBool, Ordering}};
 
 use super::{
+    camera::{CameraInputEvent, ProjectionType},
+    config::Config,
     memory::{slot_label, Memory},
     PointWidget,
     wiki::{WikiArticle, LinkType, InfoboxField}, right_panel::ElementTypesRes,
+    rotation::{axis_name, ViewRotation},
+    top_panel::FileDialogState,
 };
-use crate::{Concrete, Float, Hypersphere, Point, ui::{main_window::PolyName, wiki::WikiElement}};
+use crate::{Concrete, Float, Hypersphere, Point, Vector, export::{ExportFormat, ExportOptions, Wireframe}, ui::{main_window::PolyName, wiki::WikiElement}};
 
 use egui::TextEdit;
-use miratope_core::{conc::{ConcretePolytope, element_types::EL_NAMES}, Polytope, abs::Ranked};
+use miratope_core::{conc::{ConcretePolytope, element_types::EL_NAMES, faceting::FacetingOptions}, cox::cd::Cd, expr::Expr, group::coxeter_plane::CoxeterPlane, file::jenn::Jenn, name::Name, Polytope, abs::Ranked};
 
 use bevy::prelude::*;
 use bevy_egui::{
@@ -67,6 +71,12 @@ impl Plugin for WindowPlugin {
             .add_plugin(CompoundWindow::plugin())
             .add_plugin(TruncateWindow::plugin())
             .add_plugin(ScaleWindow::plugin())
+            .add_plugin(WythoffWindow::plugin())
+            .add_plugin(ExpressionWindow::plugin())
+            .add_plugin(ExportWindow::plugin())
+            .add_plugin(RotationWindow::plugin())
+            .add_plugin(ProjectionWindow::plugin())
+            .add_plugin(CoxeterPlaneWindow::plugin())
             .add_plugin(FacetingSettings::plugin())
 			.add_plugin(RotateWindow::plugin())
 			.add_plugin(PlaneWindow::plugin())
@@ -189,7 +199,7 @@ pub trait PlainWindow: Window {
     fn action(&self, polytope: &mut Concrete);
 
     /// Applies an action to the polytope name.
-    fn name_action(&self, name: &mut String);
+    fn name_action(&self, name: &mut Name);
 
     /// Builds the window to be shown on screen.
     fn build(&mut self, ui: &mut Ui);
@@ -226,7 +236,7 @@ pub trait UpdateWindow: Window {
     fn action(&self, polytope: &mut Concrete);
     
     /// Applies an action to the polytope name.
-    fn name_action(&self, name: &mut String);
+    fn name_action(&self, name: &mut Name);
 
     /// Builds the window to be shown on screen.
     fn build(&mut self, ui: &mut Ui);
@@ -421,7 +431,7 @@ pub trait DuoWindow: Window {
     }
 
     /// Applies an action to the polytope name.
-    fn name_action(&self, name: &mut String, memory: &Memory);
+    fn name_action(&self, name: &mut Name, memory: &Memory);
 
     /// Builds the window to be shown on screen.
     fn build(&mut self, _: &mut Ui, _: &Concrete, _: &Memory) {}
@@ -600,8 +610,8 @@ impl UpdateWindow for DualWindow {
         }
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Dual of {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = name.clone().dual();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -674,8 +684,8 @@ impl UpdateWindow for PyramidWindow {
         *polytope = polytope.pyramid_with(self.offset.push(self.height));
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Pyramid of {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = name.clone().pyramid();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -735,8 +745,8 @@ impl PlainWindow for PrismWindow {
         *polytope = polytope.prism_with(self.height);
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Prism of {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = name.clone().prism();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -808,8 +818,8 @@ impl UpdateWindow for TegumWindow {
         );
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Tegum of {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = name.clone().tegum();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -898,8 +908,8 @@ impl UpdateWindow for AntiprismWindow {
         }
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Dual of {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = name.clone().antiprism();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -988,25 +998,25 @@ impl DuoWindow for DuopyramidWindow {
         Concrete::duopyramid_with(p, q, p_offset, q_offset, self.height)
     }
 
-    fn name_action(&self, name: &mut String, memory: &Memory) {
+    fn name_action(&self, name: &mut Name, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
         let name_b = match self.slots[1] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
 
-        *name = format!("Duopyramid of ({}, {})", name_a, name_b);
+        *name = Name::Duopyramid(vec![name_a, name_b]);
     }
 
     fn slots(&self) -> [Slot; 2] {
@@ -1107,25 +1117,25 @@ impl DuoWindow for DuoprismWindow {
         p.duoprism(q)
     }
 
-    fn name_action(&self, name: &mut String, memory: &Memory) {
+    fn name_action(&self, name: &mut Name, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
         let name_b = match self.slots[1] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
 
-        *name = format!("Duoprism of ({}, {})", name_a, name_b);
+        *name = Name::Duoprism(vec![name_a, name_b]);
     }
 
     fn slots(&self) -> [Slot; 2] {
@@ -1178,25 +1188,25 @@ impl DuoWindow for DuotegumWindow {
         Concrete::duotegum_with(p, q, p_offset, q_offset)
     }
 
-    fn name_action(&self, name: &mut String, memory: &Memory) {
+    fn name_action(&self, name: &mut Name, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
         let name_b = match self.slots[1] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
 
-        *name = format!("Duotegum of ({}, {})", name_a, name_b);
+        *name = Name::Duotegum(vec![name_a, name_b]);
     }
 
     fn slots(&self) -> [Slot; 2] {
@@ -1246,25 +1256,25 @@ impl DuoWindow for DuocombWindow {
         p.duocomb(q)
     }
 
-    fn name_action(&self, name: &mut String, memory: &Memory) {
+    fn name_action(&self, name: &mut Name, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
         let name_b = match self.slots[1] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
 
-        *name = format!("Comb of ({}, {})", name_a, name_b);
+        *name = Name::Duocomb(vec![name_a, name_b]);
     }
 
     fn slots(&self) -> [Slot; 2] {
@@ -1304,25 +1314,25 @@ impl DuoWindow for StarWindow {
         p.star_product(q)
     }
 
-    fn name_action(&self, name: &mut String, memory: &Memory) {
+    fn name_action(&self, name: &mut Name, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
         let name_b = match self.slots[1] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
 
-        *name = format!("Star of ({}, {})", name_a, name_b);
+        *name = format!("Star of ({}, {})", name_a, name_b).into();
     }
 
     fn slots(&self) -> [Slot; 2] {
@@ -1364,25 +1374,25 @@ impl DuoWindow for CompoundWindow {
         p2
     }
 
-    fn name_action(&self, name: &mut String, memory: &Memory) {
+    fn name_action(&self, name: &mut Name, memory: &Memory) {
         let name_a = match self.slots[0] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
         let name_b = match self.slots[1] {
             Slot::Loaded => name.clone(),
             Slot::Memory(i) => match &memory[i].as_ref().unwrap().1 {
-                Some(label) => label.to_string(),
-                None => format!("polytope {}", i),
+                Some(label) => label.clone(),
+                None => slot_label(i).into(),
             },
-            Slot::None => "".to_string(),
+            Slot::None => "".into(),
         };
 
-        *name = format!("Compound of ({}, {})", name_a, name_b);
+        *name = Name::Compound(vec![(1, name_a), (1, name_b)]);
     }
 
     fn slots(&self) -> [Slot; 2] {
@@ -1434,8 +1444,8 @@ impl UpdateWindow for TruncateWindow {
         *polytope = polytope.truncate_with(rings, self.depth.clone());
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Truncated {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = format!("Truncated {}", name).into();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -1494,7 +1504,7 @@ impl PlainWindow for ScaleWindow {
         polytope.scale(self.scale);
     }
 
-    fn name_action(&self, _name: &mut String) {}
+    fn name_action(&self, _name: &mut Name) {}
 
     fn build(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
@@ -1506,6 +1516,796 @@ impl PlainWindow for ScaleWindow {
     }
 }
 
+/// Allows the user to build a Wythoffian polytope from its Coxeter diagram, or
+/// from the parameters Jenn3D takes, like `-c 5 2 2 3 2 3 -v 1 0 0 0`.
+pub struct WythoffWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The Coxeter diagram or the Jenn3D parameters, as typed by the user.
+    cd: String,
+}
+
+impl WythoffWindow {
+    /// Parses the diagram, or the Jenn3D parameters if the input starts with
+    /// an option or the name of the program.
+    fn diagram(&self) -> Result<Cd, String> {
+        let input = self.cd.trim_start();
+
+        if input.starts_with('-') || input.starts_with("jenn") {
+            Jenn::parse(input)
+                .and_then(|jenn| jenn.cd())
+                .map_err(|err| err.to_string())
+        } else {
+            Cd::parse(input).map_err(|err| err.to_string())
+        }
+    }
+
+    /// Parses the diagram and builds the polytope, or returns an error message
+    /// explaining why this can't be done.
+    fn polytope(&self) -> Result<Concrete, String> {
+        Concrete::from_cd(&self.diagram()?)
+            .ok_or_else(|| "the Coxeter group is not spherical".to_string())
+    }
+}
+
+impl Window for WythoffWindow {
+    const NAME: &'static str = "Wythoffian";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for WythoffWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        match self.polytope() {
+            Ok(poly) => {
+                *polytope = poly;
+                println!("Wythoffian succeeded!");
+            }
+            Err(err) => eprintln!("Wythoffian failed: {}", err),
+        }
+    }
+
+    fn name_action(&self, name: &mut Name) {
+        // Building the polytope again just for its name would be too slow.
+        if let Ok(cd) = self.diagram() {
+            if cd.cox().inner_normals().is_some() {
+                *name = self.cd.clone().into();
+            }
+        }
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Coxeter diagram:");
+            ui.add(TextEdit::singleline(&mut self.cd).desired_width(150.));
+        });
+
+        // Only parses the diagram, as building the polytope every frame would
+        // be too slow.
+        match self.diagram() {
+            Ok(cd) => {
+                if let Some(jenn) = Jenn::from_cd(&cd) {
+                    ui.label(format!("Jenn3D: {}", jenn));
+                }
+            }
+            Err(err) => {
+                ui.colored_label(egui::Color32::RED, format!("Invalid diagram: {}", err));
+            }
+        }
+    }
+}
+
+impl Default for WythoffWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            cd: "x4o3o".to_string(),
+        }
+    }
+}
+
+/// Allows the user to build a polytope by typing an expression that composes
+/// operations, like `dual(prism(star(5, 2))) x cube`.
+pub struct ExpressionWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The expression, as typed by the user.
+    expr: String,
+
+    /// Whether the expression was evaluated successfully the last time Ok
+    /// was pressed, so that the polytope is only renamed if it was.
+    succeeded: AtomicBool,
+}
+
+impl Window for ExpressionWindow {
+    const NAME: &'static str = "Expression";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl PlainWindow for ExpressionWindow {
+    fn action(&self, polytope: &mut Concrete) {
+        match Concrete::from_expr(&self.expr) {
+            Ok(poly) => {
+                *polytope = poly;
+                self.succeeded.store(true, Ordering::Relaxed);
+                println!("Expression succeeded!");
+            }
+            Err(err) => eprintln!("Expression failed: {}", err),
+        }
+    }
+
+    fn name_action(&self, name: &mut Name) {
+        if self.succeeded.swap(false, Ordering::Relaxed) {
+            *name = self.expr.trim().to_string().into();
+        }
+    }
+
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Expression:");
+            ui.add(TextEdit::singleline(&mut self.expr).desired_width(250.));
+        });
+
+        // Only parses the expression, as evaluating it every frame would be
+        // too slow.
+        if let Err(err) = Expr::parse(&self.expr) {
+            ui.colored_label(egui::Color32::RED, format!("Invalid expression: {}", err));
+        }
+
+        ui.label("Products: x (prism), + (tegum), ∨ (pyramid), □ (comb)");
+        ui.label("Atoms: \"path/to/file.off\", [x4o3o]");
+    }
+}
+
+impl Default for ExpressionWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            expr: "dual(prism(star(5, 2))) x cube".to_string(),
+            succeeded: AtomicBool::new(false),
+        }
+    }
+}
+
+/// A window that lets the user choose how to export the mesh of the polytope.
+/// Pressing Ok opens a save file dialog.
+pub struct ExportWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The format of the exported file.
+    format: ExportFormat,
+
+    /// Whether the faces are exported.
+    faces: bool,
+
+    /// Whether the edges and vertices are exported as solids.
+    wireframe: bool,
+
+    /// The settings for exporting the edges and vertices as solids.
+    wireframe_settings: Wireframe,
+}
+
+impl Default for ExportWindow {
+    fn default() -> Self {
+        let options = ExportOptions::default();
+
+        Self {
+            open: false,
+            format: options.format,
+            faces: options.faces,
+            wireframe: options.wireframe.is_some(),
+            wireframe_settings: Default::default(),
+        }
+    }
+}
+
+impl Window for ExportWindow {
+    const NAME: &'static str = "Export";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl ExportWindow {
+    /// Returns the options for exporting the mesh.
+    fn options(&self) -> ExportOptions {
+        ExportOptions {
+            format: self.format,
+            faces: self.faces,
+            wireframe: self.wireframe.then(|| self.wireframe_settings),
+        }
+    }
+
+    /// Builds the window to be shown on screen.
+    fn build(&mut self, ui: &mut Ui) {
+        egui::ComboBox::from_label("Format")
+            .selected_text(self.format.to_string())
+            .show_ui(ui, |ui| {
+                for format in ExportFormat::ALL {
+                    ui.selectable_value(&mut self.format, format, format.to_string());
+                }
+            });
+
+        ui.checkbox(&mut self.faces, "Faces");
+        ui.checkbox(&mut self.wireframe, "Edges as cylinders");
+
+        if self.wireframe {
+            let settings = &mut self.wireframe_settings;
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.edge_radius)
+                        .speed(0.001)
+                        .clamp_range(0.0..=f32::MAX),
+                );
+                ui.label("Edge radius");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.vertex_radius)
+                        .speed(0.001)
+                        .clamp_range(0.0..=f32::MAX),
+                );
+                ui.label("Vertex radius");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut settings.sides)
+                        .speed(0.05)
+                        .clamp_range(3..=64),
+                );
+                ui.label("Sides");
+            });
+        }
+
+        if !self.faces && !self.wireframe {
+            ui.colored_label(egui::Color32::RED, "Nothing will be exported.");
+        }
+    }
+
+    /// Resets the window.
+    fn reset(&mut self) {
+        *self = Self::default();
+        self.open = true;
+    }
+
+    /// Shows the window on screen.
+    fn show(&mut self, ctx: &CtxRef) -> ShowResult {
+        let mut open = self.is_open();
+        let mut result = ShowResult::None;
+
+        egui::Window::new(Self::NAME)
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.build(ui);
+                ui.add(OkReset::new(&mut result));
+            });
+
+        if open {
+            self.open();
+            result
+        } else {
+            ShowResult::Close
+        }
+    }
+
+    /// The system that shows the window.
+    fn show_system(
+        mut self_: ResMut<'_, Self>,
+        egui_ctx: Res<'_, EguiContext>,
+        poly_name: Res<'_, PolyName>,
+        mut file_dialog_state: ResMut<'_, FileDialogState>,
+    ) {
+        match self_.show(egui_ctx.ctx()) {
+            ShowResult::Ok => {
+                file_dialog_state.export(poly_name.0.to_string(), self_.options());
+                self_.close();
+            }
+            ShowResult::Close => self_.close(),
+            ShowResult::Reset => self_.reset(),
+            ShowResult::None => {}
+        }
+    }
+
+    /// A plugin that adds a resource of type `Self` and the system to show it.
+    fn plugin() -> ExportWindowPlugin {
+        Default::default()
+    }
+}
+
+/// A plugin that adds all of the necessary systems for an [`ExportWindow`].
+#[derive(Default)]
+pub struct ExportWindowPlugin(PhantomData<ExportWindow>);
+
+impl Plugin for ExportWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportWindow>()
+            .add_system(ExportWindow::show_system.system().label("show_windows"));
+    }
+}
+
+/// A window that lets the user choose how to rotate the view of polytopes in
+/// four or more dimensions. Its settings take effect immediately.
+#[derive(Default)]
+pub struct RotationWindow {
+    /// Whether the window is open.
+    open: bool,
+}
+
+impl Window for RotationWindow {
+    const NAME: &'static str = "Rotation";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl RotationWindow {
+    /// Shows a combo box to choose a coordinate axis, starting from a given
+    /// one.
+    fn axis_combo(ui: &mut Ui, label: &str, axis: &mut usize, axes: std::ops::Range<usize>) {
+        egui::ComboBox::from_label(label)
+            .selected_text(axis_name(*axis))
+            .show_ui(ui, |ui| {
+                for i in axes {
+                    ui.selectable_value(axis, i, axis_name(i));
+                }
+            });
+    }
+
+    /// Builds the window to be shown on screen. We only write back the
+    /// settings that change, so that the view isn't redrawn every frame.
+    fn build(ui: &mut Ui, view_rotation: &mut ResMut<'_, ViewRotation>) {
+        let dim = view_rotation.dim();
+        if dim < 4 {
+            ui.label("Rotations are only available in four or more dimensions.");
+            return;
+        }
+
+        ui.label("Hold Alt and drag to rotate the view.");
+        let mut axis = view_rotation.axis;
+        Self::axis_combo(ui, "Drag towards", &mut axis, 3..dim);
+        if axis != view_rotation.axis {
+            view_rotation.axis = axis;
+        }
+
+        ui.separator();
+
+        let mut animate = view_rotation.animate;
+        ui.checkbox(&mut animate, "Animate");
+        if animate != view_rotation.animate {
+            view_rotation.animate = animate;
+        }
+
+        let (mut i, mut j) = view_rotation.plane;
+        ui.horizontal(|ui| {
+            Self::axis_combo(ui, "", &mut i, 0..dim);
+            Self::axis_combo(ui, "Plane", &mut j, 0..dim);
+        });
+        if i == j {
+            ui.colored_label(egui::Color32::RED, "The axes must be different.");
+        }
+        if (i, j) != view_rotation.plane {
+            view_rotation.plane = (i, j);
+        }
+
+        let mut speed = view_rotation.speed;
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut speed).speed(0.01));
+            ui.label("Speed (rad/s)");
+        });
+        if speed != view_rotation.speed {
+            view_rotation.speed = speed;
+        }
+
+        ui.separator();
+
+        if ui.button("Reset rotation").clicked() {
+            view_rotation.reset(dim);
+        }
+    }
+
+    /// The system that shows the window.
+    fn show_system(
+        mut self_: ResMut<'_, Self>,
+        egui_ctx: Res<'_, EguiContext>,
+        mut view_rotation: ResMut<'_, ViewRotation>,
+    ) {
+        let mut open = self_.is_open();
+
+        egui::Window::new(Self::NAME)
+            .open(&mut open)
+            .resizable(false)
+            .show(egui_ctx.ctx(), |ui| {
+                Self::build(ui, &mut view_rotation);
+            });
+
+        if !open && self_.is_open() {
+            self_.close();
+        }
+    }
+
+    /// A plugin that adds a resource of type `Self` and the system to show it.
+    fn plugin() -> RotationWindowPlugin {
+        Default::default()
+    }
+}
+
+/// A plugin that adds all of the necessary systems for a [`RotationWindow`].
+#[derive(Default)]
+pub struct RotationWindowPlugin(PhantomData<RotationWindow>);
+
+impl Plugin for RotationWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RotationWindow>()
+            .add_system(RotationWindow::show_system.system().label("show_windows"));
+    }
+}
+
+/// A window that lets the user set up the projection of polytopes into 3D. Its
+/// settings take effect immediately.
+#[derive(Default)]
+pub struct ProjectionWindow {
+    /// Whether the window is open.
+    open: bool,
+}
+
+impl Window for ProjectionWindow {
+    const NAME: &'static str = "Projection";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl ProjectionWindow {
+    /// Builds the window to be shown on screen. We only write back the
+    /// projection type if it changes, so that the view isn't redrawn every
+    /// frame.
+    fn build(ui: &mut Ui, projection_type: &mut ResMut<'_, ProjectionType>, poly: &Concrete) {
+        let dim = poly.dim_or();
+        let mut new_type = (**projection_type).clone();
+
+        ui.label(new_type.name());
+        match &mut new_type {
+            ProjectionType::Schlegel { facet } => {
+                let facet_count = poly.facet_count();
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::DragValue::new(facet)
+                            .speed(0.1)
+                            .clamp_range(0..=facet_count.saturating_sub(1)),
+                    );
+                    ui.label(format!("Facet (out of {})", facet_count));
+                });
+            }
+
+            ProjectionType::Subspace { basis } => {
+                if basis.iter().any(|v| v.len() != dim) {
+                    new_type = ProjectionType::subspace(dim);
+                }
+
+                if let ProjectionType::Subspace { basis } = &mut new_type {
+                    for (axis, v) in basis.iter_mut().enumerate() {
+                        ui.add(PointWidget::new(v, axis_name(axis)));
+                    }
+                }
+
+                if ui.button("Reset to axes").clicked() {
+                    new_type = ProjectionType::subspace(dim);
+                }
+            }
+
+            _ => {
+                ui.label("This projection has no settings.");
+            }
+        }
+
+        if new_type != **projection_type {
+            **projection_type = new_type;
+        }
+    }
+
+    /// The system that shows the window.
+    fn show_system(
+        mut self_: ResMut<'_, Self>,
+        egui_ctx: Res<'_, EguiContext>,
+        mut projection_type: ResMut<'_, ProjectionType>,
+        query: Query<'_, '_, &Concrete>,
+    ) {
+        let mut open = self_.is_open();
+
+        if let Some(poly) = query.iter().next() {
+            egui::Window::new(Self::NAME)
+                .open(&mut open)
+                .resizable(false)
+                .show(egui_ctx.ctx(), |ui| {
+                    Self::build(ui, &mut projection_type, poly);
+                });
+        }
+
+        if !open && self_.is_open() {
+            self_.close();
+        }
+    }
+
+    /// A plugin that adds a resource of type `Self` and the system to show it.
+    fn plugin() -> ProjectionWindowPlugin {
+        Default::default()
+    }
+}
+
+/// A plugin that adds all of the necessary systems for a [`ProjectionWindow`].
+#[derive(Default)]
+pub struct ProjectionWindowPlugin(PhantomData<ProjectionWindow>);
+
+impl Plugin for ProjectionWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProjectionWindow>()
+            .add_system(ProjectionWindow::show_system.system().label("show_windows"));
+    }
+}
+
+/// The group element whose plane a [`CoxeterPlaneWindow`] projects onto.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlaneElement {
+    /// A Coxeter element, which gives the Coxeter plane.
+    Coxeter,
+
+    /// Any element of a given order.
+    Order,
+
+    /// A product of the generators of a Coxeter group.
+    Product,
+}
+
+/// A window that projects the polytope onto the plane in which some element
+/// of a symmetry group acts as a rotation, like its Coxeter plane.
+pub struct CoxeterPlaneWindow {
+    /// Whether the window is open.
+    open: bool,
+
+    /// The Coxeter diagram of the symmetry group, or an empty string if the
+    /// symmetry group of the polytope is used.
+    cd: String,
+
+    /// The group element whose plane we project onto.
+    element: PlaneElement,
+
+    /// The order of the element, if we look for an element of a given order.
+    order: u32,
+
+    /// The indices of the generators whose product is the element, separated
+    /// by spaces, if we use such a product.
+    word: String,
+}
+
+impl Default for CoxeterPlaneWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            cd: String::new(),
+            element: PlaneElement::Coxeter,
+            order: 6,
+            word: "0 1 2 3".to_string(),
+        }
+    }
+}
+
+impl Window for CoxeterPlaneWindow {
+    const NAME: &'static str = "Coxeter plane";
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn is_open_mut(&mut self) -> &mut bool {
+        &mut self.open
+    }
+}
+
+impl CoxeterPlaneWindow {
+    /// Finds the plane of the chosen group element, or returns an error message
+    /// explaining why this can't be done.
+    fn plane(&self, polytope: &mut Concrete) -> Result<CoxeterPlane, String> {
+        let plane = if self.cd.trim().is_empty() {
+            let group = polytope
+                .get_symmetry_group()
+                .ok_or_else(|| "could not compute the symmetry group".to_string())?
+                .0;
+
+            match self.element {
+                PlaneElement::Coxeter => CoxeterPlane::from_group(group),
+                PlaneElement::Order => CoxeterPlane::with_order(group, self.order),
+                PlaneElement::Product => {
+                    return Err("products of generators need a Coxeter diagram".to_string())
+                }
+            }
+        } else {
+            let cox = Cd::parse(&self.cd).map_err(|err| err.to_string())?.cox();
+
+            match self.element {
+                PlaneElement::Coxeter => cox.coxeter_plane(),
+                PlaneElement::Order => cox
+                    .group()
+                    .and_then(|group| CoxeterPlane::with_order(group, self.order)),
+                PlaneElement::Product => {
+                    let word = self
+                        .word
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<Vec<usize>, _>>()
+                        .map_err(|err| err.to_string())?;
+
+                    cox.product(&word).and_then(CoxeterPlane::new)
+                }
+            }
+        };
+
+        plane.ok_or_else(|| "no group element rotates any plane in this way".to_string())
+    }
+
+    /// Builds the window to be shown on screen.
+    fn build(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Coxeter diagram:");
+            ui.add(TextEdit::singleline(&mut self.cd).desired_width(150.));
+        });
+
+        if self.cd.trim().is_empty() {
+            ui.label("The symmetry group of the polytope will be used.");
+        } else if let Err(err) = Cd::parse(&self.cd) {
+            ui.colored_label(egui::Color32::RED, format!("Invalid diagram: {}", err));
+        }
+
+        ui.separator();
+
+        ui.radio_value(&mut self.element, PlaneElement::Coxeter, "Coxeter element");
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.element, PlaneElement::Order, "Element of order");
+            ui.add(
+                egui::DragValue::new(&mut self.order)
+                    .speed(0.05)
+                    .clamp_range(3..=u32::MAX),
+            );
+        });
+
+        ui.horizontal(|ui| {
+            ui.radio_value(
+                &mut self.element,
+                PlaneElement::Product,
+                "Product of generators",
+            );
+            ui.add(TextEdit::singleline(&mut self.word).desired_width(100.));
+        });
+    }
+
+    /// Resets the window.
+    fn reset(&mut self) {
+        *self = Self::default();
+        self.open = true;
+    }
+
+    /// Shows the window on screen.
+    fn show(&mut self, ctx: &CtxRef) -> ShowResult {
+        let mut open = self.is_open();
+        let mut result = ShowResult::None;
+
+        egui::Window::new(Self::NAME)
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.build(ui);
+                ui.add(OkReset::new(&mut result));
+            });
+
+        if open {
+            self.open();
+            result
+        } else {
+            ShowResult::Close
+        }
+    }
+
+    /// The system that shows the window. Pressing Ok projects the polytope
+    /// orthogonally onto the plane, and resets the view so that the plane is
+    /// facing the camera.
+    fn show_system(
+        mut self_: ResMut<'_, Self>,
+        egui_ctx: Res<'_, EguiContext>,
+        mut query: Query<'_, '_, &mut Concrete>,
+        mut projection_type: ResMut<'_, ProjectionType>,
+        mut view_rotation: ResMut<'_, ViewRotation>,
+        mut cam_inputs: EventWriter<'_, '_, CameraInputEvent>,
+    ) {
+        match self_.show(egui_ctx.ctx()) {
+            ShowResult::Ok => {
+                if let Some(mut p) = query.iter_mut().next() {
+                    let dim = p.dim_or();
+
+                    match self_.plane(&mut p) {
+                        Ok(plane) if plane.dim() == dim => {
+                            let [u, v] = plane.basis;
+                            *projection_type = ProjectionType::Subspace {
+                                basis: vec![u, v, Vector::zeros(dim)],
+                            };
+
+                            view_rotation.reset(dim);
+                            cam_inputs.send(CameraInputEvent::Reset);
+                            println!(
+                                "Projected onto the plane of an element of order {}.",
+                                plane.order
+                            );
+                        }
+                        Ok(plane) => eprintln!(
+                            "Coxeter plane failed: the group acts on {} dimensions, but the polytope has {}.",
+                            plane.dim(),
+                            dim
+                        ),
+                        Err(err) => eprintln!("Coxeter plane failed: {}", err),
+                    }
+                }
+
+                self_.close();
+            }
+            ShowResult::Close => self_.close(),
+            ShowResult::Reset => self_.reset(),
+            ShowResult::None => {}
+        }
+    }
+
+    /// A plugin that adds a resource of type `Self` and the system to show it.
+    fn plugin() -> CoxeterPlaneWindowPlugin {
+        Default::default()
+    }
+}
+
+/// A plugin that adds all of the necessary systems for a
+/// [`CoxeterPlaneWindow`].
+#[derive(Default)]
+pub struct CoxeterPlaneWindowPlugin(PhantomData<CoxeterPlaneWindow>);
+
+impl Plugin for CoxeterPlaneWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CoxeterPlaneWindow>()
+            .add_system(CoxeterPlaneWindow::show_system.system().label("show_windows"));
+    }
+}
+
 /// Where to get the symmetry group for faceting
 #[derive(PartialEq)]
 pub enum GroupEnum2 {
@@ -1527,6 +2327,9 @@ pub struct FacetingSettings {
     /// The slot for the dropdown menu.
     slot: Slot,
 
+    /// The name under which to save the settings as a preset.
+    preset_name: String,
+
     /// The maximum number of facet types considered. 1 for isotopic, 0 for no limit.
     pub max_facet_types: usize,
 
@@ -1594,6 +2397,12 @@ pub struct FacetingSettings {
 
     /// The path to save to, if saving to file.
     pub file_path: String,
+
+    /// The number of facetings after which to stop. 0 for no limit.
+    pub max_results: usize,
+
+    /// Whether to save checkpoints in the file path, and resume from them.
+    pub checkpoint: bool,
 }
 
 impl Default for FacetingSettings {
@@ -1602,6 +2411,7 @@ impl Default for FacetingSettings {
             open: false,
             show_advanced_settings: false,
             slot: Slot::default(),
+            preset_name: String::new(),
             max_facet_types: 0,
             max_per_hyperplane: 0,
             group: GroupEnum2::Chiral(false),
@@ -1624,6 +2434,162 @@ impl Default for FacetingSettings {
             save_facets: false,
             save_to_file: false,
             file_path: "".to_string(),
+            max_results: 0,
+            checkpoint: false,
+        }
+    }
+}
+
+/// The name of the folder within the configuration directory where the
+/// faceting presets are stored.
+const PRESET_DIR: &str = "faceting presets";
+
+/// The name of the file within the file path where the faceting checkpoints
+/// are saved.
+const CHECKPOINT_FILE: &str = "faceting checkpoint.ron";
+
+impl FacetingSettings {
+    /// Returns the options for the faceting algorithm.
+    pub fn options(&self) -> FacetingOptions {
+        FacetingOptions::new()
+            .with_any_single_edge_length(self.any_single_edge_length)
+            .with_edge_lengths(
+                self.do_min_edge_length.then(|| self.min_edge_length),
+                self.do_max_edge_length.then(|| self.max_edge_length),
+            )
+            .with_inradii(
+                self.do_min_inradius.then(|| self.min_inradius),
+                self.do_max_inradius.then(|| self.max_inradius),
+            )
+            .with_exclude_hemis(self.exclude_hemis)
+            .with_only_below_vertex(self.only_below_vertex)
+            .with_noble((self.max_facet_types != 0).then(|| self.max_facet_types))
+            .with_max_per_hyperplane((self.max_per_hyperplane != 0).then(|| self.max_per_hyperplane))
+            .with_uniform(self.uniform)
+            .with_compounds(self.compounds)
+            .with_mark_fissary(self.mark_fissary)
+            .with_label_facets(self.label_facets)
+            .with_save(self.save, self.save_facets)
+            .with_save_to_file(self.save_to_file.then(|| PathBuf::from(&self.file_path)))
+            .with_max_results((self.max_results != 0).then(|| self.max_results))
+            .with_checkpoint(self.checkpoint.then(|| PathBuf::from(&self.file_path).join(CHECKPOINT_FILE)))
+    }
+
+    /// Sets the settings from the options for the faceting algorithm. The
+    /// values of any disabled bounds are kept.
+    pub fn set_options(&mut self, options: &FacetingOptions) {
+        fn set_bound(enabled: &mut bool, value: &mut f64, bound: Option<f64>) {
+            *enabled = bound.is_some();
+            if let Some(bound) = bound {
+                *value = bound;
+            }
+        }
+
+        self.any_single_edge_length = options.any_single_edge_length;
+        set_bound(&mut self.do_min_edge_length, &mut self.min_edge_length, options.min_edge_length);
+        set_bound(&mut self.do_max_edge_length, &mut self.max_edge_length, options.max_edge_length);
+        set_bound(&mut self.do_min_inradius, &mut self.min_inradius, options.min_inradius);
+        set_bound(&mut self.do_max_inradius, &mut self.max_inradius, options.max_inradius);
+        self.exclude_hemis = options.exclude_hemis;
+        self.only_below_vertex = options.only_below_vertex;
+        self.max_facet_types = options.noble.unwrap_or_default();
+        self.max_per_hyperplane = options.max_per_hyperplane.unwrap_or_default();
+        self.uniform = options.uniform;
+        self.compounds = options.include_compounds;
+        self.mark_fissary = options.mark_fissary;
+        self.label_facets = options.label_facets;
+        self.save = options.save;
+        self.save_facets = options.save_facets;
+        self.save_to_file = options.save_to_file.is_some();
+        if let Some(path) = &options.save_to_file {
+            self.file_path = path.to_string_lossy().into_owned();
+        }
+        self.max_results = options.max_results.unwrap_or_default();
+        self.checkpoint = options.checkpoint.is_some();
+    }
+
+    /// Returns whether a preset name can be used as a file name within the
+    /// preset folder. Names can't be empty, can't contain path separators or
+    /// other characters that some systems don't allow in file names, and
+    /// can't refer to a folder.
+    fn is_valid_preset_name(name: &str) -> bool {
+        const FORBIDDEN: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+        !name.is_empty()
+            && name != "."
+            && name != ".."
+            && !name.chars().any(|c| c.is_control() || FORBIDDEN.contains(&c))
+    }
+
+    /// Returns the path to the preset with a given name, or `None` if the name
+    /// isn't valid.
+    fn preset_path(name: &str) -> Option<PathBuf> {
+        Self::is_valid_preset_name(name).then(|| {
+            let mut path = Config::config_dir();
+            path.push(PRESET_DIR);
+            path.push(format!("{}.ron", name));
+            path
+        })
+    }
+
+    /// Returns the names of all saved presets, in alphabetical order.
+    fn presets() -> Vec<String> {
+        let mut dir = Config::config_dir();
+        dir.push(PRESET_DIR);
+
+        let mut presets: Vec<_> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? == "ron" {
+                    Some(path.file_stem()?.to_string_lossy().into_owned())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        presets.sort();
+        presets
+    }
+
+    /// Saves the current settings as a preset.
+    fn save_preset(&self) {
+        let path = match Self::preset_path(&self.preset_name) {
+            Some(path) => path,
+            None => {
+                eprintln!("Invalid preset name: {}", self.preset_name);
+                return;
+            }
+        };
+
+        if let Some(dir) = path.parent() {
+            if let Err(err) = fs::create_dir_all(dir) {
+                eprintln!("Could not create the preset directory: {}", err);
+                return;
+            }
+        }
+
+        match fs::write(&path, ron::to_string(&self.options()).unwrap()) {
+            Ok(_) => println!("Saved preset {}!", self.preset_name),
+            Err(err) => eprintln!("Could not save the preset: {}", err),
+        }
+    }
+
+    /// Loads the settings from a preset.
+    fn load_preset(&mut self, name: &str) {
+        let options = Self::preset_path(name)
+            .ok_or_else(|| String::from("invalid name"))
+            .and_then(|path| fs::read_to_string(path).map_err(|err| err.to_string()))
+            .and_then(|preset| ron::from_str(&preset).map_err(|err| err.to_string()));
+
+        match options {
+            Ok(options) => {
+                self.set_options(&options);
+                self.preset_name = name.to_string();
+            }
+            Err(err) => eprintln!("Could not load the preset {}: {}", name, err),
         }
     }
 }
@@ -1830,6 +2796,54 @@ impl MemoryWindow for FacetingSettings {
             );
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Stop after");
+            ui.add(
+                egui::DragValue::new(&mut self.max_results)
+                    .speed(0.1)
+                    .clamp_range(0..=usize::MAX)
+            );
+            ui.label("facetings (0 for no limit)");
+        });
+
+        ui.add(
+            egui::Checkbox::new(&mut self.checkpoint, "Save checkpoints to the path, and resume from them")
+        );
+
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Preset:");
+            ui.add(egui::TextEdit::singleline(&mut self.preset_name).desired_width(120.0));
+
+            let valid = Self::is_valid_preset_name(&self.preset_name);
+            if ui.add(Button::new("Save").enabled(valid)).clicked() {
+                self.save_preset();
+            }
+
+            let mut load = None;
+            egui::ComboBox::from_id_source("faceting_presets")
+                .selected_text("Load")
+                .show_ui(ui, |ui| {
+                    for preset in Self::presets() {
+                        if ui.selectable_label(false, preset.as_str()).clicked() {
+                            load = Some(preset);
+                        }
+                    }
+                });
+
+            if let Some(preset) = load {
+                self.load_preset(&preset);
+            }
+        });
+
+        if !self.preset_name.is_empty() && !Self::is_valid_preset_name(&self.preset_name) {
+            ui.colored_label(
+                egui::Color32::RED,
+                "Preset names can't be . or .., nor contain any of / \\ : * ? \" < > |",
+            );
+        }
+
         ui.separator();
 
         if ui.button(if self.show_advanced_settings {"Hide advanced settings"} else {"Show advanced settings"}).clicked() {
@@ -1905,8 +2919,8 @@ impl UpdateWindow for RotateWindow {
 		}
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Rotated {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = format!("Rotated {}", name).into();
     }
 
     fn build(&mut self, ui: &mut Ui) {
@@ -2085,8 +3099,8 @@ impl UpdateWindow for PlaneWindow {
 	
     }
 
-    fn name_action(&self, name: &mut String) {
-        *name = format!("Rotated {}", name);
+    fn name_action(&self, name: &mut Name) {
+        *name = format!("Rotated {}", name).into();
     }
 
     fn build(&mut self, ui: &mut Ui) {